@@ -0,0 +1,117 @@
+use super::Field;
+
+/// Provides a list of header fields decoded from a single header block.
+///
+/// The fields are kept in the order of their appearance in the block. The
+/// block can be iterated as a whole or separately by the pseudo-header fields,
+/// whose names start with the `:` character, and the regular header fields.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::Decoder;
+///
+/// let mut decoder = Decoder::default();
+/// let mut buf = vec![0x80 | 2, 0x80 | 16]; // :method, accept-encoding
+/// let block = decoder.decode_block(&mut buf).unwrap();
+/// assert_eq!(block.pseudo_headers().count(), 1);
+/// assert_eq!(block.regular_headers().count(), 1);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DecodedBlock {
+    /// A list of decoded header fields.
+    fields: Vec<Field>,
+}
+
+impl DecodedBlock {
+    /// Returns the total number of decoded header fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns `true` if the block holds no header fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns an iterator over all the header fields.
+    pub fn iter(&self) -> std::slice::Iter<'_, Field> {
+        self.fields.iter()
+    }
+
+    /// Returns an iterator over the pseudo-header fields.
+    pub fn pseudo_headers(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|(name, _, _)| name.starts_with(b":"))
+    }
+
+    /// Returns an iterator over the regular header fields.
+    pub fn regular_headers(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|(name, _, _)| !name.starts_with(b":"))
+    }
+}
+
+impl From<Vec<Field>> for DecodedBlock {
+    fn from(fields: Vec<Field>) -> Self {
+        Self { fields }
+    }
+}
+
+impl IntoIterator for DecodedBlock {
+    type Item = Field;
+    type IntoIter = std::vec::IntoIter<Field>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a DecodedBlock {
+    type Item = &'a Field;
+    type IntoIter = std::slice::Iter<'a, Field>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should split a mixed block into the pseudo-header and the regular header
+    /// fields while preserving the order within each group.
+    #[test]
+    fn separates_pseudo_headers() {
+        let block = DecodedBlock::from(vec![
+            (b":method".to_vec(), b"GET".to_vec(), 0x0),
+            (b"accept".to_vec(), b"*/*".to_vec(), 0x0),
+            (b":path".to_vec(), b"/".to_vec(), 0x0),
+            (b"cookie".to_vec(), b"a=1".to_vec(), 0x4),
+            (b":authority".to_vec(), b"example.com".to_vec(), 0x0),
+        ]);
+        let names = |fields: Vec<&Field>| -> Vec<Vec<u8>> {
+            fields
+                .into_iter()
+                .map(|(name, _, _)| name.clone())
+                .collect()
+        };
+        assert_eq!(
+            names(block.pseudo_headers().collect()),
+            vec![
+                b":method".to_vec(),
+                b":path".to_vec(),
+                b":authority".to_vec()
+            ]
+        );
+        assert_eq!(
+            names(block.regular_headers().collect()),
+            vec![b"accept".to_vec(), b"cookie".to_vec()]
+        );
+        assert_eq!(block.len(), 5);
+        assert_eq!(block.into_iter().nth(3).unwrap().2, 0x4);
+    }
+}
@@ -29,12 +29,23 @@ pub enum DecoderError {
     /// Indicates that the decoder received a size that do not follow external
     /// protocol rules.
     InvalidMaxDynamicSize,
+
+    /// Indicates that the block contains more header field representations
+    /// than allowed.
+    TooManyFields,
 }
 
 impl From<HuffmanError> for DecoderError {
     fn from(err: HuffmanError) -> Self {
         match err {
-            HuffmanError::InvalidInput => Self::InvalidInput,
+            HuffmanError::InvalidInput
+            | HuffmanError::InvalidPadding(_)
+            | HuffmanError::EosEncountered(_)
+            | HuffmanError::IncompleteCode(_)
+            | HuffmanError::LimitExceeded
+            | HuffmanError::InvalidUtf8
+            | HuffmanError::Io(_)
+            | HuffmanError::BufferTooSmall => Self::InvalidInput,
         }
     }
 }
@@ -48,6 +59,7 @@ impl fmt::Display for DecoderError {
             Self::IntegerOverflow => write!(fmt, "Too many bytes."),
             Self::IntegerUnderflow => write!(fmt, "Not enough bytes."),
             Self::InvalidMaxDynamicSize => write!(fmt, "New size exceeds hard limit."),
+            Self::TooManyFields => write!(fmt, "Too many header fields."),
         }
     }
 }
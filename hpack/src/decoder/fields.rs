@@ -0,0 +1,57 @@
+use super::{Decoder, DecoderError, Field};
+
+/// Provides an iterator which decodes header fields on demand.
+///
+/// The iterator is created by the `Decoder::fields` method. Each header field
+/// is decoded, and the dynamic table of the decoder updated, only when the
+/// iterator advances. The iterator stops after yielding an error.
+#[derive(Debug)]
+pub struct Fields<'s, 'a> {
+    /// The decoder which decodes the fields.
+    decoder: &'s mut Decoder<'a>,
+
+    /// The encoded header block.
+    src: &'s [u8],
+
+    /// The number of bytes which have already been decoded.
+    offset: usize,
+
+    /// Whether the iterator has yielded an error.
+    failed: bool,
+}
+
+impl<'s, 'a> Fields<'s, 'a> {
+    /// Returns a new iterator which decodes the `src` with the `decoder`.
+    pub(crate) fn new(decoder: &'s mut Decoder<'a>, src: &'s [u8]) -> Self {
+        Self {
+            decoder,
+            src,
+            offset: 0,
+            failed: false,
+        }
+    }
+}
+
+impl<'s, 'a> Iterator for Fields<'s, 'a> {
+    type Item = Result<Field, DecoderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        while self.offset < self.src.len() {
+            let mut dst = Vec::with_capacity(1);
+            match self.decoder.decode_next(&self.src[self.offset..], &mut dst) {
+                Ok(size) => self.offset += size,
+                Err(err) => {
+                    self.failed = true;
+                    return Some(Err(err));
+                }
+            }
+            if let Some(field) = dst.pop() {
+                return Some(Ok(field)); // size updates yield no field
+            }
+        }
+        None
+    }
+}
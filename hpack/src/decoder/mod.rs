@@ -18,14 +18,27 @@
 //! [HTTP/2]: https://tools.ietf.org/html/rfc7540
 //! [SETTINGS_HEADER_TABLE_SIZE]: https://tools.ietf.org/html/rfc7540#section-6.5.2
 
+mod block;
 mod error;
+mod fields;
+mod parser;
 mod primitives;
+mod stats;
 
+pub use block::*;
 pub use error::*;
+pub use fields::*;
 pub use httlib_huffman::DecoderSpeed;
+pub use parser::*;
 use primitives::*;
+pub use stats::*;
 
-use super::Table;
+use std::collections::HashMap;
+
+use super::{Representation, Table};
+
+/// A header field holding header name, value and flags.
+type Field = (Vec<u8>, Vec<u8>, u8);
 
 /// Provides the decoding engine for HTTP/2 headers.
 #[derive(Debug)]
@@ -40,6 +53,21 @@ pub struct Decoder<'a> {
 
     /// A store for the static and the dynamic headers.
     table: Table<'a>,
+
+    /// Whether to reject header names containing uppercase ASCII characters.
+    reject_uppercase_names: bool,
+
+    /// The maximum allowed number of header field representations in a block.
+    max_fields: Option<usize>,
+
+    /// Whether to collect the compression statistics per header name.
+    track_names: bool,
+
+    /// The compression statistics of the decoded header names.
+    name_stats: HashMap<Vec<u8>, NameStat>,
+
+    /// The counts of the decoded Huffman encoded and raw strings.
+    huffman_stats: HuffmanStats,
 }
 
 impl<'a> Decoder<'a> {
@@ -50,6 +78,13 @@ impl<'a> Decoder<'a> {
     /// A flag indicating a sensitive header field ([6.2.3.]).
     pub const NEVER_INDEXED: u8 = 0x8;
 
+    /// A flag indicating that a header name contains uppercase ASCII characters
+    /// which is malformed in HTTP/2 ([8.1.2.]). The flag can only be returned
+    /// when uppercase names are not rejected.
+    ///
+    /// [8.1.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2
+    pub const UPPERCASE_NAME: u8 = 0x10;
+
     /// Returns a new decoder instance with a desired maximum allowed size of
     /// the dynamic table.
     pub fn with_dynamic_size(max_dynamic_size: u32) -> Self {
@@ -57,6 +92,11 @@ impl<'a> Decoder<'a> {
             speed: DecoderSpeed::FiveBits,
             max_dynamic_size,
             table: Table::with_dynamic_size(max_dynamic_size),
+            reject_uppercase_names: true,
+            max_fields: None,
+            track_names: false,
+            name_stats: HashMap::new(),
+            huffman_stats: HuffmanStats::default(),
         }
     }
 
@@ -68,6 +108,13 @@ impl<'a> Decoder<'a> {
         self.table.max_dynamic_size()
     }
 
+    /// Returns an iterator through all the headers of the indexing table with
+    /// their indexes. Headers of the static table are followed by headers of
+    /// the dynamic table.
+    pub fn table_entries(&self) -> impl Iterator<Item = (u32, &[u8], &[u8])> {
+        self.table.entries()
+    }
+
     /// Sets the maximum allowed size of the dynamic table.
     ///
     /// This size is just a hard limit that should be set by the external
@@ -78,12 +125,85 @@ impl<'a> Decoder<'a> {
         self.max_dynamic_size = size;
     }
 
+    /// Sets whether header names containing uppercase ASCII characters are
+    /// rejected.
+    ///
+    /// HTTP/2 requires header names to be lowercase ([8.1.2.]) thus such names
+    /// are rejected with the `InvalidInput` error by default. A lenient decoder
+    /// can accept them in which case the affected header fields are returned
+    /// with the `0x10` flag.
+    ///
+    /// [8.1.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2
+    pub fn set_reject_uppercase_names(&mut self, reject: bool) {
+        self.reject_uppercase_names = reject;
+    }
+
+    /// Sets the maximum allowed number of header field representations in a
+    /// block decoded by the `decode` method.
+    ///
+    /// All representation types are counted, including dynamic table size
+    /// updates. Decoding stops with the `TooManyFields` error as soon as the
+    /// block is found to contain more representations than allowed, which
+    /// bounds the work spent on blocks with a huge number of tiny fields.
+    pub fn set_max_fields(&mut self, max: usize) {
+        self.max_fields = Some(max);
+    }
+
+    /// Sets whether to collect the compression statistics per header name.
+    ///
+    /// The decoder counts the occurrences of each distinct header name, the
+    /// number of octets their representations consumed and how they have been
+    /// indexed. This data can drive the recommendations of which headers
+    /// should be indexed. The statistics are collected for the lifetime of the
+    /// decoder, usually a connection, and are disabled by default.
+    pub fn set_track_names(&mut self, track: bool) {
+        self.track_names = track;
+    }
+
+    /// Returns the compression statistics of the decoded header names.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// decoder.set_track_names(true);
+    /// let mut dst = Vec::new();
+    /// decoder.decode(&mut vec![0x80 | 2, 0x80 | 3], &mut dst).unwrap();
+    /// assert_eq!(decoder.name_stats()[&b":method".to_vec()].count, 2);
+    /// ```
+    pub fn name_stats(&self) -> &HashMap<Vec<u8>, NameStat> {
+        &self.name_stats
+    }
+
+    /// Returns the counts of the decoded Huffman encoded and raw strings.
+    ///
+    /// The counts are accumulated for the lifetime of the decoder and reveal
+    /// whether the peer makes use of the Huffman encoding.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let mut dst = Vec::new();
+    /// decoder.decode(&mut vec![0x04, 0x01, 0x2f], &mut dst).unwrap(); // (:path, /)
+    /// assert_eq!(decoder.huffman_stats().raw, 1);
+    /// ```
+    pub fn huffman_stats(&self) -> HuffmanStats {
+        self.huffman_stats
+    }
+
     /// Decodes headers provided in HPACK's header field representation format.
     ///
     /// The functions consumes the `buf` of bytes and writes header results to
     /// `dst`. Each item contains header name, value and flags. The decoder will
     /// not index fields unless `0x4` flag is returned. When the `0x8` flag is
-    /// present, the header field should be treated with caution.
+    /// present, the header field should be treated with caution. The `0x10`
+    /// flag marks a header name with uppercase characters which is returned
+    /// only when such names are not rejected.
     ///
     /// **Example:**
     ///
@@ -104,10 +224,14 @@ impl<'a> Decoder<'a> {
         dst: &mut Vec<(Vec<u8>, Vec<u8>, u8)>,
     ) -> Result<usize, DecoderError> {
         let mut total = 0;
+        let mut count = 0;
         loop {
             if buf.is_empty() {
                 return Ok(total);
+            } else if self.max_fields == Some(count) {
+                return Err(DecoderError::TooManyFields);
             }
+            count += 1;
 
             let mut data = Vec::with_capacity(1);
             total += self.decode_exact(buf, &mut data)?;
@@ -115,13 +239,284 @@ impl<'a> Decoder<'a> {
         }
     }
 
+    /// Decodes headers from the `src` slice and passes each decoded header name,
+    /// value and flags to the `cb` closure as soon as the header is decoded.
+    ///
+    /// The headers are decoded exactly as with the `decode` method, which also
+    /// updates the dynamic table, and the number of decoded bytes is returned.
+    /// The headers are decoded directly from the slice and are never collected.
+    /// Note that an error can occur after the closure has already been invoked
+    /// for the headers preceding the invalid one, thus the caller should
+    /// discard these headers when an error is returned.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let mut names = Vec::new();
+    /// decoder.decode_with(&[0x82, 0x84], |name, _, _| names.push(name)).unwrap();
+    /// assert_eq!(names, vec![b":method".to_vec(), b":path".to_vec()]);
+    /// ```
+    pub fn decode_with<F>(&mut self, src: &[u8], mut cb: F) -> Result<usize, DecoderError>
+    where
+        F: FnMut(Vec<u8>, Vec<u8>, u8),
+    {
+        let mut total = 0;
+        let mut offset = 0;
+        let mut count = 0;
+        while offset < src.len() {
+            if self.max_fields == Some(count) {
+                return Err(DecoderError::TooManyFields);
+            }
+            count += 1;
+
+            let mut dst = Vec::with_capacity(1);
+            let size = self.decode_next(&src[offset..], &mut dst)?;
+            offset += size;
+            if let Some((name, value, flags)) = dst.pop() {
+                total += size; // size updates are not counted
+                cb(name, value, flags);
+            }
+        }
+        Ok(total)
+    }
+
+    /// Decodes at most `max_fields` headers from the `src` slice and returns
+    /// the decoded headers together with the number of consumed bytes.
+    ///
+    /// This allows for decoding a large header block in batches and yielding
+    /// to a scheduler in between. The caller resumes by passing the remaining
+    /// bytes (`&src[consumed..]`) to the next call. A dynamic table size update
+    /// is consumed only when it precedes a decoded header or ends the `src`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let src = vec![0x80 | 2, 0x80 | 4, 0x80 | 6];
+    /// let (fields, consumed) = decoder.decode_budgeted(&src, 2).unwrap();
+    /// assert_eq!((fields.len(), consumed), (2, 2));
+    /// let (fields, consumed) = decoder.decode_budgeted(&src[consumed..], 2).unwrap();
+    /// assert_eq!((fields.len(), consumed), (1, 1));
+    /// ```
+    pub fn decode_budgeted(
+        &mut self,
+        src: &[u8],
+        max_fields: usize,
+    ) -> Result<(Vec<Field>, usize), DecoderError> {
+        let mut buf = src.to_vec();
+        let mut fields = Vec::new();
+        while fields.len() < max_fields && !buf.is_empty() {
+            let mut data = Vec::with_capacity(1);
+            self.decode_exact(&mut buf, &mut data)?;
+            fields.append(&mut data);
+        }
+        Ok((fields, src.len() - buf.len()))
+    }
+
+    /// Returns an iterator which decodes headers from the `src` slice on
+    /// demand.
+    ///
+    /// Each header is decoded, and the dynamic table updated, only when the
+    /// iterator advances. Dropping the iterator early leaves the headers which
+    /// have not been yielded undecoded, thus the dynamic table holds only the
+    /// changes of the yielded headers. The iterator stops after yielding an
+    /// error.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let src = vec![0x80 | 2, 0x80 | 4, 0x80 | 6];
+    /// let path = decoder.fields(&src).find(|f| f.as_ref().unwrap().0 == b":path");
+    /// assert_eq!(path.unwrap().unwrap().1, b"/");
+    /// ```
+    pub fn fields<'s>(&'s mut self, src: &'s [u8]) -> Fields<'s, 'a> {
+        Fields::new(self, src)
+    }
+
+    /// Decodes headers of a single header block into a `DecodedBlock` which
+    /// separates the pseudo-header fields from the regular header fields.
+    ///
+    /// The headers are decoded exactly as with the `decode` method.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let mut buf = vec![0x80 | 2, 0x80 | 16, 0x80 | 4]; // :method, accept-encoding, :path
+    /// let block = decoder.decode_block(&mut buf).unwrap();
+    /// for (name, value, flags) in block.pseudo_headers() {}
+    /// ```
+    pub fn decode_block(&mut self, buf: &mut Vec<u8>) -> Result<DecodedBlock, DecoderError> {
+        let mut fields = Vec::new();
+        self.decode(buf, &mut fields)?;
+        Ok(DecodedBlock::from(fields))
+    }
+
+    /// Decodes headers provided as a hex dump of the HPACK's header field
+    /// representation format (e.g. captured from the network traffic).
+    ///
+    /// Whitespace characters in the `hex` string are ignored. The decoded
+    /// headers are written to `dst` exactly as with the `decode` method and the
+    /// number of decoded bytes is returned.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let mut dst = Vec::new();
+    /// let hex = "8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 ff";
+    /// decoder.decode_hex(hex, &mut dst).unwrap();
+    /// assert_eq!(dst.len(), 4);
+    /// ```
+    ///
+    /// A string with an odd number of hex digits or with invalid characters
+    /// results in the `InvalidInput` error.
+    pub fn decode_hex(
+        &mut self,
+        hex: &str,
+        dst: &mut Vec<(Vec<u8>, Vec<u8>, u8)>,
+    ) -> Result<usize, DecoderError> {
+        let digits = hex
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_digit(16).ok_or(DecoderError::InvalidInput))
+            .collect::<Result<Vec<u32>, DecoderError>>()?;
+        if digits.len() % 2 != 0 {
+            return Err(DecoderError::InvalidInput);
+        }
+
+        let mut buf: Vec<u8> = digits.chunks(2).map(|d| (d[0] << 4 | d[1]) as u8).collect();
+        self.decode(&mut buf, dst)
+    }
+
+    /// Decodes headers provided in HPACK's header field representation format
+    /// and renders them as an HTTP/1.1 message head.
+    ///
+    /// The pseudo-header fields are mapped into the start line. A response
+    /// block with the `:status` field is rendered into a status line while a
+    /// request block requires the `:method` and `:path` fields which are
+    /// rendered into a request line. The `:authority` field is rendered as the
+    /// `host` header unless the block already contains it and the `:scheme`
+    /// field is omitted. Multiple `cookie` headers are joined into a single
+    /// header ([8.1.2.5.]). Each regular header is rendered as a `name: value`
+    /// line and the head is terminated with an empty line.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Decoder;
+    ///
+    /// let mut decoder = Decoder::default();
+    /// let mut buf = vec![0x80 | 2, 0x80 | 4]; // GET /
+    /// let head = decoder.decode_to_http1(&mut buf).unwrap();
+    /// assert_eq!(head, b"GET / HTTP/1.1\r\n\r\n".to_vec());
+    /// ```
+    ///
+    /// A block missing the required pseudo-header fields results in the
+    /// `InvalidInput` error. The same error is returned for a header name which
+    /// is not a valid HTTP/1.1 token, for a value containing the CR, LF or NUL
+    /// octet, which would allow a peer to inject headers into the rendered
+    /// head, for a start line field containing a space, for an empty `:path`,
+    /// for a `:status` which is not exactly three digits and for a repeated
+    /// `:method`, `:path`, `:status` or `:authority` field. Note that such
+    /// block has already been decoded, and thus consumed from the buffer, when
+    /// the error is returned.
+    ///
+    /// [8.1.2.5.]: https://tools.ietf.org/html/rfc7540#section-8.1.2.5
+    pub fn decode_to_http1(&mut self, buf: &mut Vec<u8>) -> Result<Vec<u8>, DecoderError> {
+        let mut fields = Vec::new();
+        self.decode(buf, &mut fields)?;
+
+        let mut method = None;
+        let mut path = None;
+        let mut status = None;
+        let mut authority = None;
+        let mut has_host = false;
+        let mut cookies: Vec<&[u8]> = Vec::new();
+        let mut lines = Vec::new();
+        for (name, value, _) in &fields {
+            if !is_http1_value(value) {
+                return Err(DecoderError::InvalidInput);
+            }
+            let pseudo = match name.as_slice() {
+                b":method" if is_http1_token(value) => &mut method,
+                b":path" if !value.is_empty() && !value.contains(&b' ') => &mut path,
+                b":status" if value.len() == 3 && value.iter().all(u8::is_ascii_digit) => {
+                    &mut status
+                }
+                b":authority" if !value.contains(&b' ') => &mut authority,
+                b":scheme" => continue,
+                b"cookie" => {
+                    cookies.push(value);
+                    continue;
+                }
+                _ if !is_http1_token(name) => return Err(DecoderError::InvalidInput),
+                _ => {
+                    has_host |= name.as_slice() == b"host";
+                    lines.extend_from_slice(name);
+                    lines.extend_from_slice(b": ");
+                    lines.extend_from_slice(value);
+                    lines.extend_from_slice(b"\r\n");
+                    continue;
+                }
+            };
+            if pseudo.replace(value).is_some() {
+                return Err(DecoderError::InvalidInput); // repeated pseudo-header
+            }
+        }
+
+        let mut head = Vec::new();
+        match (status, method, path) {
+            (Some(status), None, None) => {
+                head.extend_from_slice(b"HTTP/1.1 ");
+                head.extend_from_slice(status);
+                head.extend_from_slice(b" \r\n");
+            }
+            (None, Some(method), Some(path)) => {
+                head.extend_from_slice(method);
+                head.push(b' ');
+                head.extend_from_slice(path);
+                head.extend_from_slice(b" HTTP/1.1\r\n");
+                if let (Some(authority), false) = (authority, has_host) {
+                    head.extend_from_slice(b"host: ");
+                    head.extend_from_slice(authority);
+                    head.extend_from_slice(b"\r\n");
+                }
+            }
+            _ => return Err(DecoderError::InvalidInput),
+        }
+        head.append(&mut lines);
+        if !cookies.is_empty() {
+            head.extend_from_slice(b"cookie: ");
+            head.extend_from_slice(&cookies.join(&b"; "[..]));
+            head.extend_from_slice(b"\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+
+        Ok(head)
+    }
+
     /// Decodes the exact number of headers from the provided HPACK's sequence,
     /// based on the available vector capacity.
     ///
     /// The functions consumes the `buf` of bytes and writes header results to
     /// `dst`. Each item contains header name, value and flags. The decoder will
     /// not index fields unless `0x4` flag is returned. When the `0x8` flag is
-    /// present, the header field should be treated with caution.
+    /// present, the header field should be treated with caution. The `0x10`
+    /// flag marks a header name with uppercase characters which is returned
+    /// only when such names are not rejected.
     ///
     /// **Example:**
     ///
@@ -150,23 +545,44 @@ impl<'a> Decoder<'a> {
                 limit -= 1;
             }
 
-            let octet = buf[0];
+            let len = dst.len();
+            let size = self.decode_next(buf, dst)?;
+            buf.drain(0..size);
+            if dst.len() > len {
+                total += size; // size updates are not counted
+            }
+        }
+    }
+
+    /// Decodes a single representation at the beginning of the non-empty `buf`
+    /// slice and returns the number of bytes it occupies.
+    ///
+    /// A header field is pushed into `dst` while a dynamic table size update
+    /// leaves it untouched. The slice is never modified thus the caller is
+    /// responsible for advancing past the returned number of bytes.
+    pub(crate) fn decode_next(
+        &mut self,
+        buf: &[u8],
+        dst: &mut Vec<(Vec<u8>, Vec<u8>, u8)>,
+    ) -> Result<usize, DecoderError> {
+        let octet = buf[0];
+        let size = match Representation::from_prefix(octet) {
+            Representation::Indexed => self.decode_indexed(buf, dst)?,
+            Representation::SizeUpdate => return self.update_max_dynamic_size(buf),
+            _ => self.decode_literal(buf, dst)?, // with, without and never indexed
+        };
+
+        if let (true, Some((name, _, flags))) = (self.track_names, dst.last()) {
+            let stat = self.name_stats.entry(name.to_vec()).or_default();
+            stat.count += 1;
+            stat.encoded_len += size;
             if octet & 128 == 128 {
-                // indexed
-                total += self.decode_indexed(buf, dst)?;
-            } else if octet & 64 == 64 {
-                // with indexing
-                total += self.decode_literal(buf, dst)?;
-            } else if octet & 32 == 32 {
-                self.update_max_dynamic_size(buf)?;
-            } else if octet & 16 == 16 {
-                // never indexed
-                total += self.decode_literal(buf, dst)?;
-            } else {
-                // without indexing
-                total += self.decode_literal(buf, dst)?;
+                stat.indexed += 1;
+            } else if flags & Self::WITH_INDEXING == Self::WITH_INDEXING {
+                stat.inserted += 1;
             }
         }
+        Ok(size)
     }
 
     /// Decodes a header that exists in the indexing table.
@@ -183,13 +599,14 @@ impl<'a> Decoder<'a> {
     /// +---+---------------------------+
     /// ```
     ///
-    /// This function consumes the buffer only if the decoding succeeds. The
-    /// provided vector will stay untouched in case of an error.
+    /// This function returns the number of bytes read from the buffer without
+    /// consuming them. The provided vector will stay untouched in case of an
+    /// error.
     ///
     /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
     fn decode_indexed(
         &self,
-        buf: &mut Vec<u8>,
+        buf: &[u8],
         dst: &mut Vec<(Vec<u8>, Vec<u8>, u8)>,
     ) -> Result<usize, DecoderError> {
         let mut index = 0;
@@ -199,9 +616,9 @@ impl<'a> Decoder<'a> {
             Some(field) => field,
             None => return Err(DecoderError::InvalidIndex),
         };
-        dst.push((name.to_vec(), value.to_vec(), 0x0));
+        let flags = self.validate_name(name)?;
+        dst.push((name.to_vec(), value.to_vec(), flags));
 
-        buf.drain(0..total);
         Ok(total)
     }
 
@@ -304,34 +721,32 @@ impl<'a> Decoder<'a> {
     /// +-------------------------------+
     /// ```
     ///
-    /// This function consumes the buffer only if the decoding succeeds. The
-    /// provided vector will stay untouched in case of an error.
+    /// This function returns the number of bytes read from the buffer without
+    /// consuming them. The provided vector will stay untouched in case of an
+    /// error.
     ///
     /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
     /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
     /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
     fn decode_literal(
         &mut self,
-        buf: &mut Vec<u8>,
+        buf: &[u8],
         dst: &mut Vec<(Vec<u8>, Vec<u8>, u8)>,
     ) -> Result<usize, DecoderError> {
         let mut total = 0;
         let octet = buf[0];
 
-        let prefix = if octet & 64 == 64 {
-            // with indexing
-            6
-        } else {
-            // without and never indexed
-            4
-        };
+        let prefix = Representation::from_prefix(octet).prefix_bits();
 
         let mut index = 0;
         total += decode_integer(&buf[total..], &mut index, prefix)?;
 
+        let mut name_string = None; // counted only if the field is decoded
         let name = if index == 0 {
             let mut name = Vec::new();
-            total += decode_string(&buf[total..], self.speed, &mut name)?;
+            let size = decode_string(&buf[total..], self.speed, &mut name)?;
+            name_string = Some((buf[total] & 128 == 128, size));
+            total += size;
             name
         } else if let Some(h) = self.table.get(index) {
             h.0.to_vec()
@@ -339,22 +754,45 @@ impl<'a> Decoder<'a> {
             return Err(DecoderError::InvalidIndex);
         };
 
+        let flags = self.validate_name(&name)?;
+
         let mut value = Vec::new();
-        total += decode_string(&buf[total..], self.speed, &mut value)?;
+        let size = decode_string(&buf[total..], self.speed, &mut value)?;
+        let value_string = (buf[total] & 128 == 128, size);
+        total += size;
+
+        if let Some((huffman, size)) = name_string {
+            self.huffman_stats.record(huffman, size);
+        }
+        self.huffman_stats.record(value_string.0, value_string.1);
 
         if octet & 64 == 64 {
             self.table.insert(name.clone(), value.clone());
-            dst.push((name, value, 0x4));
+            dst.push((name, value, 0x4 | flags));
         } else if octet & 16 == 16 {
-            dst.push((name, value, 0x8));
+            dst.push((name, value, 0x8 | flags));
         } else {
-            dst.push((name, value, 0x0));
+            dst.push((name, value, flags));
         }
 
-        buf.drain(0..total);
         Ok(total)
     }
 
+    /// Validates the header `name` and returns the flags describing it.
+    ///
+    /// A name containing uppercase ASCII characters results in the
+    /// `InvalidInput` error when such names are rejected and in the `0x10` flag
+    /// otherwise.
+    fn validate_name(&self, name: &[u8]) -> Result<u8, DecoderError> {
+        if !name.iter().any(|b| b.is_ascii_uppercase()) {
+            Ok(0x0)
+        } else if self.reject_uppercase_names {
+            Err(DecoderError::InvalidInput)
+        } else {
+            Ok(0x10)
+        }
+    }
+
     /// Decodes the dynamic table size update signal and sets the new size to
     /// the dynamic table.
     ///
@@ -372,11 +810,11 @@ impl<'a> Decoder<'a> {
     /// +---+---------------------------+
     /// ```
     ///
-    /// This function consumes the buffer only if the decoding succeeds. The
-    /// provided vector will stay untouched in case of an error.
+    /// This function returns the number of bytes read from the buffer without
+    /// consuming them.
     ///
     /// [6.3]: https://tools.ietf.org/html/rfc7541#section-6.3
-    fn update_max_dynamic_size(&mut self, buf: &mut Vec<u8>) -> Result<usize, DecoderError> {
+    fn update_max_dynamic_size(&mut self, buf: &[u8]) -> Result<usize, DecoderError> {
         let mut new_size = 0;
         let total = decode_integer(buf, &mut new_size, 5)?;
 
@@ -386,7 +824,6 @@ impl<'a> Decoder<'a> {
             self.table.update_max_dynamic_size(new_size);
         }
 
-        buf.drain(0..total);
         Ok(total)
     }
 }
@@ -398,6 +835,11 @@ impl<'a> Default for Decoder<'a> {
             speed: DecoderSpeed::FiveBits, // fast decoding
             max_dynamic_size: table.max_dynamic_size(),
             table,
+            reject_uppercase_names: true,
+            max_fields: None,
+            track_names: false,
+            name_stats: HashMap::new(),
+            huffman_stats: HuffmanStats::default(),
         }
     }
 }
@@ -574,6 +1016,156 @@ mod test {
         assert_eq!(decoder.table.len(), 61); // table not altered
     }
 
+    /// Should reject a header name with uppercase characters by default and
+    /// accept it with the `0x10` flag when uppercase names are allowed.
+    #[test]
+    fn decodes_uppercase_name() {
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let mut buf = vec![
+            0, 3, 70, 111, 111, 3, 98, 97, 114, // (Foo, bar)
+        ];
+        let res = decoder.decode(&mut buf, &mut dst);
+        assert_eq!(res, Err(DecoderError::InvalidInput));
+        assert_eq!(dst, vec![]);
+        decoder.set_reject_uppercase_names(false);
+        decoder.decode(&mut buf, &mut dst).unwrap();
+        assert_eq!(dst, vec![(b"Foo".to_vec(), b"bar".to_vec(), 0x10)]);
+        let mut buf = vec![
+            64,
+            3,
+            70,
+            111,
+            111,
+            3,
+            98,
+            97,
+            114,       // (Foo, bar) with indexing
+            0x80 | 62, // index 62
+        ];
+        let mut dst = Vec::new();
+        decoder.decode(&mut buf, &mut dst).unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                (b"Foo".to_vec(), b"bar".to_vec(), 0x4 | 0x10),
+                (b"Foo".to_vec(), b"bar".to_vec(), 0x10),
+            ]
+        );
+    }
+
+    /// Should decode a hex dump of an HPACK block and reject an invalid hex
+    /// string ([C.4.1.]).
+    ///
+    /// [C.4.1.]: https://tools.ietf.org/html/rfc7541#appendix-C.4.1
+    #[test]
+    fn decodes_hex() {
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let hex = "8286 8441 8cf1 e3c2 e5f2\n3a6b a0ab 90f4 FF";
+        assert_eq!(decoder.decode_hex(hex, &mut dst), Ok(17));
+        assert_eq!(
+            dst,
+            vec![
+                (b":method".to_vec(), b"GET".to_vec(), 0x0),
+                (b":scheme".to_vec(), b"http".to_vec(), 0x0),
+                (b":path".to_vec(), b"/".to_vec(), 0x0),
+                (b":authority".to_vec(), b"www.example.com".to_vec(), 0x4),
+            ]
+        );
+        for hex in ["828", "82 8x", "0x82"] {
+            let res = decoder.decode_hex(hex, &mut dst);
+            assert_eq!(res, Err(DecoderError::InvalidInput));
+        }
+    }
+
+    /// Should render a decoded request and response block as an HTTP/1.1
+    /// message head.
+    #[test]
+    fn decodes_to_http1() {
+        let mut decoder = Decoder::default();
+        let mut buf = [
+            vec![0x80 | 2],                    // (:method, GET)
+            vec![0x80 | 6],                    // (:scheme, http)
+            vec![0x80 | 4],                    // (:path, /)
+            vec![0x41, 3, 102, 111, 111],      // (:authority, foo)
+            vec![0x0f, 17, 3, 97, 61, 98],     // (cookie, a=b)
+            vec![0x00, 3, 120, 45, 97, 1, 49], // (x-a, 1)
+            vec![0x0f, 17, 3, 99, 61, 100],    // (cookie, c=d)
+        ]
+        .concat();
+        let head = decoder.decode_to_http1(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(head).unwrap(),
+            "GET / HTTP/1.1\r\nhost: foo\r\nx-a: 1\r\ncookie: a=b; c=d\r\n\r\n"
+        );
+        assert!(buf.is_empty());
+        let mut buf = vec![0x80 | 8]; // (:status, 200)
+        let head = decoder.decode_to_http1(&mut buf).unwrap();
+        assert_eq!(head, b"HTTP/1.1 200 \r\n\r\n".to_vec());
+        let mut buf = vec![0x80 | 2]; // missing :path
+        let res = decoder.decode_to_http1(&mut buf);
+        assert_eq!(res, Err(DecoderError::InvalidInput));
+    }
+
+    /// Should reject fields which would inject lines into the rendered HTTP/1.1
+    /// message head or break its start line.
+    #[test]
+    fn rejects_http1_injection() {
+        let fields: Vec<(&[u8], &[u8])> = vec![
+            (b"x-a", b"a\r\nx-evil: 1"),
+            (b"x-a", b"a\nb"),
+            (b"x-a", b"a\0b"),
+            (b"x a", b"1"),
+            (b"x-a:", b"1"),
+            (b"cookie", b"a\r\nx-evil: 1"),
+            (b":authority", b"a\r\n"),
+            (b":authority", b"a b"),
+            (b":method", b"GET / HTTP/1.1\r\n"),
+            (b":method", b"G T"),
+        ];
+        for (name, value) in fields {
+            let mut buf = vec![0x80 | 2, 0x80 | 4];
+            buf.push(0x00);
+            buf.push(name.len() as u8);
+            buf.extend_from_slice(name);
+            buf.push(value.len() as u8);
+            buf.extend_from_slice(value);
+            let mut decoder = Decoder::default();
+            let res = decoder.decode_to_http1(&mut buf);
+            assert_eq!(res, Err(DecoderError::InvalidInput), "{:?}", name);
+        }
+        let mut buf = vec![0x80 | 2, 0x80 | 4, 0x00, 3, b'X', b'-', b'a', 1, b'1'];
+        let mut decoder = Decoder::default();
+        decoder.set_reject_uppercase_names(false);
+        let head = decoder.decode_to_http1(&mut buf).unwrap();
+        assert_eq!(head, b"GET / HTTP/1.1\r\nX-a: 1\r\n\r\n".to_vec());
+    }
+
+    /// Should reject pseudo-header fields which would render a malformed
+    /// HTTP/1.1 start line.
+    #[test]
+    fn rejects_malformed_http1_start_line() {
+        let blocks = vec![
+            vec![0x08, 3, b'a', b'b', b'c'],                        // :status abc
+            vec![0x08, 2, b'2', b'0'],                              // :status 20
+            vec![0x08, 4, b'2', b'0', b'0', b'0'],                  // :status 2000
+            vec![0x80 | 2, 0x04, 0],                                // empty :path
+            vec![0x80 | 2, 0x80 | 3, 0x80 | 4],                     // repeated :method
+            vec![0x80 | 2, 0x80 | 4, 0x80 | 5],                     // repeated :path
+            vec![0x80 | 8, 0x80 | 9],                               // repeated :status
+            vec![0x80 | 2, 0x80 | 4, 0x01, 1, b'a', 0x01, 1, b'b'], // repeated :authority
+        ];
+        for mut buf in blocks {
+            let mut decoder = Decoder::default();
+            let res = decoder.decode_to_http1(&mut buf);
+            assert_eq!(res, Err(DecoderError::InvalidInput));
+        }
+        let mut decoder = Decoder::default();
+        let head = decoder.decode_to_http1(&mut vec![0x80 | 8]).unwrap();
+        assert_eq!(head, b"HTTP/1.1 200 \r\n\r\n".to_vec());
+    }
+
     /// Should decode the exact number of headers based on vector capacity.
     #[test]
     fn decodes_exact() {
@@ -601,6 +1193,252 @@ mod test {
         assert_eq!(decoder.table.dynamic_len(), 1); // 1 header evicted
     }
 
+    /// Should reject a reference to a header which has been evicted by a
+    /// preceding dynamic table size update in the same block.
+    #[test]
+    fn rejects_evicted_index() {
+        let mut decoder = Decoder::with_dynamic_size(70);
+        decoder.table.insert(b"a".to_vec(), b"a".to_vec()); // index(63) after next insert
+        decoder.table.insert(b"b".to_vec(), b"b".to_vec()); // index(62)
+        let mut dst = Vec::new();
+        let mut buf = vec![
+            0x80 | 63, // index 63
+            63,
+            19,        // set to size 50 (evicts index 63)
+            0x80 | 62, // index 62
+            0x80 | 63, // index 63 (evicted)
+        ];
+        let res = decoder.decode(&mut buf, &mut dst);
+        assert_eq!(res, Err(DecoderError::InvalidIndex));
+        assert_eq!(
+            dst,
+            vec![
+                (b"a".to_vec(), b"a".to_vec(), 0x0),
+                (b"b".to_vec(), b"b".to_vec(), 0x0),
+            ]
+        );
+        assert_eq!(buf, vec![0x80 | 63]);
+    }
+
+    /// Should reject a block with more representations than allowed, counting
+    /// also the dynamic table size updates.
+    #[test]
+    fn limits_number_of_fields() {
+        let mut decoder = Decoder::default();
+        decoder.set_max_fields(3);
+        let mut dst = Vec::new();
+        decoder
+            .decode(&mut vec![0x20 | 10, 0x82, 0x84], &mut dst)
+            .unwrap(); // at the limit
+        assert_eq!(dst.len(), 2);
+        let mut dst = Vec::new();
+        let mut buf = vec![0x82, 0x84, 0x86, 0x87];
+        let res = decoder.decode(&mut buf, &mut dst);
+        assert_eq!(res, Err(DecoderError::TooManyFields));
+        assert_eq!(dst.len(), 3);
+        assert_eq!(buf, vec![0x87]); // not decoded
+    }
+
+    /// Should pass each decoded header to the closure as soon as it's decoded,
+    /// including the headers preceding a truncated one.
+    #[test]
+    fn decodes_with_callback() {
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let src = hex::decode("828684418cf1e3c2e5f23a6ba0ab90f4ff").unwrap(); // [C.4.1.]
+        decoder
+            .decode_with(&src, |name, value, flags| dst.push((name, value, flags)))
+            .unwrap();
+        assert_eq!(dst.len(), 4);
+        assert_eq!(
+            dst[3],
+            (b":authority".to_vec(), b"www.example.com".to_vec(), 0x4)
+        );
+        assert_eq!(decoder.table.dynamic_len(), 1);
+        let mut count = 0;
+        let res = decoder.decode_with(&[0x82, 0x41, 0x8c], |_, _, _| count += 1);
+        assert_eq!(res, Err(DecoderError::IntegerUnderflow));
+        assert_eq!(count, 1);
+        let mut buf = src.clone();
+        let mut expected = Vec::new();
+        let total = Decoder::default().decode(&mut buf, &mut expected).unwrap();
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let res = decoder.decode_with(&src, |name, value, flags| dst.push((name, value, flags)));
+        assert_eq!((res, dst), (Ok(total), expected));
+    }
+
+    /// Should accumulate the compression statistics of repeated headers under
+    /// their name.
+    #[test]
+    fn tracks_name_stats() {
+        let mut decoder = Decoder::default();
+        decoder.set_track_names(true);
+        let mut dst = Vec::new();
+        let mut buf = [
+            vec![0x40 | 31, 9], // (content-type, text/html), with indexing
+            b"text/html".to_vec(),
+            vec![0x80 | 62, 0x80 | 2], // (content-type, text/html), (:method, GET)
+        ]
+        .concat();
+        decoder.decode(&mut buf, &mut dst).unwrap();
+        assert_eq!(
+            decoder.name_stats()[&b"content-type".to_vec()],
+            NameStat {
+                count: 2,
+                encoded_len: 12,
+                indexed: 1,
+                inserted: 1,
+            }
+        );
+        assert_eq!(decoder.name_stats()[&b":method".to_vec()].count, 1);
+        assert_eq!(decoder.name_stats().len(), 2);
+    }
+
+    /// Should count the decoded Huffman encoded and raw strings, but not the
+    /// strings of an incomplete representation.
+    #[test]
+    fn counts_huffman_strings() {
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let mut buf = [
+            vec![0x40, 3, 102, 111, 111, 0x80 | 2, 148, 231], // (foo, foo) raw, Huffman
+            vec![0x04, 0x80 | 1, 0x63],                       // (:path, /) Huffman
+            vec![0x00, 3, 102, 111],                          // incomplete
+        ]
+        .concat();
+        assert!(decoder.decode(&mut buf, &mut dst).is_err());
+        assert_eq!(
+            decoder.huffman_stats(),
+            HuffmanStats {
+                huffman: 2,
+                huffman_len: 5,
+                raw: 1,
+                raw_len: 4,
+            }
+        );
+    }
+
+    /// Should decode a header block in batches of limited size where each
+    /// call resumes with the bytes which have not been consumed.
+    #[test]
+    fn decodes_budgeted() {
+        let mut encoder = crate::Encoder::default();
+        let mut src = Vec::new();
+        for i in 0..10 {
+            let value = format!("value{}", i).into_bytes();
+            encoder
+                .encode((b"x-field".to_vec(), value, 0x4), &mut src)
+                .unwrap();
+        }
+        let mut decoder = Decoder::default();
+        let mut fields = Vec::new();
+        let mut offset = 0;
+        let mut batches = 0;
+        while offset < src.len() {
+            let (mut batch, consumed) = decoder.decode_budgeted(&src[offset..], 3).unwrap();
+            assert!(batch.len() <= 3);
+            fields.append(&mut batch);
+            offset += consumed;
+            batches += 1;
+        }
+        assert_eq!(batches, 4);
+        assert_eq!(fields.len(), 10);
+        for (i, (name, value, _)) in fields.into_iter().enumerate() {
+            assert_eq!(name, b"x-field");
+            assert_eq!(value, format!("value{}", i).into_bytes());
+        }
+        assert_eq!(decoder.decode_budgeted(&[], 3).unwrap(), (vec![], 0));
+    }
+
+    /// Should decode headers lazily where the dynamic table is updated only
+    /// for the headers which have been yielded.
+    #[test]
+    fn decodes_fields_lazily() {
+        let mut encoder = crate::Encoder::default();
+        let mut src = Vec::new();
+        for name in &["x-a", "x-b", "x-c"] {
+            let field = (name.as_bytes().to_vec(), b"1".to_vec(), 0x4);
+            encoder.encode(field, &mut src).unwrap();
+        }
+        src.push(0x80); // invalid index
+        let mut decoder = Decoder::default();
+        let fields: Vec<_> = decoder.fields(&src).take(2).collect();
+        assert_eq!(
+            fields,
+            vec![
+                Ok((b"x-a".to_vec(), b"1".to_vec(), 0x4)),
+                Ok((b"x-b".to_vec(), b"1".to_vec(), 0x4)),
+            ]
+        );
+        assert_eq!(decoder.table.dynamic_len(), 2);
+        let mut decoder = Decoder::default();
+        let fields: Vec<_> = decoder.fields(&src).collect();
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[3], Err(DecoderError::InvalidIndex));
+        assert_eq!(decoder.table.dynamic_len(), 3);
+        assert_eq!(decoder.fields(&[]).count(), 0);
+    }
+
+    /// Should skip dynamic table size updates while decoding headers lazily.
+    #[test]
+    fn decodes_fields_after_size_update() {
+        let src = vec![0x3f, 0xe1, 0x1f, 0x82, 0x84, 0x20];
+        let mut decoder = Decoder::default();
+        let fields: Vec<_> = decoder.fields(&src).collect();
+        assert_eq!(
+            fields,
+            vec![
+                Ok((b":method".to_vec(), b"GET".to_vec(), 0x0)),
+                Ok((b":path".to_vec(), b"/".to_vec(), 0x0)),
+            ]
+        );
+        assert_eq!(decoder.max_dynamic_size(), 0);
+    }
+
+    /// Should route each of the five representations by the first byte and
+    /// decode its integer with the prefix size of the representation ([6.]).
+    ///
+    /// [6.]: https://tools.ietf.org/html/rfc7541#section-6
+    #[test]
+    fn dispatches_representations() {
+        let mut decoder = Decoder::default();
+        let mut dst = Vec::new();
+        let mut buf = Vec::new();
+        buf.extend(&[0x82]); // 1xxxxxxx indexed
+        buf.extend(&[0x41, 1, b'a']); // 01xxxxxx with indexing
+        buf.extend(&[0x20]); // 001xxxxx size update to 0
+        buf.extend(&[0x11, 1, b'b']); // 0001xxxx never indexed
+        buf.extend(&[0x01, 1, b'c']); // 0000xxxx without indexing
+        decoder.decode(&mut buf, &mut dst).unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                (b":method".to_vec(), b"GET".to_vec(), 0x0),
+                (b":authority".to_vec(), b"a".to_vec(), 0x4),
+                (b":authority".to_vec(), b"b".to_vec(), 0x8),
+                (b":authority".to_vec(), b"c".to_vec(), 0x0),
+            ]
+        );
+        assert_eq!(decoder.table.dynamic_len(), 0); // evicted by the size update
+
+        // a prefix with all bits set requires a continuation byte
+        for octet in &[0xff, 0x7f, 0x3f, 0x1f, 0x0f] {
+            let mut dst = Vec::new();
+            let mut buf = vec![*octet];
+            let res = Decoder::default().decode(&mut buf, &mut dst);
+            assert_eq!(res, Err(DecoderError::IntegerUnderflow));
+            assert_eq!(buf, vec![*octet]);
+        }
+        // a literal without the string after the prefix
+        for octet in &[0x41, 0x11, 0x01] {
+            let mut dst = Vec::new();
+            let mut buf = vec![*octet];
+            let res = Decoder::default().decode(&mut buf, &mut dst);
+            assert_eq!(res, Err(DecoderError::IntegerUnderflow));
+        }
+    }
+
     /// Should decode an incomplete encoded sequence to simulate the HTTP/2
     /// continuation frame.
     #[test]
@@ -0,0 +1,219 @@
+use std::collections::VecDeque;
+
+use super::{primitives::decode_integer, Decoder, DecoderError, Field, Representation};
+
+/// Provides a persistent parser which decodes headers from bytes received
+/// incrementally (e.g. read from a socket).
+///
+/// The parser wraps the `Decoder` and buffers the bytes of a header field
+/// representation until it is complete. Decoded header fields are queued in
+/// the order of their appearance in the sequence.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::HpackParser;
+///
+/// let mut parser = HpackParser::default();
+/// parser.push(&[0x82, 0x86]);
+/// parser.push(&[0x84]);
+/// while let Some(field) = parser.pop() {
+///     let (name, value, flags) = field.unwrap();
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct HpackParser<'a> {
+    /// The underlying decoder.
+    decoder: Decoder<'a>,
+
+    /// A carry buffer holding bytes of an incomplete representation.
+    buf: Vec<u8>,
+
+    /// A queue of decoded fields and errors.
+    fields: VecDeque<Result<Field, DecoderError>>,
+
+    /// Whether the parser has encountered an error.
+    failed: bool,
+}
+
+impl<'a> HpackParser<'a> {
+    /// Returns a new parser instance wrapping the provided `decoder`.
+    pub fn new(decoder: Decoder<'a>) -> Self {
+        Self {
+            decoder,
+            buf: Vec::new(),
+            fields: VecDeque::new(),
+            failed: false,
+        }
+    }
+
+    /// Returns the number of buffered bytes of an incomplete representation.
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Feeds the parser with `bytes` and decodes all the header fields that
+    /// are complete.
+    ///
+    /// A representation is decoded only once all its bytes are buffered, and
+    /// the decoded bytes are removed from the buffer once per push, thus the
+    /// work is linear in the size of the block regardless of how it's split.
+    ///
+    /// An error, other than an incomplete sequence, is queued as the last item
+    /// since the decoding state can not be recovered. All subsequent bytes are
+    /// ignored in that case.
+    pub fn push(&mut self, bytes: &[u8]) {
+        if self.failed {
+            return;
+        }
+        self.buf.extend_from_slice(bytes);
+
+        let mut offset = 0;
+        while offset < self.buf.len() && is_complete(&self.buf[offset..]) {
+            let mut dst = Vec::with_capacity(1);
+            match self.decoder.decode_next(&self.buf[offset..], &mut dst) {
+                Ok(size) => {
+                    offset += size;
+                    self.fields.extend(dst.pop().map(Ok));
+                }
+                Err(DecoderError::IntegerUnderflow) => break, // wait for more bytes
+                Err(err) => {
+                    self.fields.push_back(Err(err));
+                    self.failed = true;
+                    self.buf.clear();
+                    return;
+                }
+            }
+        }
+        self.buf.drain(0..offset);
+    }
+
+    /// Returns the next decoded header field or an error.
+    pub fn pop(&mut self) -> Option<Result<Field, DecoderError>> {
+        self.fields.pop_front()
+    }
+}
+
+/// Returns `true` if the non-empty `buf` starts with a complete representation
+/// or with an invalid integer. Only the integers are decoded, thus the strings
+/// of an incomplete representation are not decoded on each push.
+fn is_complete(buf: &[u8]) -> bool {
+    let representation = Representation::from_prefix(buf[0]);
+    let mut index = 0;
+    let mut pos = match decode_integer(buf, &mut index, representation.prefix_bits()) {
+        Ok(size) => size,
+        Err(err) => return err != DecoderError::IntegerUnderflow,
+    };
+
+    let strings = match representation {
+        Representation::Indexed | Representation::SizeUpdate => 0,
+        _ if index == 0 => 2, // literal name and value
+        _ => 1,
+    };
+    for _ in 0..strings {
+        let mut len = 0;
+        match decode_integer(&buf[pos..], &mut len, 7) {
+            Ok(size) => pos += size + len as usize,
+            Err(err) => return err != DecoderError::IntegerUnderflow,
+        }
+        if pos > buf.len() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should decode a sequence pushed one byte at a time ([C.4.]).
+    ///
+    /// [C.4.]: https://tools.ietf.org/html/rfc7541#appendix-C.4
+    #[test]
+    fn parses_bytes_incrementally() {
+        let mut parser = HpackParser::default();
+        let bytes = [
+            "828684418cf1e3c2e5f23a6ba0ab90f4ff", // request 1
+            "828684be5886a8eb10649cbf",           // request 2
+        ]
+        .concat();
+        for byte in hex::decode(bytes).unwrap() {
+            parser.push(&[byte]);
+        }
+        let mut fields = Vec::new();
+        while let Some(field) = parser.pop() {
+            fields.push(field.unwrap());
+        }
+        assert_eq!(
+            fields,
+            vec![
+                (b":method".to_vec(), b"GET".to_vec(), 0x0),
+                (b":scheme".to_vec(), b"http".to_vec(), 0x0),
+                (b":path".to_vec(), b"/".to_vec(), 0x0),
+                (b":authority".to_vec(), b"www.example.com".to_vec(), 0x4),
+                (b":method".to_vec(), b"GET".to_vec(), 0x0),
+                (b":scheme".to_vec(), b"http".to_vec(), 0x0),
+                (b":path".to_vec(), b"/".to_vec(), 0x0),
+                (b":authority".to_vec(), b"www.example.com".to_vec(), 0x0),
+                (b"cache-control".to_vec(), b"no-cache".to_vec(), 0x4),
+            ]
+        );
+        assert_eq!(parser.buffered_len(), 0);
+    }
+
+    /// Should queue an error and ignore all subsequent bytes.
+    #[test]
+    fn stops_on_error() {
+        let mut parser = HpackParser::default();
+        parser.push(&[0x82, 0x80, 0x82]); // index 0 is invalid
+        assert_eq!(
+            parser.pop(),
+            Some(Ok((b":method".to_vec(), b"GET".to_vec(), 0x0)))
+        );
+        assert_eq!(parser.pop(), Some(Err(DecoderError::InvalidIndex)));
+        parser.push(&[0x82]);
+        assert_eq!(parser.pop(), None);
+    }
+
+    /// Should buffer an incomplete representation until its last byte arrives
+    /// and decode a block pushed at once.
+    #[test]
+    fn buffers_incomplete_representations() {
+        let mut src = vec![0x82, 0x40, 5];
+        src.extend_from_slice(b"x-foo");
+        src.extend_from_slice(&[0x7f, 0x01]); // value length 128
+        src.extend_from_slice(&[b'a'; 128]);
+        src.push(0x3f); // incomplete size update
+        let literal_end = src.len() - 2;
+        let mut parser = HpackParser::default();
+        for (i, byte) in src.iter().enumerate() {
+            parser.push(&[*byte]);
+            let buffered = match i {
+                0 => 0,
+                _ if i == literal_end => 0,
+                _ if i > literal_end => 1,
+                _ => i,
+            };
+            assert_eq!(parser.buffered_len(), buffered);
+        }
+        parser.push(&[0xe1, 0x1f, 0x84]);
+        assert_eq!(parser.buffered_len(), 0);
+        let mut fields = Vec::new();
+        while let Some(field) = parser.pop() {
+            fields.push(field.unwrap());
+        }
+        assert_eq!(
+            fields,
+            vec![
+                (b":method".to_vec(), b"GET".to_vec(), 0x0),
+                (b"x-foo".to_vec(), vec![b'a'; 128], 0x4),
+                (b":path".to_vec(), b"/".to_vec(), 0x0),
+            ]
+        );
+        let mut parser = HpackParser::default();
+        parser.push(&src);
+        assert_eq!(parser.buffered_len(), 1);
+        assert_eq!(parser.fields.len(), 2);
+    }
+}
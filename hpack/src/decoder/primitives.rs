@@ -73,7 +73,7 @@ pub(crate) fn decode_integer(
 
     let mut shift = 0;
     loop {
-        let byte = if buf.is_empty() {
+        let byte = if total >= buf.len() {
             return Err(DecoderError::IntegerUnderflow);
         } else {
             total += 1;
@@ -117,6 +117,9 @@ pub(crate) fn decode_string(
     speed: DecoderSpeed,
     dst: &mut Vec<u8>,
 ) -> Result<usize, DecoderError> {
+    if buf.is_empty() {
+        return Err(DecoderError::IntegerUnderflow);
+    }
     let huffman = buf[0] & 128 == 128;
 
     let mut len = 0;
@@ -139,6 +142,26 @@ pub(crate) fn decode_string(
     Ok(total)
 }
 
+/// Returns `true` if the `name` is a valid HTTP/1.1 token ([RFC7230 3.2.6.])
+/// which can be rendered as a header field name.
+///
+/// [RFC7230 3.2.6.]: https://tools.ietf.org/html/rfc7230#section-3.2.6
+pub(crate) fn is_http1_token(name: &[u8]) -> bool {
+    !name.is_empty()
+        && name.iter().all(|b| {
+            matches!(b,
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'!' | b'#' | b'$' | b'%' | b'&'
+                | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+            )
+        })
+}
+
+/// Returns `true` if the `value` can be rendered into an HTTP/1.1 message head
+/// without breaking its lines, thus when it contains no CR, LF or NUL octet.
+pub(crate) fn is_http1_value(value: &[u8]) -> bool {
+    !value.iter().any(|b| matches!(b, b'\r' | b'\n' | b'\0'))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -186,4 +209,45 @@ mod test {
             assert_eq!(dst, value);
         }
     }
+
+    /// Should reject a Huffman encoded string which holds only the padding of
+    /// more than 7 bits ([5.2.]).
+    ///
+    /// [5.2.]: https://tools.ietf.org/html/rfc7541#section-5.2
+    #[test]
+    fn rejects_padding_only_string() {
+        let speeds = vec![
+            DecoderSpeed::OneBit,
+            DecoderSpeed::TwoBits,
+            DecoderSpeed::ThreeBits,
+            DecoderSpeed::FourBits,
+            DecoderSpeed::FiveBits,
+        ];
+        for speed in speeds {
+            let mut dst = Vec::new();
+            let res = decode_string(&[0x80 | 2, 0xFF, 0xFF], speed, &mut dst);
+            assert_eq!(res, Err(DecoderError::InvalidInput));
+            assert!(dst.is_empty());
+        }
+    }
+
+    /// Should return the `IntegerUnderflow` error when the sequence is
+    /// incomplete.
+    #[test]
+    fn rejects_incomplete_sequence() {
+        let mut dst = 0;
+        assert_eq!(
+            decode_integer(&[], &mut dst, 5),
+            Err(DecoderError::IntegerUnderflow)
+        );
+        assert_eq!(
+            decode_integer(&[31, 154], &mut dst, 5),
+            Err(DecoderError::IntegerUnderflow)
+        );
+        let mut dst = Vec::new();
+        for bytes in [&[][..], &[127], &[3, 102]] {
+            let res = decode_string(bytes, DecoderSpeed::FiveBits, &mut dst);
+            assert_eq!(res, Err(DecoderError::IntegerUnderflow));
+        }
+    }
 }
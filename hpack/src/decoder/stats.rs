@@ -0,0 +1,52 @@
+/// Provides the compression statistics of all the occurrences of a header name
+/// decoded by a decoder.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NameStat {
+    /// The number of decoded header fields with the name.
+    pub count: usize,
+
+    /// The total number of octets of all the decoded representations.
+    pub encoded_len: usize,
+
+    /// The number of occurrences represented as a fully indexed header field.
+    pub indexed: usize,
+
+    /// The number of occurrences which have been inserted into the dynamic
+    /// table.
+    pub inserted: usize,
+}
+
+/// Provides the counts of string literals decoded by a decoder by whether they
+/// have been Huffman encoded or sent as raw octets.
+///
+/// A peer which rarely uses Huffman encoding leaves compression on the table.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct HuffmanStats {
+    /// The number of decoded Huffman encoded strings.
+    pub huffman: usize,
+
+    /// The total number of octets of the Huffman encoded strings, including
+    /// the string length.
+    pub huffman_len: usize,
+
+    /// The number of decoded raw strings.
+    pub raw: usize,
+
+    /// The total number of octets of the raw strings, including the string
+    /// length.
+    pub raw_len: usize,
+}
+
+impl HuffmanStats {
+    /// Counts a decoded string of `len` octets where `huffman` tells whether
+    /// the string has been Huffman encoded (the H bit).
+    pub(crate) fn record(&mut self, huffman: bool, len: usize) {
+        if huffman {
+            self.huffman += 1;
+            self.huffman_len += len;
+        } else {
+            self.raw += 1;
+            self.raw_len += len;
+        }
+    }
+}
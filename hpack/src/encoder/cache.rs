@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use super::EncoderError;
+
+/// Provides a store of precomputed Huffman encodings of string literals.
+///
+/// Encoding the same string with Huffman over and over again is wasteful when
+/// the header is not indexed. The encoder with a cache computes the Huffman
+/// form of each string only once and reuses it afterwards. This trades memory
+/// for speed since the cache holds every distinct string until it is cleared.
+#[derive(Debug, Default, Clone)]
+pub struct HuffmanCache {
+    /// A map of plain strings to their Huffman encodings.
+    entries: HashMap<Vec<u8>, Vec<u8>>,
+
+    /// The number of lookups which found a precomputed encoding.
+    hits: usize,
+
+    /// The number of lookups which required the encoding to be computed.
+    misses: usize,
+}
+
+impl HuffmanCache {
+    /// Returns a new empty cache instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of cached encodings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache holds no encodings.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of lookups which found a precomputed encoding.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of lookups which required the encoding to be
+    /// computed.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Removes all the cached encodings.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the Huffman encoding of the `data` which is computed and stored
+    /// only if not found in the cache.
+    pub fn get_or_encode(&mut self, data: &[u8]) -> Result<&[u8], EncoderError> {
+        if self.entries.contains_key(data) {
+            self.hits += 1;
+        } else {
+            let mut dst = Vec::new();
+            httlib_huffman::encode(data, &mut dst)?;
+            self.entries.insert(data.to_vec(), dst);
+            self.misses += 1;
+        }
+        Ok(&self.entries[data])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should compute the Huffman encoding of a string only once.
+    #[test]
+    fn caches_encodings() {
+        let mut cache = HuffmanCache::new();
+        assert_eq!(cache.get_or_encode(b"foo").unwrap(), &[148, 231]);
+        assert_eq!(cache.get_or_encode(b"foo").unwrap(), &[148, 231]);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(cache.len(), 1);
+    }
+}
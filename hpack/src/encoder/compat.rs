@@ -0,0 +1,44 @@
+use crate::table::entry_size;
+
+/// Provides reference HPACK implementations whose representation choices the
+/// encoder can reproduce byte for byte, which enables differential testing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompatMode {
+    /// Indicates that literals are represented as requested by the flags.
+    #[default]
+    None,
+
+    /// Indicates that literals are represented exactly as the deflater of the
+    /// [nghttp2] library represents them.
+    ///
+    /// [nghttp2]: https://nghttp2.org
+    Nghttp2,
+}
+
+/// Returns `true` if nghttp2 encodes the header as a never indexed literal.
+/// This is the case for the `authorization` header and for short cookies
+/// which are easy to guess.
+pub(crate) fn nghttp2_never_indexed(name: &[u8], value: &[u8]) -> bool {
+    name == b"authorization" || (name == b"cookie" && value.len() < 20)
+}
+
+/// Returns `true` if nghttp2 inserts the header into the dynamic table. Large
+/// headers, compared to the `max_dynamic_size`, and headers with values which
+/// are likely unique are not indexed.
+pub(crate) fn nghttp2_indexing(name: &[u8], value: &[u8], max_dynamic_size: u32) -> bool {
+    if entry_size(name, value) > max_dynamic_size as usize * 3 / 4 {
+        return false;
+    }
+
+    !matches!(
+        name,
+        b":path"
+            | b"age"
+            | b"content-length"
+            | b"etag"
+            | b"if-modified-since"
+            | b"if-none-match"
+            | b"location"
+            | b"set-cookie"
+    )
+}
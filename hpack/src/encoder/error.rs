@@ -13,19 +13,46 @@ pub enum EncoderError {
 
     /// Indicates that an invalid index was provided. According to the HPACK
     /// specification, the index `0` must be treated as an invalid index number.
-    /// The first valid number is `1`.
+    /// The first valid number is `1`. This error is returned for a fully
+    /// indexed header, which has always been reported this way.
     InvalidIndex,
 
+    /// Indicates that the index of a header name is `0`, which is never valid
+    /// in HPACK, or that it exceeds the number of headers in the indexing
+    /// table. This error is returned by every method encoding a header with an
+    /// indexed name, thus also when encoding an `EncoderInput` with an indexed
+    /// name.
+    IndexOutOfRange,
+
     /// Indicates that an invalid prefix was provided (must be [1, 8]).
     InvalidPrefix,
 
     /// Indicates that the value of the integer being encoded exceeds a certain
-    /// threshold (5 bytes are chosen by this implementation). This can also
-    /// happen while encoding too long string.
+    /// threshold (5 bytes are chosen by this implementation).
     IntegerOverflow,
 
     /// Indicates that a low-level I/O operation failed.
     IoError,
+
+    /// Indicates that a header name is empty, contains uppercase ASCII
+    /// characters or other characters not allowed in HTTP/2 header names. This
+    /// error is returned only when the encoder validation is enabled.
+    InvalidHeaderName,
+
+    /// Indicates that encoding a header would exceed the maximum allowed size
+    /// of the header list.
+    HeaderListTooLarge,
+
+    /// Indicates that a string is longer than `u32::MAX` octets, thus its
+    /// length can not be encoded.
+    StringTooLong,
+
+    /// Indicates that a pseudo-header field name does not begin with the `:`
+    /// character or that a regular header field name does, which would break
+    /// the order of pseudo-header fields ([8.1.2.1.]).
+    ///
+    /// [8.1.2.1.]: https://tools.ietf.org/html/rfc7540#section-8.1.2.1
+    PseudoHeaderOrder,
 }
 
 impl From<HuffmanError> for EncoderError {
@@ -47,9 +74,14 @@ impl fmt::Display for EncoderError {
         match self {
             Self::InvalidInput => write!(fmt, "Invalid input character."),
             Self::InvalidIndex => write!(fmt, "Invalid index."),
+            Self::IndexOutOfRange => write!(fmt, "Index out of range."),
             Self::InvalidPrefix => write!(fmt, "Invalid prefix."),
             Self::IntegerOverflow => write!(fmt, "Too many bytes."),
             Self::IoError => write!(fmt, "I/O error."),
+            Self::InvalidHeaderName => write!(fmt, "Invalid header name."),
+            Self::HeaderListTooLarge => write!(fmt, "Header list size exceeds limit."),
+            Self::StringTooLong => write!(fmt, "String too long."),
+            Self::PseudoHeaderOrder => write!(fmt, "Invalid pseudo-header field order."),
         }
     }
 }
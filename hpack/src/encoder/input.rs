@@ -1,7 +1,49 @@
+use super::Encoder;
+
 /// Provides encoder input format options.
+///
+/// The borrowed variants accept slices of any lifetime, including `'static`,
+/// so there is no need for a separate variant for compile-time constants. A
+/// list of constant headers can be stored in a `static` array and passed to
+/// the encoder by reference, which avoids copying the names and values:
+///
+/// ```rust
+/// use httlib_hpack::Encoder;
+///
+/// static HEADERS: [(&[u8], &[u8], u8); 2] = [
+///     (b":method", b"GET", 0x10),
+///     (b"content-type", b"application/json", 0x2 | 0x4 | 0x10),
+/// ];
+///
+/// let mut encoder = Encoder::default();
+/// let mut dst = Vec::new();
+/// encoder.encode_headers(HEADERS.iter(), &mut dst).unwrap();
+/// ```
+///
+/// Note that byte string literals (e.g. `b":method"`) are references to arrays
+/// and have to be coerced to slices, which the type annotation of the array
+/// above does.
+///
+/// Inputs are compared by their content, thus an owned variant equals the
+/// borrowed variant holding the same index, bytes and flags.
+///
+/// ```rust
+/// use httlib_hpack::EncoderInput;
+///
+/// let owned = EncoderInput::from((b"foo".to_vec(), b"bar".to_vec(), 0x4));
+/// let borrowed = EncoderInput::from((&b"foo"[..], &b"bar"[..], 0x4));
+/// assert_eq!(owned, borrowed);
+/// assert_eq!(EncoderInput::from(2), EncoderInput::Indexed(2));
+/// ```
 #[derive(Debug)]
 pub enum EncoderInput<'a> {
-    /// Represents a fully indexed header field.
+    /// Represents a fully indexed header field where the index refers to an
+    /// entry of the indexing table matching both the header name and value.
+    ///
+    /// HPACK has no representation which would reference the name and the
+    /// value by separate indexes. A fully indexed header is never inserted into
+    /// the dynamic table nor Huffman encoded thus this format can not hold
+    /// configuration flags.
     Indexed(u32),
 
     /// Represents a header field where name is represented by an index and the
@@ -15,12 +57,40 @@ pub enum EncoderInput<'a> {
     LiteralBorrowed(&'a [u8], &'a [u8], u8),
 }
 
+impl<'a> EncoderInput<'a> {
+    /// Returns the content of the input regardless of whether it is owned.
+    fn content(&self) -> (Option<u32>, &[u8], &[u8], u8) {
+        match self {
+            Self::Indexed(index) => (Some(*index), &[], &[], 0),
+            Self::IndexedNameOwned(index, value, flags) => (Some(*index), &[], value, *flags),
+            Self::IndexedNameBorrowed(index, value, flags) => (Some(*index), &[], value, *flags),
+            Self::LiteralOwned(name, value, flags) => (None, name, value, *flags),
+            Self::LiteralBorrowed(name, value, flags) => (None, name, value, *flags),
+        }
+    }
+}
+
+impl<'a, 'b> PartialEq<EncoderInput<'b>> for EncoderInput<'a> {
+    fn eq(&self, other: &EncoderInput<'b>) -> bool {
+        let indexed = |input: &EncoderInput| matches!(input, EncoderInput::Indexed(_));
+        indexed(self) == indexed(other) && self.content() == other.content()
+    }
+}
+
+impl<'a> Eq for EncoderInput<'a> {}
+
 impl<'a> From<u32> for EncoderInput<'a> {
     fn from(field: u32) -> Self {
         EncoderInput::Indexed(field)
     }
 }
 
+impl<'a> From<(u32,)> for EncoderInput<'a> {
+    fn from(field: (u32,)) -> Self {
+        EncoderInput::Indexed(field.0)
+    }
+}
+
 impl<'a> From<(u32, Vec<u8>, u8)> for EncoderInput<'a> {
     fn from(field: (u32, Vec<u8>, u8)) -> Self {
         EncoderInput::IndexedNameOwned(field.0, field.1, field.2)
@@ -44,3 +114,129 @@ impl<'a> From<(&'a [u8], &'a [u8], u8)> for EncoderInput<'a> {
         EncoderInput::LiteralBorrowed(field.0, field.1, field.2)
     }
 }
+
+impl<'a> From<&'a (u32, &'a [u8], u8)> for EncoderInput<'a> {
+    fn from(field: &'a (u32, &'a [u8], u8)) -> Self {
+        EncoderInput::IndexedNameBorrowed(field.0, field.1, field.2)
+    }
+}
+
+impl<'a> From<&'a (&'a [u8], &'a [u8], u8)> for EncoderInput<'a> {
+    fn from(field: &'a (&'a [u8], &'a [u8], u8)) -> Self {
+        EncoderInput::LiteralBorrowed(field.0, field.1, field.2)
+    }
+}
+
+/// Provides a builder of the encoder input where the configuration flags are
+/// set by named methods instead of combining flag bits by hand.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::{Encoder, FieldBuilder};
+///
+/// let mut encoder = Encoder::default();
+/// let mut dst = Vec::new();
+/// let field = FieldBuilder::literal(b"content-type", b"text/html")
+///     .huffman_value()
+///     .with_indexing()
+///     .best_format();
+/// assert_eq!(field.flags(), 0x2 | 0x4 | 0x10);
+/// encoder.encode(field, &mut dst).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldBuilder<'a> {
+    /// The index of the header name in the indexing table, if any.
+    index: Option<u32>,
+
+    /// The header name which is empty when the name is indexed.
+    name: &'a [u8],
+
+    /// The header value.
+    value: &'a [u8],
+
+    /// The configuration flags.
+    flags: u8,
+}
+
+impl<'a> FieldBuilder<'a> {
+    /// Returns a new builder of a header field where the name and the value
+    /// are provided in bytes.
+    pub fn literal<N, V>(name: &'a N, value: &'a V) -> Self
+    where
+        N: AsRef<[u8]> + ?Sized,
+        V: AsRef<[u8]> + ?Sized,
+    {
+        Self {
+            index: None,
+            name: name.as_ref(),
+            value: value.as_ref(),
+            flags: 0x0,
+        }
+    }
+
+    /// Returns a new builder of a header field where the name is represented
+    /// by an `index` of the indexing table and the value is provided in bytes.
+    pub fn indexed_name<V>(index: u32, value: &'a V) -> Self
+    where
+        V: AsRef<[u8]> + ?Sized,
+    {
+        Self {
+            index: Some(index),
+            name: &[],
+            value: value.as_ref(),
+            flags: 0x0,
+        }
+    }
+
+    /// Returns the configuration flags set so far.
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Sets the flag to encode the header name with Huffman algorithm (`0x1`).
+    pub fn huffman_name(self) -> Self {
+        self.flag(Encoder::HUFFMAN_NAME)
+    }
+
+    /// Sets the flag to encode the header value with Huffman algorithm (`0x2`).
+    pub fn huffman_value(self) -> Self {
+        self.flag(Encoder::HUFFMAN_VALUE)
+    }
+
+    /// Sets the flag to index the literal header field (`0x4`).
+    pub fn with_indexing(self) -> Self {
+        self.flag(Encoder::WITH_INDEXING)
+    }
+
+    /// Sets the flag to never index the literal header field (`0x8`).
+    pub fn never_indexed(self) -> Self {
+        self.flag(Encoder::NEVER_INDEXED)
+    }
+
+    /// Sets the flag to find the best literal representation (`0x10`).
+    pub fn best_format(self) -> Self {
+        self.flag(Encoder::BEST_FORMAT)
+    }
+
+    /// Sets the flag to use Huffman algorithm only when the result is shorter
+    /// than the plain text (`0x20`).
+    pub fn huffman_if_shorter(self) -> Self {
+        self.flag(Encoder::HUFFMAN_IF_SHORTER)
+    }
+
+    /// Returns the builder with the `flag` bit set.
+    fn flag(mut self, flag: u8) -> Self {
+        self.flags |= flag;
+        self
+    }
+}
+
+impl<'a> From<FieldBuilder<'a>> for EncoderInput<'a> {
+    fn from(field: FieldBuilder<'a>) -> Self {
+        match field.index {
+            Some(index) => EncoderInput::IndexedNameBorrowed(index, field.value, field.flags),
+            None => EncoderInput::LiteralBorrowed(field.name, field.value, field.flags),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashSet;
+
+/// Provides a set of header values which are expected to repeat in the
+/// encoded headers.
+///
+/// An interner can be shared by multiple encoders. When the encoder finds the
+/// best literal representation of a header with an interned value, the header
+/// is added to the dynamic table so that the next occurrence of the same header
+/// is encoded as an indexed reference.
+#[derive(Debug, Default, Clone)]
+pub struct Interner {
+    /// A list of unique interned values.
+    values: HashSet<Vec<u8>>,
+}
+
+impl Interner {
+    /// Returns a new empty interner instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total number of interned values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the interner holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Interns the provided `value`.
+    pub fn insert(&mut self, value: Vec<u8>) {
+        self.values.insert(value);
+    }
+
+    /// Returns `true` if the `value` has been interned.
+    pub fn contains(&self, value: &[u8]) -> bool {
+        self.values.contains(value)
+    }
+}
+
+impl<V: Into<Vec<u8>>> std::iter::FromIterator<V> for Interner {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should intern unique values.
+    #[test]
+    fn interns_values() {
+        let mut interner: Interner = vec![b"foo".to_vec(), b"bar".to_vec()].into_iter().collect();
+        interner.insert(b"foo".to_vec());
+        assert_eq!(interner.len(), 2);
+        assert!(interner.contains(b"bar"));
+        assert!(!interner.contains(b"baz"));
+    }
+}
@@ -61,27 +61,82 @@
 //! [SETTINGS_HEADER_TABLE_SIZE]: https://tools.ietf.org/html/rfc7540#section-6.5.2
 //! [Huffman algorithm]: https://dev.to/xpepermint/hpack-huffman-encoder-3i7c
 
+mod cache;
+mod compat;
 mod error;
 mod input;
+mod interner;
+mod output;
+mod preference;
+mod prewarm;
 mod primitives;
+mod representation;
+#[cfg(feature = "serde")]
+mod serialize;
 
-use std::io::Write;
+use std::{io::Write, sync::Arc};
 
+pub use cache::*;
+pub use compat::CompatMode;
+use compat::*;
 pub use error::*;
 pub use input::*;
+pub use interner::*;
+use output::FirstOctet;
+pub use output::{BlockSummary, EncodedAs};
+pub use preference::MatchPreference;
+pub use prewarm::*;
 use primitives::*;
+pub use primitives::{encode_integer, encode_string};
+pub use representation::Representation;
 
-use crate::table::Table;
+use crate::table::{entry_size, Table};
+use crate::Decoder;
 
 /// Provides the encoding engine for HTTP/2 headers.
 ///
 /// Since headers in HPACK can be encoded in multiple ways, the encoder provides
 /// multiple methods for encoding headers. A developer is responsible to
 /// carefully choose between them to achieve the best encoding performance.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Encoder<'a> {
     /// A store for the static and the dynamic headers.
     table: Table<'a>,
+
+    /// An optional set of values which should be indexed when the best literal
+    /// representation is requested.
+    interner: Option<Arc<Interner>>,
+
+    /// A list of header names which are always encoded as never indexed.
+    sensitive: Vec<Vec<u8>>,
+
+    /// Whether to reject invalid HTTP/2 header names.
+    validate: bool,
+
+    /// The maximum allowed size of the header list, as advertised by the
+    /// peer with the `SETTINGS_MAX_HEADER_LIST_SIZE`.
+    max_header_list_size: Option<u32>,
+
+    /// The size of the headers encoded since the last counter reset.
+    header_list_size: usize,
+
+    /// An optional store of precomputed Huffman encodings.
+    huffman_cache: Option<HuffmanCache>,
+
+    /// The reference implementation whose representations are reproduced.
+    compat: CompatMode,
+
+    /// The table preferred when a header matches in both tables.
+    preference: MatchPreference,
+
+    /// Whether headers are always encoded with the smallest representation.
+    minimal: bool,
+
+    /// Whether strings are always encoded as plain text.
+    huffman_disabled: bool,
+
+    /// The total number of Huffman encoded strings.
+    huffman_strings: usize,
 }
 
 impl<'a> Encoder<'a> {
@@ -101,500 +156,3070 @@ impl<'a> Encoder<'a> {
     /// the indexing table (`0x10`).
     pub const BEST_FORMAT: u8 = 0x10;
 
+    /// A flag indicating to encode header name and value with Huffman
+    /// algorithm only when the result is shorter than the plain text (`0x20`).
+    /// The choice is made for the name and the value independently.
+    pub const HUFFMAN_IF_SHORTER: u8 = 0x20;
+
     /// Returns a new encoder instance with the provided maximum allowed size of
     /// the dynamic table.
     pub fn with_dynamic_size(max_dynamic_size: u32) -> Self {
         Self {
             table: Table::with_dynamic_size(max_dynamic_size),
+            ..Self::default()
         }
     }
 
-    /// Returns the maximum allowed size of the dynamic table.
-    pub fn max_dynamic_size(&mut self) -> u32 {
-        self.table.max_dynamic_size()
+    /// Returns a new encoder instance with a custom static table holding the
+    /// provided `entries` and with the provided maximum allowed size of the
+    /// dynamic table.
+    ///
+    /// This is useful for protocols which reuse HPACK with a different static
+    /// table than the one defined by the HPACK specification ([Appendix A]).
+    /// Indexes of the dynamic table start right after the last custom entry.
+    ///
+    /// [Appendix A]: https://tools.ietf.org/html/rfc7541#appendix-A
+    pub fn with_static_table(entries: Vec<(Vec<u8>, Vec<u8>)>, max_dynamic_size: u32) -> Self {
+        Self {
+            table: Table::with_static_table(entries, max_dynamic_size),
+            ..Self::default()
+        }
     }
 
-    /// Encodes headers into the HPACK's header field representation format.
-    ///
-    /// By default headers are represented without indexing and Huffman encoding
-    /// is not enabled for literals. We can configure the encoder by providing
-    /// byte `flags`:
+    /// Returns a new encoder instance with the provided maximum allowed size of
+    /// the header list.
     ///
-    /// * `0x1`: Use Huffman to encode header name.
-    /// * `0x2`: Use Huffman to encode header value.
-    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
-    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
-    /// * `0x10`: Encode literal as the best representation.
+    /// The size of the header list is the sum of the sizes of all encoded
+    /// headers where the size of a header is the length of its name and value
+    /// in octets, and 32 ([6.5.2.]). The size is accumulated by the `encode`
+    /// and `encode_headers` methods until the counter is reset with the
+    /// `reset_header_list_counter` method. A header which would exceed the
+    /// limit is not encoded and the `HeaderListTooLarge` error is returned.
     ///
     /// **Example:**
     ///
     /// ```rust
     /// use httlib_hpack::Encoder;
     ///
-    /// let mut encoder = Encoder::default();
+    /// let mut encoder = Encoder::with_max_header_list_size(8192);
     /// let mut dst = Vec::new();
-    /// let name = b":method".to_vec();
-    /// let value = b"PATCH".to_vec();
-    /// let flags = 0x2 | 0x4 | 0x10;
-    /// encoder.encode((name, value, flags), &mut dst).unwrap();
+    /// encoder.encode((b"foo".to_vec(), b"bar".to_vec(), 0x0), &mut dst).unwrap();
+    /// encoder.reset_header_list_counter(); // before the next header block
     /// ```
     ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
-    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
-    pub fn encode<'b, 'c: 'b, F, W>(&mut self, field: F, dst: W) -> Result<(), EncoderError>
-    where
-        F: Into<EncoderInput<'b>>,
-        W: Write,
-    {
-        match field.into() {
-            EncoderInput::Indexed(index) => self.encode_indexed(index, dst),
-            EncoderInput::IndexedNameBorrowed(index, value, flags) => {
-                self.encode_indexed_name(index, value, flags, dst)
-            }
-            EncoderInput::IndexedNameOwned(index, value, flags) => {
-                self.encode_indexed_name(index, &value, flags, dst)
-            }
-            EncoderInput::LiteralBorrowed(name, value, flags) => {
-                if flags & 0x10 == 0x10 {
-                    match self.table.find(&name, &value) {
-                        Some((index, true)) => self.encode_indexed(index as u32, dst),
-                        Some((index, false)) => {
-                            self.encode_indexed_name(index as u32, value, flags, dst)
-                        }
-                        None => self.encode_literal(name, value, flags, dst),
-                    }
-                } else {
-                    self.encode_literal(name, value, flags, dst)
-                }
-            }
-            EncoderInput::LiteralOwned(name, value, flags) => {
-                if flags & 0x10 == 0x10 {
-                    match self.table.find(&name, &value) {
-                        Some((index, true)) => self.encode_indexed(index as u32, dst),
-                        Some((index, false)) => {
-                            self.encode_indexed_name(index as u32, &value, flags, dst)
-                        }
-                        None => self.encode_literal(&name, &value, flags, dst),
-                    }
-                } else {
-                    self.encode_literal(&name, &value, flags, dst)
-                }
-            }
+    /// [6.5.2.]: https://tools.ietf.org/html/rfc7540#section-6.5.2
+    pub fn with_max_header_list_size(limit: u32) -> Self {
+        Self {
+            max_header_list_size: Some(limit),
+            ..Self::default()
         }
     }
 
-    /// Encodes a header that exists at `index` in the indexing table.
+    /// Sets the maximum allowed size of the header list. See the
+    /// `with_max_header_list_size` method for details.
+    pub fn set_max_header_list_size(&mut self, limit: u32) {
+        self.max_header_list_size = Some(limit);
+    }
+
+    /// Returns the size of the header list encoded since the last counter
+    /// reset.
+    pub fn header_list_size(&self) -> usize {
+        self.header_list_size
+    }
+
+    /// Resets the header list size counter. This should be called before
+    /// encoding each header block.
+    pub fn reset_header_list_counter(&mut self) {
+        self.header_list_size = 0;
+    }
+
+    /// Returns the maximum allowed size of the dynamic table.
+    pub fn max_dynamic_size(&self) -> u32 {
+        self.table.max_dynamic_size()
+    }
+
+    /// Reserves capacity for at least `additional` more headers in the dynamic
+    /// table.
     ///
-    /// The function converts the header index into HPACK's indexed header field
-    /// representation and writes it into the `dst` buffer.
+    /// This avoids repeated reallocations when many headers are expected to
+    /// be indexed (e.g. on a long-lived connection). It is only a performance
+    /// hint which has no effect on which headers are inserted or evicted.
     ///
-    /// **Indexed header field representation ([6.1.], figure 5):**
+    /// **Example:**
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 1 |        Index (7+)         |
-    /// +---+---------------------------+
-    /// ```
+    /// ```rust
+    /// use httlib_hpack::Encoder;
     ///
-    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
-    pub fn encode_indexed<W: Write>(&self, index: u32, dst: W) -> Result<(), EncoderError> {
-        if self.table.get(index).is_none() {
-            return Err(EncoderError::InvalidIndex);
-        }
-
-        encode_integer(index, 0x80, 7, dst)
+    /// let mut encoder = Encoder::default();
+    /// encoder.reserve(128);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.table.reserve(additional);
     }
 
-    /// Encodes a header where its name is represented with an `index` from the
-    /// indexing table and the `value` is provided in bytes.
+    /// Inserts the provided headers into the dynamic table without emitting
+    /// any output.
     ///
-    /// This function converts the header into HPACK's literal header field
-    /// representation and writes it into the `dst` buffer.
+    /// Headers are inserted in the provided order and older headers are
+    /// evicted when the maximum dynamic size is exceeded. The most recently
+    /// inserted header gets the lowest dynamic index (`62`). This is useful for
+    /// reproducing a peer's table state when testing or replaying traffic.
     ///
-    /// **Literal header field with incremental indexing ([6.2.1.], figure 6):**
+    /// **Example:**
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 1 |      Index (6+)       |
-    /// +---+---+-----------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.seed_dynamic_table(vec![
+    ///     (b"x-a".to_vec(), b"1".to_vec()),
+    ///     (b"x-b".to_vec(), b"2".to_vec()),
+    /// ]);
+    /// assert_eq!(encoder.index_of(b"x-b", b"2"), Some((62, true)));
     /// ```
+    pub fn seed_dynamic_table(&mut self, entries: Vec<(Vec<u8>, Vec<u8>)>) {
+        self.table.seed(entries);
+    }
+
+    /// Returns the current size (in octets) of the dynamic table.
     ///
-    /// **Literal header field without indexing ([6.2.2.], figure 8):**
+    /// The size is the sum of the sizes of all the entries in the dynamic
+    /// table where the size of an entry is the length of its name and value
+    /// plus `32` octets ([4.1.]). Comparing it with the `max_dynamic_size`
+    /// tells how close the table is to its limit.
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 0 | 0 | 0 |  Index (4+)   |
-    /// +---+---+-----------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
-    /// ```
+    /// **Example:**
     ///
-    /// **Literal header field never indexed ([6.2.3.], figure 10):**
+    /// ```rust
+    /// use httlib_hpack::Encoder;
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 0 | 0 | 1 |  Index (4+)   |
-    /// +---+---+-----------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"x-a".to_vec(), b"1".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert_eq!(encoder.dynamic_size(), 36);
     /// ```
     ///
-    /// By default headers are represented as literals without indexing and
-    /// header's value is encoded as a string. We can configure the encoder by
-    /// providing byte `flags`:
+    /// [4.1.]: https://tools.ietf.org/html/rfc7541#section-4.1
+    pub fn dynamic_size(&self) -> u32 {
+        self.table.dynamic_size()
+    }
+
+    /// Sets the interner which is consulted when encoding literals with the
+    /// `0x10` flag.
     ///
-    /// * `0x2`: Use Huffman to encode header value.
-    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
-    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
+    /// A header with an interned value, which is not fully matched in the
+    /// indexing table, is encoded as a literal with incremental indexing unless
+    /// the `0x8` flag is set. The next occurrence of the same header is thus
+    /// encoded as an indexed reference.
     ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
-    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
-    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
-    pub fn encode_indexed_name<W: Write>(
-        &mut self,
-        index: u32,
-        value: &[u8],
-        flags: u8,
-        mut dst: W,
-    ) -> Result<(), EncoderError> {
-        let name = if let Some(entry) = self.table.get(index) {
-            entry.0.to_vec()
-        } else {
-            return Err(EncoderError::InvalidIndex);
-        };
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use httlib_hpack::{Encoder, Interner};
+    ///
+    /// let interner: Interner = vec![b"application/json".to_vec()].into_iter().collect();
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_interner(Arc::new(interner));
+    /// ```
+    pub fn set_interner(&mut self, interner: Arc<Interner>) {
+        self.interner = Some(interner);
+    }
 
-        if flags & 0x4 == 0x4 {
-            self.table.insert(name, value.to_vec());
-            encode_integer(index, 0x40, 6, &mut dst)?;
-        } else if flags & 0x8 == 0x8 {
-            encode_integer(index, 0b00010000, 4, &mut dst)?;
-        } else {
-            // without indexing
-            encode_integer(index, 0x0, 4, &mut dst)?;
-        }
+    /// Sets the cache which is consulted for the Huffman encoding of string
+    /// literals.
+    ///
+    /// Each distinct string is encoded with Huffman only once and the result
+    /// is reused whenever the same string is encoded again, e.g. a repetitive
+    /// value which is not indexed.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::{Encoder, HuffmanCache};
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_huffman_cache(HuffmanCache::new());
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"foo".to_vec(), b"bar".to_vec(), 0x2), &mut dst).unwrap();
+    /// encoder.encode((b"foo".to_vec(), b"bar".to_vec(), 0x2), &mut dst).unwrap();
+    /// assert_eq!(encoder.huffman_cache().unwrap().hits(), 1);
+    /// ```
+    pub fn set_huffman_cache(&mut self, cache: HuffmanCache) {
+        self.huffman_cache = Some(cache);
+    }
 
-        encode_string(value, flags & 0x2 == 0x2, dst)
+    /// Returns the cache of the Huffman encodings if set.
+    pub fn huffman_cache(&self) -> Option<&HuffmanCache> {
+        self.huffman_cache.as_ref()
     }
 
-    /// Encodes a header where its name and value are provided in bytes.
+    /// Returns an iterator through all the headers of the indexing table with
+    /// their indexes. Headers of the static table are followed by headers of
+    /// the dynamic table.
     ///
-    /// This function converts the header into HPACK's literal header field
-    /// representation and writes it into the `dst` buffer.
+    /// **Example:**
     ///
-    /// **Literal header field with incremental indexing ([6.2.1.], figure 7):**
+    /// ```rust
+    /// use httlib_hpack::Encoder;
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 1 |           0           |
-    /// +---+---+-----------------------+
-    /// | H |     Name Length (7+)      |
-    /// +---+---------------------------+
-    /// |  Name String (Length octets)  |
-    /// +---+---------------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
+    /// let encoder = Encoder::default();
+    /// for (index, name, value) in encoder.table_entries() {
+    ///     println!("{} {:?} {:?}", index, name, value);
+    /// }
     /// ```
+    pub fn table_entries(&self) -> impl Iterator<Item = (u32, &[u8], &[u8])> {
+        self.table.entries()
+    }
+
+    /// Returns the lowest index of a header in the indexing table with the
+    /// provided `name` regardless of its value.
     ///
-    /// **Literal header field without indexing ([6.2.2.], figure 9):**
+    /// This allows for deliberately encoding a header with an indexed name
+    /// (e.g. with the `encode_indexed_name` method) even when the header is
+    /// fully indexed. Headers of the static table are preferred over headers
+    /// of the dynamic table.
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 0 | 0 | 0 |       0       |
-    /// +---+---+-----------------------+
-    /// | H |     Name Length (7+)      |
-    /// +---+---------------------------+
-    /// |  Name String (Length octets)  |
-    /// +---+---------------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let encoder = Encoder::default();
+    /// assert_eq!(encoder.find_name(b":method"), Some(2));
+    /// assert_eq!(encoder.find_name(b"x-unknown"), None);
     /// ```
+    pub fn find_name(&self, name: &[u8]) -> Option<u32> {
+        self.table.find_name(name)
+    }
+
+    /// Returns the index of a header in the indexing table with the provided
+    /// `name` and `value` without encoding anything.
     ///
-    /// **Literal header field never indexed ([6.2.3.], figure 11):**
+    /// The boolean of the returned match is `true` when the header is fully
+    /// indexed and `false` when only the name matched. This allows for
+    /// deciding on the encoding flags before the header is encoded.
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 0 | 0 | 1 |       0       |
-    /// +---+---+-----------------------+
-    /// | H |     Name Length (7+)      |
-    /// +---+---------------------------+
-    /// |  Name String (Length octets)  |
-    /// +---+---------------------------+
-    /// | H |     Value Length (7+)     |
-    /// +---+---------------------------+
-    /// | Value String (Length octets)  |
-    /// +-------------------------------+
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let encoder = Encoder::default();
+    /// assert_eq!(encoder.index_of(b":method", b"GET"), Some((2, true)));
+    /// assert_eq!(encoder.index_of(b":method", b"PUT"), Some((2, false)));
+    /// assert_eq!(encoder.index_of(b"x-unknown", b"foo"), None);
     /// ```
+    pub fn index_of(&self, name: &[u8], value: &[u8]) -> Option<(u32, bool)> {
+        self.table
+            .find(name, value)
+            .map(|(index, full)| (index as u32, full))
+    }
+
+    /// Returns `true` if the indexing table of the encoder holds exactly the
+    /// same entries as the indexing table of the `decoder`.
     ///
-    /// By default headers are represented as literals without indexing. Heder's
-    /// name and value are encoded as a string. We can configure the encoder by
-    /// providing byte `flags`:
+    /// This is meant for testing that the tables have not drifted apart after
+    /// the headers encoded by this encoder have been decoded by the `decoder`.
     ///
-    /// * `0x1`: Use Huffman to encode header name.
-    /// * `0x2`: Use Huffman to encode header value.
-    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
-    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
+    /// **Example:**
     ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
-    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
-    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
-    pub fn encode_literal<W: Write>(
-        &mut self,
-        name: &[u8],
-        value: &[u8],
-        flags: u8,
-        mut dst: W,
-    ) -> Result<(), EncoderError> {
-        if flags & 0x4 == 0x4 {
-            dst.write_all(&[0x40])?;
-            self.table.insert(name.to_vec(), value.to_vec());
-        } else if flags & 0x8 == 0x8 {
-            dst.write_all(&[0b00010000])?;
-        } else {
-            // without indexing
-            dst.write_all(&[0x0])?;
+    /// ```rust
+    /// use httlib_hpack::{Decoder, Encoder};
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut decoder = Decoder::default();
+    /// let mut buf = Vec::new();
+    /// encoder.encode((b"x-foo".to_vec(), b"bar".to_vec(), 0x4), &mut buf).unwrap();
+    /// assert!(!encoder.is_synced_with(&decoder));
+    /// decoder.decode(&mut buf, &mut Vec::new()).unwrap();
+    /// assert!(encoder.is_synced_with(&decoder));
+    /// ```
+    pub fn is_synced_with(&self, decoder: &Decoder) -> bool {
+        self.table_entries().eq(decoder.table_entries())
+    }
+
+    /// Returns the highest size (in octets) the dynamic table has reached.
+    ///
+    /// The size is the sum of the sizes of the entries ([4.1.]) right after an
+    /// insertion, once the entries over the maximum size have been evicted. A
+    /// peak well below the maximum size of the dynamic table indicates that a
+    /// lower `SETTINGS_HEADER_TABLE_SIZE` could be advertised.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"x-a".to_vec(), b"1".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert_eq!(encoder.peak_dynamic_size(), 36);
+    /// ```
+    ///
+    /// [4.1.]: https://tools.ietf.org/html/rfc7541#section-4.1
+    pub fn peak_dynamic_size(&self) -> u32 {
+        self.table.peak_dynamic_size()
+    }
+
+    /// Returns the ratio of headers which have been evicted from the dynamic
+    /// table without ever being referenced, to all evicted headers.
+    ///
+    /// A header is referenced when it's encoded as an indexed header by the
+    /// `encode` method or when its name is used by the `encode_indexed_name`
+    /// method. Headers dropped by clearing the dynamic table count as evicted.
+    /// A high ratio indicates that headers are indexed without any benefit and
+    /// that the indexing policy (e.g. the `0x4` flag) should be reconsidered.
+    /// The ratio is `0` until the first header is evicted.
+    pub fn wasted_indexing_ratio(&self) -> f64 {
+        match self.table.evicted_len() {
+            0 => 0.0,
+            evicted => self.table.wasted_len() as f64 / evicted as f64,
         }
+    }
 
-        encode_string(name, flags & 0x1 == 0x1, &mut dst)?;
-        encode_string(value, flags & 0x2 == 0x2, dst)
+    /// Sets whether header names are validated before encoding.
+    ///
+    /// HTTP/2 header names must be lowercase and may contain only a limited
+    /// set of characters ([8.1.2.]). When the validation is enabled, encoding a
+    /// header with an empty name, a name with uppercase ASCII characters or a
+    /// name with characters not allowed in a header name (e.g. control
+    /// characters) results in the `InvalidHeaderName` error. The validation is
+    /// disabled by default.
+    ///
+    /// [8.1.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2
+    pub fn set_validation(&mut self, validate: bool) {
+        self.validate = validate;
     }
 
-    /// Updates the maximum size of the dynamic table and encodes the new size
-    /// into a dynamic table size signal.
+    /// Sets the reference implementation whose representation choices are
+    /// reproduced when encoding literals.
     ///
-    /// The new maximum size MUST be lower than or equal to the limit determined
-    /// by the protocol using HPACK. In HTTP/2, this limit is the last value of
-    /// the `SETTINGS_HEADER_TABLE_SIZE` received from the decoder and
-    /// acknowledged by the encoder.
+    /// In the `CompatMode::Nghttp2` mode, the encoder ignores the flags of
+    /// literal headers, except the `0x8` flag, and chooses the representation,
+    /// the indexing and the Huffman encoding exactly as the nghttp2 deflater
+    /// does. Headers marked as sensitive are treated as never indexed. This
+    /// allows for comparing the output with the output of nghttp2 byte for
+    /// byte. Headers with an explicit index are encoded as usual.
     ///
-    /// **Maximum Dynamic table size change ([6.3.], figure 12):**
+    /// **Example:**
     ///
-    /// ```txt
-    ///   0   1   2   3   4   5   6   7
-    /// +---+---+---+---+---+---+---+---+
-    /// | 0 | 0 | 1 |   Max size (5+)   |
-    /// +---+---------------------------+
+    /// ```rust
+    /// use httlib_hpack::{CompatMode, Encoder};
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_compat(CompatMode::Nghttp2);
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b":path".to_vec(), b"/".to_vec(), 0x0), &mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x80 | 4]);
+    /// ```
+    pub fn set_compat(&mut self, mode: CompatMode) {
+        self.compat = mode;
+    }
+
+    /// Sets which table is preferred when the best literal representation is
+    /// requested and a header matches in both the static and the dynamic
+    /// table. The default `MatchPreference::LowestIndex` policy results in the
+    /// best compression.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::{Encoder, MatchPreference};
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_match_preference(MatchPreference::DynamicFirst);
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b":method".to_vec(), b"PUT".to_vec(), 0x4), &mut dst).unwrap();
+    /// dst.clear();
+    /// encoder.encode((b":method".to_vec(), b"PATCH".to_vec(), 0x14), &mut dst).unwrap();
+    /// assert_eq!(dst[0], 0x40 | 62); // name indexed from the dynamic table
     /// ```
+    pub fn set_match_preference(&mut self, preference: MatchPreference) {
+        self.preference = preference;
+    }
+
+    /// Sets whether each header is encoded with the smallest possible
+    /// representation, which is useful for producing canonical test vectors.
     ///
-    /// [6.3]: https://tools.ietf.org/html/rfc7541#section-6.3
-    pub fn update_max_dynamic_size<W: Write>(
+    /// In the minimal mode, the encoder ignores the flags of headers, except
+    /// the `0x8` flag, and never inserts headers into the dynamic table. A
+    /// literal header is encoded as the shortest of a fully indexed header, a
+    /// header with an indexed name and a literal header, preferred in that
+    /// order when equally long. Strings are Huffman encoded only when shorter.
+    /// Never indexed and sensitive headers are never fully indexed. The
+    /// minimal mode takes precedence over the `set_compat` setting.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_minimal(true);
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b":path".to_vec(), b"/".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x80 | 4]);
+    /// ```
+    pub fn set_minimal(&mut self, minimal: bool) {
+        self.minimal = minimal;
+    }
+
+    /// Disables the Huffman encoding of strings, regardless of the flags of
+    /// headers, until it is enabled again with the `enable_huffman` method.
+    ///
+    /// All names and values are then written as plain text, which makes the
+    /// encoded headers human-readable (e.g. for debugging wire captures). Note
+    /// that the `encode_indexed_name_prehuffman` method still writes the bytes
+    /// as they are provided.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.disable_huffman();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"x-a".to_vec(), b"b".to_vec(), 0x1 | 0x2), &mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x0, 0x3, b'x', b'-', b'a', 0x1, b'b']);
+    /// ```
+    pub fn disable_huffman(&mut self) {
+        self.huffman_disabled = true;
+    }
+
+    /// Enables the Huffman encoding of strings which has been disabled by the
+    /// `disable_huffman` method. Strings are then encoded as the flags of
+    /// headers say.
+    pub fn enable_huffman(&mut self) {
+        self.huffman_disabled = false;
+    }
+
+    /// Marks the header `name` as sensitive.
+    ///
+    /// Headers with a sensitive name, like `authorization` or `cookie`, are
+    /// always encoded as literals never indexed ([6.2.3.]), as if the `0x8` flag
+    /// was set, and are never inserted into the dynamic table, even when the
+    /// `0x4` flag is requested. Names are matched case-insensitively.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.mark_sensitive(b"authorization");
+    /// ```
+    ///
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    pub fn mark_sensitive(&mut self, name: &[u8]) {
+        if !self.is_sensitive(name) {
+            self.sensitive.push(name.to_ascii_lowercase());
+        }
+    }
+
+    /// Marks all the header `names` as sensitive exactly as the
+    /// `mark_sensitive` method does. This allows for loading a configurable
+    /// list of names which must never be indexed (e.g. secrets).
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.load_sensitive_names(vec![b"authorization".to_vec(), b"cookie".to_vec()]);
+    /// ```
+    pub fn load_sensitive_names<I>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+    {
+        for name in names {
+            self.mark_sensitive(&name);
+        }
+    }
+
+    /// Evicts all the headers from the dynamic table so that the encoder can be
+    /// reused for a new connection. The maximum allowed size of the dynamic
+    /// table is preserved.
+    pub fn reset(&mut self) {
+        self.table.clear_dynamic();
+    }
+
+    /// Evicts the header with the provided `name` and `value` from the dynamic
+    /// table and returns `true` if it has been found. All duplicate entries of
+    /// the header are evicted.
+    ///
+    /// This allows for immediate removal of a sensitive header which has been
+    /// indexed by mistake. Note that the decoder is not informed about the
+    /// eviction and its dynamic table gets out of sync with the encoder,
+    /// shifting the indexes of all the older entries. The eviction must thus be
+    /// paired with a table flush, e.g. the `clear_dynamic_table` method, before
+    /// the next header block is encoded.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"secret".to_vec(), b"123".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert!(encoder.evict_entry(b"secret", b"123"));
+    /// encoder.clear_dynamic_table(&mut dst).unwrap();
+    /// ```
+    pub fn evict_entry(&mut self, name: &[u8], value: &[u8]) -> bool {
+        self.table.remove_dynamic(name, value)
+    }
+
+    /// Returns `true` if no partially encoded representation is pending.
+    ///
+    /// The encoder writes each representation into the destination in a single
+    /// `encode` call and keeps no buffered bytes or partial integer state
+    /// between calls, thus the encoder is always clean. The method exists to
+    /// make this guarantee explicit for code which interleaves encoding with
+    /// other writes.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert!(encoder.is_clean());
+    /// ```
+    pub fn is_clean(&self) -> bool {
+        true
+    }
+
+    /// Encodes headers into the HPACK's header field representation format.
+    ///
+    /// By default headers are represented without indexing and Huffman encoding
+    /// is not enabled for literals. We can configure the encoder by providing
+    /// byte `flags`:
+    ///
+    /// * `0x1`: Use Huffman to encode header name.
+    /// * `0x2`: Use Huffman to encode header value.
+    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
+    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
+    /// * `0x10`: Encode literal as the best representation.
+    /// * `0x20`: Use Huffman only when shorter than the plain text.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let name = b":method".to_vec();
+    /// let value = b"PATCH".to_vec();
+    /// let flags = 0x2 | 0x4 | 0x10;
+    /// encoder.encode((name, value, flags), &mut dst).unwrap();
+    /// ```
+    ///
+    /// Each call writes a complete header field representation into `dst`.
+    /// Errors caused by the input (e.g. an invalid index, an invalid header
+    /// name or an exceeded header list size) are returned before anything is
+    /// written. The encoder never buffers a partial representation between
+    /// calls (see the `is_clean` method), thus nothing needs to be flushed.
+    /// The representation is assembled first and then written with a single
+    /// `write_all` call, and the indexing table is updated only after the
+    /// write succeeds. A writer which rejects the write thus receives nothing
+    /// and the encoding can be safely retried, while only a writer which
+    /// accepts a part of the bytes before failing holds a partial
+    /// representation.
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    pub fn encode<'b, 'c: 'b, F, W>(&mut self, field: F, dst: W) -> Result<(), EncoderError>
+    where
+        F: Into<EncoderInput<'b>>,
+        W: Write,
+    {
+        debug_assert!(self.is_clean());
+        let field = field.into();
+        let header_list_size = self.header_list_size + self.field_size(&field);
+        match self.max_header_list_size {
+            Some(limit) if header_list_size > limit as usize => {
+                return Err(EncoderError::HeaderListTooLarge);
+            }
+            _ => {}
+        }
+
+        match field {
+            EncoderInput::Indexed(index) => self.encode_referenced(index, dst),
+            EncoderInput::IndexedNameBorrowed(index, value, flags) => {
+                let flags = self.indexed_name_flags(index, value, flags);
+                self.encode_indexed_name(index, value, flags, dst)
+            }
+            EncoderInput::IndexedNameOwned(index, value, flags) => {
+                let flags = self.indexed_name_flags(index, &value, flags);
+                self.encode_indexed_name(index, &value, flags, dst)
+            }
+            EncoderInput::LiteralBorrowed(name, value, flags) => {
+                self.encode_best_literal(name, value, flags, dst)
+            }
+            EncoderInput::LiteralOwned(name, value, flags) => {
+                self.encode_best_literal(&name, &value, flags, dst)
+            }
+        }?;
+
+        self.header_list_size = header_list_size;
+        Ok(())
+    }
+
+    /// Encodes headers into the HPACK's header field representation format
+    /// exactly as the `encode` method and returns the representation that has
+    /// been used.
+    ///
+    /// This is useful when the `0x10` flag is set and the encoder decides on
+    /// its own how to represent the header.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::{EncodedAs, Encoder};
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let field = (b":method".to_vec(), b"GET".to_vec(), 0x10);
+    /// let kind = encoder.encode_reported(field, &mut dst).unwrap();
+    /// assert_eq!(kind, EncodedAs::Indexed);
+    /// ```
+    pub fn encode_reported<'b, F, W>(&mut self, field: F, dst: W) -> Result<EncodedAs, EncoderError>
+    where
+        F: Into<EncoderInput<'b>>,
+        W: Write,
+    {
+        let mut dst = FirstOctet::new(dst);
+        self.encode(field, &mut dst)?;
+
+        match dst.octet {
+            Some(octet) => Ok(EncodedAs::from_octet(octet)),
+            None => Err(EncoderError::InvalidInput), // nothing has been written
+        }
+    }
+
+    /// Encodes a list of headers into a single HPACK header block.
+    ///
+    /// Each item of `fields` is encoded in the provided order, exactly as if it
+    /// was passed to the `encode` method, and the resulting bytes are written
+    /// into the same `dst` buffer. This allows for encoding the whole header
+    /// list of an HTTP/2 `HEADERS` frame in one call.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let fields = vec![
+    ///     (b":method".to_vec(), b"GET".to_vec(), 0x10),
+    ///     (b":path".to_vec(), b"/".to_vec(), 0x10),
+    /// ];
+    /// encoder.encode_headers(fields, &mut dst).unwrap();
+    /// ```
+    ///
+    /// The encoding stops at the first error. The `dst` will in that case hold
+    /// the bytes of all the headers encoded before the failing one.
+    pub fn encode_headers<'b, I, F, W>(&mut self, fields: I, mut dst: W) -> Result<(), EncoderError>
+    where
+        I: IntoIterator<Item = F>,
+        F: Into<EncoderInput<'b>>,
+        W: Write,
+    {
+        for field in fields {
+            self.encode(field, &mut dst)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a list of headers into a single HPACK header block exactly as
+    /// the `encode_headers` method and returns the summary of the block.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let fields = vec![
+    ///     (b":method".to_vec(), b"GET".to_vec(), 0x10),
+    ///     (b"x-id".to_vec(), b"1".to_vec(), 0x0),
+    /// ];
+    /// let summary = encoder.encode_headers_summarized(fields, &mut dst).unwrap();
+    /// assert_eq!((summary.indexed, summary.literal), (1, 1));
+    /// ```
+    pub fn encode_headers_summarized<'b, I, F, W>(
         &mut self,
-        size: u32,
+        fields: I,
         dst: W,
-    ) -> Result<(), EncoderError> {
-        self.table.update_max_dynamic_size(size);
-        encode_integer(size, 0b00100000, 5, dst)
+    ) -> Result<BlockSummary, EncoderError>
+    where
+        I: IntoIterator<Item = F>,
+        F: Into<EncoderInput<'b>>,
+        W: Write,
+    {
+        let huffman_strings = self.huffman_strings;
+        let mut summary = BlockSummary::default();
+        let mut dst = FirstOctet::new(dst);
+        for field in fields {
+            dst.octet = None;
+            self.encode(field, &mut dst)?;
+            match dst.octet.map(EncodedAs::from_octet) {
+                Some(EncodedAs::Indexed) => summary.indexed += 1,
+                Some(EncodedAs::LiteralNeverIndex) => summary.never_indexed += 1,
+                Some(_) => summary.literal += 1,
+                None => return Err(EncoderError::InvalidInput), // nothing has been written
+            }
+            summary.field_count += 1;
+        }
+        summary.bytes = dst.len;
+        summary.huffman_strings = self.huffman_strings - huffman_strings;
+        Ok(summary)
+    }
+
+    /// Encodes a literal header with the value produced by the `make_value`
+    /// closure exactly as the `encode` method would.
+    ///
+    /// The closure is invoked only after the header name passes the checks
+    /// which do not depend on the value. An invalid header name, rejected when
+    /// the validation is enabled, thus never materializes the value. Note that
+    /// the closure can not be skipped for a fully indexed header since the
+    /// value is needed to find the matching entry in the indexing table.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode_lazy_value(b"cookie", || b"a=1; b=2".to_vec(), 0x4, &mut dst).unwrap();
+    /// ```
+    pub fn encode_lazy_value<F, W>(
+        &mut self,
+        name: &[u8],
+        make_value: F,
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError>
+    where
+        F: FnOnce() -> Vec<u8>,
+        W: Write,
+    {
+        if self.validate && !is_valid_name(name) {
+            return Err(EncoderError::InvalidHeaderName);
+        }
+
+        let value = make_value();
+        self.encode((name, value.as_slice(), flags), dst)
+    }
+
+    /// Encodes an HTTP/1.1 request head into a single HPACK header block.
+    ///
+    /// The `request_line` (e.g. `GET / HTTP/1.1`) is parsed into the `:method`,
+    /// `:scheme` and `:path` pseudo-header fields. The `:scheme` is `http`
+    /// unless the request target is in the absolute form (e.g.
+    /// `https://example.com/`) which also provides the `:authority`.
+    /// Otherwise, the `Host` header is mapped into the `:authority`
+    /// pseudo-header field. Pseudo-header fields are encoded first, followed by
+    /// the `headers` with names converted to lowercase. Connection-specific
+    /// headers, which are not allowed in HTTP/2 ([8.1.2.2.]), are omitted.
+    ///
+    /// All headers are encoded with the best representation and with Huffman
+    /// encoded literals which are added to the dynamic table (`0x1 | 0x2 | 0x4 |
+    /// 0x10`).
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let headers = vec![("Host", "example.com"), ("Accept", "*/*")];
+    /// encoder.encode_from_http1("GET / HTTP/1.1", &headers, &mut dst).unwrap();
+    /// ```
+    ///
+    /// A malformed request line results in the `InvalidInput` error.
+    ///
+    /// [8.1.2.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2.2
+    pub fn encode_from_http1<W: Write>(
+        &mut self,
+        request_line: &str,
+        headers: &[(&str, &str)],
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        let flags = 0x1 | 0x2 | 0x4 | 0x10;
+
+        let mut parts = request_line.split(' ');
+        let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(m), Some(t), Some(v)) if !m.is_empty() && !t.is_empty() => (m, t, v),
+            _ => return Err(EncoderError::InvalidInput),
+        };
+        if parts.next().is_some() || !version.starts_with("HTTP/1.") {
+            return Err(EncoderError::InvalidInput);
+        }
+
+        let (scheme, mut authority, path) = match target.find("://") {
+            Some(i) => {
+                let rest = &target[i + 3..];
+                let (authority, path) = match rest.find('/') {
+                    Some(j) => (&rest[..j], &rest[j..]),
+                    None => (rest, "/"),
+                };
+                (&target[..i], Some(authority), path)
+            }
+            None => ("http", None, target),
+        };
+
+        let mut fields = Vec::with_capacity(headers.len() + 4);
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            match name.as_str() {
+                "host" => {
+                    if authority.is_none() {
+                        authority = Some(value);
+                    }
+                }
+                "connection" | "keep-alive" | "proxy-connection" | "transfer-encoding"
+                | "upgrade" => {}
+                _ => fields.push((name.into_bytes(), value.as_bytes().to_vec(), flags)),
+            }
+        }
+
+        let mut pseudo = vec![
+            (b":method".to_vec(), method.as_bytes().to_vec(), flags),
+            (b":scheme".to_vec(), scheme.as_bytes().to_vec(), flags),
+            (b":path".to_vec(), path.as_bytes().to_vec(), flags),
+        ];
+        if let Some(authority) = authority {
+            pseudo.push((b":authority".to_vec(), authority.as_bytes().to_vec(), flags));
+        }
+
+        self.encode_headers(pseudo.into_iter().chain(fields), dst)
+    }
+
+    /// Encodes the header fields of an HTTP/2 request into a single HPACK
+    /// header block where all the `pseudo` header fields precede the `regular`
+    /// header fields as required by [8.1.2.1.].
+    ///
+    /// All headers are encoded with the same `flags`, exactly as if they were
+    /// passed to the `encode` method.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let pseudo: Vec<(&[u8], &[u8])> = vec![(b":method", b"GET"), (b":path", b"/")];
+    /// let regular: Vec<(&[u8], &[u8])> = vec![(b"accept", b"*/*")];
+    /// encoder.encode_request(&pseudo, &regular, 0x10, &mut dst).unwrap();
+    /// ```
+    ///
+    /// The `PseudoHeaderOrder` error is returned, before anything is written,
+    /// if a `pseudo` header name does not begin with the `:` character or if
+    /// a `regular` header name does.
+    ///
+    /// [8.1.2.1.]: https://tools.ietf.org/html/rfc7540#section-8.1.2.1
+    pub fn encode_request<W: Write>(
+        &mut self,
+        pseudo: &[(&[u8], &[u8])],
+        regular: &[(&[u8], &[u8])],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        self.encode_pseudo_first(pseudo, regular, flags, dst)
+    }
+
+    /// Encodes the header fields of an HTTP/2 response into a single HPACK
+    /// header block exactly as the `encode_request` method.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let pseudo: Vec<(&[u8], &[u8])> = vec![(b":status", b"200")];
+    /// let regular: Vec<(&[u8], &[u8])> = vec![(b"content-type", b"text/html")];
+    /// encoder.encode_response(&pseudo, &regular, 0x10, &mut dst).unwrap();
+    /// ```
+    pub fn encode_response<W: Write>(
+        &mut self,
+        pseudo: &[(&[u8], &[u8])],
+        regular: &[(&[u8], &[u8])],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        self.encode_pseudo_first(pseudo, regular, flags, dst)
+    }
+
+    /// Validates the names of the `pseudo` and the `regular` header fields and
+    /// encodes them in this order.
+    fn encode_pseudo_first<W: Write>(
+        &mut self,
+        pseudo: &[(&[u8], &[u8])],
+        regular: &[(&[u8], &[u8])],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        let is_pseudo = |name: &[u8]| name.first() == Some(&b':');
+        if !pseudo.iter().all(|(name, _)| is_pseudo(name))
+            || regular.iter().any(|(name, _)| is_pseudo(name))
+        {
+            return Err(EncoderError::PseudoHeaderOrder);
+        }
+
+        let fields = pseudo.iter().chain(regular);
+        self.encode_headers(fields.map(|(name, value)| (*name, *value, flags)), dst)
+    }
+
+    /// Returns the number of bytes that the `field` would occupy when encoded
+    /// with the `encode` method, without writing anything.
+    ///
+    /// The length accounts for the representation selected by the `flags` of
+    /// the field, the Huffman encoding of the literals and the continuation
+    /// bytes of integers. When the `0x10` flag is set for a literal, the
+    /// representation is resolved by searching the indexing table exactly as
+    /// the `encode` method does it. The length of an invalid field is
+    /// calculated as if the field was valid.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::{Encoder, EncoderInput};
+    ///
+    /// let encoder = Encoder::default();
+    /// let field = EncoderInput::from((b"foo".to_vec(), b"bar".to_vec(), 0x2));
+    /// assert_eq!(encoder.encoded_len(&field), 9);
+    /// ```
+    pub fn encoded_len(&self, field: &EncoderInput) -> usize {
+        match field {
+            EncoderInput::Indexed(index) => integer_len(*index, 7),
+            EncoderInput::IndexedNameBorrowed(index, value, flags) => {
+                let flags = self.indexed_name_flags(*index, value, *flags);
+                self.indexed_name_len(*index, value, flags)
+            }
+            EncoderInput::IndexedNameOwned(index, value, flags) => {
+                let flags = self.indexed_name_flags(*index, value, *flags);
+                self.indexed_name_len(*index, value, flags)
+            }
+            EncoderInput::LiteralBorrowed(name, value, flags) => {
+                self.best_literal_len(name, value, *flags)
+            }
+            EncoderInput::LiteralOwned(name, value, flags) => {
+                self.best_literal_len(name, value, *flags)
+            }
+        }
+    }
+
+    /// Encodes a header that exists at `index` in the indexing table.
+    ///
+    /// The function converts the header index into HPACK's indexed header field
+    /// representation and writes it into the `dst` buffer.
+    ///
+    /// **Indexed header field representation ([6.1.], figure 5):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 1 |        Index (7+)         |
+    /// +---+---------------------------+
+    /// ```
+    ///
+    /// The index `0` and an index exceeding the number of headers in the
+    /// indexing table result in the `InvalidIndex` error. Unlike the indexed
+    /// name methods, this method has always reported such an index with this
+    /// error, which is kept so that existing callers matching on it still work.
+    ///
+    /// Since the encoder is not modified, the header is not marked as
+    /// referenced for the `wasted_indexing_ratio` method. Use the `encode`
+    /// method for that.
+    ///
+    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
+    pub fn encode_indexed<W: Write>(&self, index: u32, dst: W) -> Result<(), EncoderError> {
+        if self.table.get(index).is_none() {
+            return Err(EncoderError::InvalidIndex);
+        }
+
+        write_whole(integer_len(index, 7), dst, |buf| {
+            encode_integer(index, 0x80, 7, buf)
+        })
+    }
+
+    /// Encodes a header that exists at `index` in the indexing table exactly as
+    /// the `encode_indexed` method does and marks the header as referenced.
+    fn encode_referenced<W: Write>(&mut self, index: u32, dst: W) -> Result<(), EncoderError> {
+        self.encode_indexed(index, dst)?;
+        self.table.mark_referenced(index);
+        Ok(())
+    }
+
+    /// Encodes a header where its name is represented with an `index` from the
+    /// indexing table and the `value` is provided in bytes.
+    ///
+    /// This function converts the header into HPACK's literal header field
+    /// representation and writes it into the `dst` buffer.
+    ///
+    /// **Literal header field with incremental indexing ([6.2.1.], figure 6):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 1 |      Index (6+)       |
+    /// +---+---+-----------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// **Literal header field without indexing ([6.2.2.], figure 8):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 0 | 0 | 0 |  Index (4+)   |
+    /// +---+---+-----------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// **Literal header field never indexed ([6.2.3.], figure 10):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 0 | 0 | 1 |  Index (4+)   |
+    /// +---+---+-----------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// By default headers are represented as literals without indexing and
+    /// header's value is encoded as a string. We can configure the encoder by
+    /// providing byte `flags`:
+    ///
+    /// * `0x2`: Use Huffman to encode header value.
+    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
+    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
+    /// * `0x20`: Use Huffman only when shorter than the plain text.
+    ///
+    /// A header which is larger than the maximum size of the dynamic table is
+    /// encoded without indexing even when the `0x4` flag is set. The decoder
+    /// would only empty its dynamic table when inserting such header ([4.4.]),
+    /// thus the dynamic table is left untouched instead.
+    ///
+    /// A string longer than `u32::MAX` octets results in the `StringTooLong`
+    /// error before anything is written.
+    ///
+    /// The index `0`, which is never valid in HPACK, and an index exceeding the
+    /// number of headers in the indexing table result in the `IndexOutOfRange`
+    /// error. Note that while indexes of the static table are fixed, an index
+    /// of the dynamic table refers to a different header after each insertion
+    /// or eviction, thus it should be resolved (e.g. with the `index_of`
+    /// method) right before encoding.
+    ///
+    /// [4.4.]: https://tools.ietf.org/html/rfc7541#section-4.4
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    pub fn encode_indexed_name<W: Write>(
+        &mut self,
+        index: u32,
+        value: &[u8],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        let (name, _) = self.table.get(index).ok_or(EncoderError::IndexOutOfRange)?;
+        if self.validate && !is_valid_name(name) {
+            return Err(EncoderError::InvalidHeaderName);
+        }
+        check_string_len(value)?;
+        let flags = self.fitting_flags(name, value, flags);
+
+        let (inserted, prefix, prefix_size) = if flags & 0x4 == 0x4 {
+            let name = name.to_vec(); // cloned only when inserted
+            (Some((name, value.to_vec())), 0x40, 6)
+        } else if flags & 0x8 == 0x8 {
+            (None, 0b00010000, 4)
+        } else {
+            // without indexing
+            (None, 0x0, 4)
+        };
+
+        let huffman = self.use_huffman(value, flags & 0x2 == 0x2, flags);
+        let len = self.indexed_name_len(index, value, flags);
+        write_whole(len, dst, |buf| {
+            encode_integer(index, prefix, prefix_size, &mut *buf)?;
+            self.encode_str(value, huffman, buf)
+        })?;
+
+        self.table.mark_referenced(index);
+        if let Some((name, value)) = inserted {
+            self.table.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Encodes a header where its name is represented with an `index` from the
+    /// indexing table and the value is provided as already Huffman encoded
+    /// `huffman_bytes`.
+    ///
+    /// The header is written as a literal header field without indexing
+    /// ([6.2.2.]) where the `huffman_bytes` are copied as they are, preceded by
+    /// their length with the Huffman flag set. The Huffman encoder is never
+    /// invoked, which allows for passing through values received from a peer
+    /// without decoding and encoding them again. The `huffman_bytes` are not
+    /// validated. Since the representation is not indexed, the indexing table
+    /// stays untouched.
+    ///
+    /// The index `0` and an index exceeding the number of headers in the
+    /// indexing table result in the `IndexOutOfRange` error, exactly as with
+    /// the `encode_indexed_name` method.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// let huffman_bytes = vec![0x1c, 0x64]; // "204"
+    /// encoder.encode_indexed_name_prehuffman(8, &huffman_bytes, &mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x08, 0x82, 0x1c, 0x64]);
+    /// ```
+    ///
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    pub fn encode_indexed_name_prehuffman<W: Write>(
+        &self,
+        index: u32,
+        huffman_bytes: &[u8],
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        if self.table.get(index).is_none() {
+            return Err(EncoderError::IndexOutOfRange);
+        }
+        check_string_len(huffman_bytes)?;
+
+        let len = integer_len(index, 4)
+            + integer_len(huffman_bytes.len() as u32, 7)
+            + huffman_bytes.len();
+        write_whole(len, dst, |buf| {
+            encode_integer(index, 0x0, 4, &mut *buf)?;
+            encode_string_bytes(huffman_bytes, true, buf)
+        })
+    }
+
+    /// Encodes a header where its name and value are provided in bytes.
+    ///
+    /// This function converts the header into HPACK's literal header field
+    /// representation and writes it into the `dst` buffer.
+    ///
+    /// **Literal header field with incremental indexing ([6.2.1.], figure 7):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 1 |           0           |
+    /// +---+---+-----------------------+
+    /// | H |     Name Length (7+)      |
+    /// +---+---------------------------+
+    /// |  Name String (Length octets)  |
+    /// +---+---------------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// **Literal header field without indexing ([6.2.2.], figure 9):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 0 | 0 | 0 |       0       |
+    /// +---+---+-----------------------+
+    /// | H |     Name Length (7+)      |
+    /// +---+---------------------------+
+    /// |  Name String (Length octets)  |
+    /// +---+---------------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// **Literal header field never indexed ([6.2.3.], figure 11):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 0 | 0 | 1 |       0       |
+    /// +---+---+-----------------------+
+    /// | H |     Name Length (7+)      |
+    /// +---+---------------------------+
+    /// |  Name String (Length octets)  |
+    /// +---+---------------------------+
+    /// | H |     Value Length (7+)     |
+    /// +---+---------------------------+
+    /// | Value String (Length octets)  |
+    /// +-------------------------------+
+    /// ```
+    ///
+    /// By default headers are represented as literals without indexing. Heder's
+    /// name and value are encoded as a string. We can configure the encoder by
+    /// providing byte `flags`:
+    ///
+    /// * `0x1`: Use Huffman to encode header name.
+    /// * `0x2`: Use Huffman to encode header value.
+    /// * `0x4`: Literal header field with incremental indexing ([6.2.1.]).
+    /// * `0x8`: Literal header field never indexed ([6.2.3.]).
+    /// * `0x20`: Use Huffman only when shorter than the plain text.
+    ///
+    /// A header which is larger than the maximum size of the dynamic table is
+    /// encoded without indexing even when the `0x4` flag is set. The decoder
+    /// would only empty its dynamic table when inserting such header ([4.4.]),
+    /// thus the dynamic table is left untouched instead.
+    ///
+    /// A string longer than `u32::MAX` octets results in the `StringTooLong`
+    /// error before anything is written.
+    ///
+    /// [4.4.]: https://tools.ietf.org/html/rfc7541#section-4.4
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    pub fn encode_literal<W: Write>(
+        &mut self,
+        name: &[u8],
+        value: &[u8],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        if self.validate && !is_valid_name(name) {
+            return Err(EncoderError::InvalidHeaderName);
+        }
+        check_string_len(name)?;
+        check_string_len(value)?;
+        let flags = self.fitting_flags(name, value, flags);
+
+        let prefix = if flags & 0x4 == 0x4 {
+            0x40
+        } else if flags & 0x8 == 0x8 {
+            0b00010000
+        } else {
+            // without indexing
+            0x0
+        };
+
+        let name_huffman = self.use_huffman(name, flags & 0x1 == 0x1, flags);
+        let value_huffman = self.use_huffman(value, flags & 0x2 == 0x2, flags);
+        let len = self.literal_len(name, value, flags);
+        write_whole(len, dst, |buf| {
+            buf.write_all(&[prefix])?;
+            self.encode_str(name, name_huffman, &mut *buf)?;
+            self.encode_str(value, value_huffman, buf)
+        })?;
+
+        if flags & 0x4 == 0x4 {
+            self.table.insert(name.to_vec(), value.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Encodes a string exactly as the `encode_string` function does while
+    /// reusing the Huffman encoding from the cache when set.
+    fn encode_str<W: Write>(
+        &mut self,
+        data: &[u8],
+        huffman: bool,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        if huffman {
+            self.huffman_strings += 1;
+        }
+        match &mut self.huffman_cache {
+            Some(cache) if huffman => encode_string_bytes(cache.get_or_encode(data)?, true, dst),
+            _ => encode_string(data, huffman, dst),
+        }
+    }
+
+    /// Encodes a literal header with the best representation when the `0x10`
+    /// flag is set. Sensitive headers are always encoded as never indexed
+    /// literals and are never represented as a fully indexed header.
+    fn encode_best_literal<W: Write>(
+        &mut self,
+        name: &[u8],
+        value: &[u8],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        if self.minimal {
+            return self.encode_minimal(name, value, flags, dst);
+        } else if self.compat == CompatMode::Nghttp2 {
+            return self.encode_nghttp2(name, value, flags, dst);
+        }
+
+        let sensitive = self.is_sensitive(name);
+        let flags = if sensitive { flags & !0x4 | 0x8 } else { flags };
+
+        if flags & 0x10 == 0x10 {
+            let flags = self.interned_flags(value, flags);
+            match self.find_preferred(name, value) {
+                Some((index, true)) if !sensitive => self.encode_referenced(index as u32, dst),
+                Some((index, _)) => self.encode_indexed_name(index as u32, value, flags, dst),
+                None => self.encode_literal(name, value, flags, dst),
+            }
+        } else {
+            self.encode_literal(name, value, flags, dst)
+        }
+    }
+
+    /// Searches the indexing table for the provided header while honoring the
+    /// match preference.
+    fn find_preferred(&self, name: &[u8], value: &[u8]) -> Option<(usize, bool)> {
+        match self.preference {
+            MatchPreference::LowestIndex => self.table.find(name, value),
+            MatchPreference::StaticFirst => self
+                .table
+                .find_static(name, value)
+                .or_else(|| self.table.find_dynamic(name, value)),
+            MatchPreference::DynamicFirst => {
+                let dynamic = self.table.find_dynamic(name, value);
+                match (dynamic, self.table.find_static(name, value)) {
+                    (Some((_, false)), Some((index, true))) => Some((index, true)),
+                    (dynamic, fallback) => dynamic.or(fallback),
+                }
+            }
+        }
+    }
+
+    /// Encodes a literal header with the representation the nghttp2 deflater
+    /// would choose. Only the `0x8` flag is taken into account.
+    ///
+    /// Strings are Huffman encoded when shorter. A never indexed header is
+    /// matched by its name only, while other headers are fully indexed when
+    /// found in the indexing table, or inserted into the dynamic table unless
+    /// nghttp2 rules say otherwise.
+    fn encode_nghttp2<W: Write>(
+        &mut self,
+        name: &[u8],
+        value: &[u8],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        let huffman = 0x1 | 0x2 | 0x20;
+
+        if flags & 0x8 == 0x8 || self.is_sensitive(name) || nghttp2_never_indexed(name, value) {
+            let flags = huffman | 0x8;
+            return match self.table.find_name(name) {
+                Some(index) => self.encode_indexed_name(index, value, flags, dst),
+                None => self.encode_literal(name, value, flags, dst),
+            };
+        }
+
+        let flags = if nghttp2_indexing(name, value, self.table.max_dynamic_size()) {
+            huffman | 0x4
+        } else {
+            huffman
+        };
+        match self.table.find(name, value) {
+            Some((index, true)) => self.encode_referenced(index as u32, dst),
+            Some((index, false)) => self.encode_indexed_name(index as u32, value, flags, dst),
+            None => self.encode_literal(name, value, flags, dst),
+        }
+    }
+
+    /// Encodes a literal header with the smallest possible representation
+    /// without indexing. Only the `0x8` flag is taken into account.
+    fn encode_minimal<W: Write>(
+        &mut self,
+        name: &[u8],
+        value: &[u8],
+        flags: u8,
+        dst: W,
+    ) -> Result<(), EncoderError> {
+        let never_indexed = flags & 0x8 == 0x8 || self.is_sensitive(name);
+        let flags = if never_indexed { 0x8 } else { 0x0 } | 0x1 | 0x2 | 0x20;
+        match self.minimal_literal(name, value, never_indexed).0 {
+            Some((index, true)) => self.encode_referenced(index, dst),
+            Some((index, false)) => self.encode_indexed_name(index, value, flags, dst),
+            None => self.encode_literal(name, value, flags, dst),
+        }
+    }
+
+    /// Returns the index and the length of the smallest representation of a
+    /// literal header. The index is `None` when the name is encoded as a
+    /// literal, and the `bool` tells whether the header is fully indexed.
+    fn minimal_literal(
+        &self,
+        name: &[u8],
+        value: &[u8],
+        never_indexed: bool,
+    ) -> (Option<(u32, bool)>, usize) {
+        let flags = 0x1 | 0x2 | 0x20;
+        let mut best = (None, self.literal_len(name, value, flags));
+        if let Some(index) = self.table.find_name(name) {
+            let len = self.indexed_name_len(index, value, flags);
+            if len <= best.1 {
+                best = (Some((index, false)), len);
+            }
+        }
+        if let (false, Some((index, true))) = (never_indexed, self.table.find(name, value)) {
+            let len = integer_len(index as u32, 7);
+            if len <= best.1 {
+                best = (Some((index as u32, true)), len);
+            }
+        }
+        best
+    }
+
+    /// Returns the size of the `field` in the header list which is the length
+    /// of its name and value in octets, and 32. An invalid index has no size.
+    fn field_size(&self, field: &EncoderInput) -> usize {
+        match field {
+            EncoderInput::Indexed(index) => match self.table.get(*index) {
+                Some((name, value)) => entry_size(name, value),
+                None => 0,
+            },
+            EncoderInput::IndexedNameBorrowed(index, value, _) => match self.table.get(*index) {
+                Some((name, _)) => entry_size(name, value),
+                None => 0,
+            },
+            EncoderInput::IndexedNameOwned(index, value, _) => match self.table.get(*index) {
+                Some((name, _)) => entry_size(name, value),
+                None => 0,
+            },
+            EncoderInput::LiteralBorrowed(name, value, _) => entry_size(name, value),
+            EncoderInput::LiteralOwned(name, value, _) => entry_size(name, value),
+        }
+    }
+
+    /// Returns `true` if the header `name` has been marked as sensitive.
+    fn is_sensitive(&self, name: &[u8]) -> bool {
+        self.sensitive.iter().any(|n| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the `flags` of a header with an indexed name where the never
+    /// indexed representation is forced for sensitive headers and the
+    /// incremental indexing is dropped for headers which do not fit into the
+    /// dynamic table. In the minimal mode, the value is Huffman encoded when
+    /// shorter and never indexed.
+    fn indexed_name_flags(&self, index: u32, value: &[u8], flags: u8) -> u8 {
+        let flags = match self.table.get(index) {
+            Some((name, _)) if self.is_sensitive(name) => flags & !0x4 | 0x8,
+            Some((name, _)) => self.fitting_flags(name, value, flags),
+            None => flags,
+        };
+        if self.minimal {
+            flags & 0x8 | 0x2 | 0x20
+        } else {
+            flags
+        }
+    }
+
+    /// Returns the length of a literal header which representation is resolved
+    /// the same way as in the `encode` method.
+    fn best_literal_len(&self, name: &[u8], value: &[u8], flags: u8) -> usize {
+        let sensitive = self.is_sensitive(name);
+        if self.minimal {
+            return self
+                .minimal_literal(name, value, sensitive || flags & 0x8 == 0x8)
+                .1;
+        } else if self.compat == CompatMode::Nghttp2 {
+            return self.nghttp2_len(name, value, flags);
+        }
+        let flags = if sensitive { flags & !0x4 | 0x8 } else { flags };
+
+        if flags & 0x10 == 0x10 {
+            let flags = self.interned_flags(value, flags);
+            match self.find_preferred(name, value) {
+                Some((index, true)) if !sensitive => integer_len(index as u32, 7),
+                Some((index, _)) => {
+                    let flags = self.fitting_flags(name, value, flags);
+                    self.indexed_name_len(index as u32, value, flags)
+                }
+                None => self.literal_len(name, value, flags),
+            }
+        } else {
+            self.literal_len(name, value, flags)
+        }
+    }
+
+    /// Returns the length of a literal header encoded with the `encode_nghttp2`
+    /// method.
+    fn nghttp2_len(&self, name: &[u8], value: &[u8], flags: u8) -> usize {
+        let huffman = 0x1 | 0x2 | 0x20;
+
+        if flags & 0x8 == 0x8 || self.is_sensitive(name) || nghttp2_never_indexed(name, value) {
+            let flags = huffman | 0x8;
+            return match self.table.find_name(name) {
+                Some(index) => self.indexed_name_len(index, value, flags),
+                None => self.literal_len(name, value, flags),
+            };
+        }
+
+        let flags = if nghttp2_indexing(name, value, self.table.max_dynamic_size()) {
+            self.fitting_flags(name, value, huffman | 0x4)
+        } else {
+            huffman
+        };
+        match self.table.find(name, value) {
+            Some((index, true)) => integer_len(index as u32, 7),
+            Some((index, false)) => self.indexed_name_len(index as u32, value, flags),
+            None => self.literal_len(name, value, flags),
+        }
+    }
+
+    /// Returns the `flags` without the `0x4` flag when the header is larger
+    /// than the maximum size of the dynamic table and can not be inserted.
+    fn fitting_flags(&self, name: &[u8], value: &[u8], flags: u8) -> u8 {
+        if entry_size(name, value) > self.table.max_dynamic_size() as usize {
+            flags & !0x4
+        } else {
+            flags
+        }
+    }
+
+    /// Returns the literal `flags` extended with the `0x4` flag when the `value`
+    /// is interned and the header is not marked as never indexed.
+    fn interned_flags(&self, value: &[u8], flags: u8) -> u8 {
+        match &self.interner {
+            Some(interner) if flags & 0x8 == 0 && interner.contains(value) => flags | 0x4,
+            _ => flags,
+        }
+    }
+
+    /// Returns the length of a header encoded with the `encode_indexed_name`
+    /// method.
+    fn indexed_name_len(&self, index: u32, value: &[u8], flags: u8) -> usize {
+        let prefix_size = if flags & 0x4 == 0x4 { 6 } else { 4 };
+        integer_len(index, prefix_size) + self.string_len(value, flags & 0x2 == 0x2, flags)
+    }
+
+    /// Returns `true` if the `data` should be encoded with Huffman algorithm.
+    /// When the `0x20` flag is set, Huffman is used only when the result is
+    /// shorter than the plain text, otherwise the `huffman` choice is kept.
+    /// Huffman is never used when it has been disabled.
+    fn use_huffman(&self, data: &[u8], huffman: bool, flags: u8) -> bool {
+        if self.huffman_disabled {
+            false
+        } else if flags & 0x20 == 0x20 {
+            huffman_len(data) < data.len()
+        } else {
+            huffman
+        }
+    }
+
+    /// Returns the length of a string encoded with the `encode_string`
+    /// function where the Huffman choice is resolved by `flags`.
+    fn string_len(&self, data: &[u8], huffman: bool, flags: u8) -> usize {
+        string_len(data, self.use_huffman(data, huffman, flags))
+    }
+
+    /// Returns the length of a header encoded with the `encode_literal` method.
+    fn literal_len(&self, name: &[u8], value: &[u8], flags: u8) -> usize {
+        1 + self.string_len(name, flags & 0x1 == 0x1, flags)
+            + self.string_len(value, flags & 0x2 == 0x2, flags)
+    }
+
+    /// Updates the maximum size of the dynamic table and encodes the new size
+    /// into a dynamic table size signal.
+    ///
+    /// The new maximum size MUST be lower than or equal to the limit determined
+    /// by the protocol using HPACK. In HTTP/2, this limit is the last value of
+    /// the `SETTINGS_HEADER_TABLE_SIZE` received from the decoder and
+    /// acknowledged by the encoder.
+    ///
+    /// **Maximum Dynamic table size change ([6.3.], figure 12):**
+    ///
+    /// ```txt
+    ///   0   1   2   3   4   5   6   7
+    /// +---+---+---+---+---+---+---+---+
+    /// | 0 | 0 | 1 |   Max size (5+)   |
+    /// +---+---------------------------+
+    /// ```
+    ///
+    /// On success the number of headers evicted from the dynamic table is
+    /// returned. The decoder evicts the same headers when it receives the
+    /// signal.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst).unwrap();
+    /// assert_eq!(encoder.update_max_dynamic_size(0, &mut dst), Ok(1));
+    /// ```
+    ///
+    /// [6.3]: https://tools.ietf.org/html/rfc7541#section-6.3
+    pub fn update_max_dynamic_size<W: Write>(
+        &mut self,
+        size: u32,
+        dst: W,
+    ) -> Result<u32, EncoderError> {
+        write_whole(integer_len(size, 5), dst, |buf| {
+            encode_integer(size, 0b00100000, 5, buf)
+        })?;
+        let evicted = self.table.evicted_len();
+        self.table.update_max_dynamic_size(size);
+        Ok((self.table.evicted_len() - evicted) as u32)
+    }
+
+    /// Evicts all the headers from the dynamic table and signals the decoder to
+    /// do the same.
+    ///
+    /// The encoder writes a dynamic table size update to `0`, which forces the
+    /// decoder to evict all the entries, followed by an update restoring the
+    /// current maximum size of the dynamic table ([4.2.]). Both signals must be
+    /// sent at the beginning of the next header block.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_hpack::Encoder;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// let mut dst = Vec::new();
+    /// encoder.clear_dynamic_table(&mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x20, 0x3f, 0xe1, 0x1f]); // sizes 0 and 4096
+    /// ```
+    ///
+    /// [4.2.]: https://tools.ietf.org/html/rfc7541#section-4.2
+    pub fn clear_dynamic_table<W: Write>(&mut self, mut dst: W) -> Result<(), EncoderError> {
+        let max_dynamic_size = self.table.max_dynamic_size();
+        self.update_max_dynamic_size(0, &mut dst)?;
+        self.table.clear_dynamic();
+        self.update_max_dynamic_size(max_dynamic_size, &mut dst)?;
+        Ok(())
+    }
+}
+
+impl<'a> Default for Encoder<'a> {
+    fn default() -> Self {
+        Self {
+            table: Table::default(),
+            interner: None,
+            sensitive: Vec::new(),
+            validate: false,
+            max_header_list_size: None,
+            header_list_size: 0,
+            huffman_cache: None,
+            compat: CompatMode::None,
+            preference: MatchPreference::default(),
+            minimal: false,
+            huffman_disabled: false,
+            huffman_strings: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should encode a header that exists in the indexing table into HPACK's
+    /// indexed header field representation ([6.1.], figure 5).
+    ///
+    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
+    #[test]
+    fn encodes_indexed() {
+        let mut encoder = Encoder::default();
+        encoder
+            .table
+            .insert(b"name62".to_vec(), b"value62".to_vec()); // add dynamic header
+        let fields = vec![
+            (2, vec![0x80 | 2]),   // (:method, GET)
+            (3, vec![0x80 | 3]),   // (:method, POST)
+            (14, vec![0x80 | 14]), // (:status, 500)
+            (62, vec![0x80 | 62]), // (name62, value62)
+        ];
+        for (index, res) in fields {
+            let mut dst = Vec::new();
+            encoder.encode(index, &mut dst).unwrap();
+            assert_eq!(dst, res);
+        }
+        assert_eq!(encoder.table.len(), 62); // only one header in dynamic table
+    }
+
+    /// Should encode a header, where its name is represented with an index and
+    /// the value is provided in bytes, into a literal header field
+    /// representation with incremental indexing ([6.2.1.], figure 6).
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    #[test]
+    fn encodes_indexed_name_with_indexing() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (
+            2, // index
+            b"PATCH".to_vec(),
+            0x2 | 0x4,
+        );
+        encoder.encode(field, &mut dst).unwrap(); // (:method, PATCH), Huffman
+        assert_eq!(dst[0] & 0b01000000, 64); // with incremental indexing
+        assert_eq!(dst[1] & 0b10000000, 128); // value encoded with Huffman
+        assert_eq!(&dst[2..], vec![215, 14, 251, 216, 255]); // value as huffman sequence
+        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
+        let entry = encoder.table.get(62).unwrap();
+        assert_eq!(entry.0, b":method"); // indexed name
+        assert_eq!(entry.1, b"PATCH"); // indexed value
+    }
+
+    /// Should encode a header, where its name and value are provided in bytes,
+    /// into a literal header field representation with incremental indexing
+    /// ([6.2.1.], figure 7).
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    #[test]
+    fn encodes_literal_with_indexing() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x4 | 0x1 | 0x2);
+        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), huffman(bar))
+        assert_eq!(dst[0], 0b01000000); // with incremental indexing
+        assert_eq!(&dst[1..4], vec![130, 148, 231]); // name as huffman sequence
+        assert_eq!(&dst[4..], vec![131, 140, 118, 127]); // value as huffman sequence
+        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
+        let entry = encoder.table.get(62).unwrap();
+        assert_eq!(entry.0, b"foo"); // indexed name
+        assert_eq!(entry.1, b"bar"); // indexed value
+    }
+
+    /// Should encode a header, where its name and value are provided in
+    /// borrowed bytes, into a literal header field representation with
+    /// incremental indexing ([6.2.1.], figure 7).
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    #[test]
+    fn encodes_borrowed_literal_with_indexing() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (b"foo".as_slice(), b"bar".as_slice(), 0x4 | 0x1 | 0x2);
+        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), huffman(bar))
+        assert_eq!(dst[0], 0b01000000); // with incremental indexing
+        assert_eq!(&dst[1..4], vec![130, 148, 231]); // name as huffman sequence
+        assert_eq!(&dst[4..], vec![131, 140, 118, 127]); // value as huffman sequence
+        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
+        let entry = encoder.table.get(62).unwrap();
+        assert_eq!(entry.0, b"foo"); // indexed name
+        assert_eq!(entry.1, b"bar"); // indexed value
+    }
+
+    /// Should encode a header, where its name is represented with an index and
+    /// the value is provided in bytes, into a literal header field
+    /// representation without indexing ([6.2.2.], figure 8). The indexing table
+    /// should not be altered.
+    ///
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    #[test]
+    fn encodes_indexed_name_without_indexing() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (13, b"PATCH".to_vec(), 0x0);
+        encoder.encode(field, &mut dst).unwrap(); // (:status, PATCH)
+        assert_eq!(dst[0], 13); // without indexing (matches index value)
+        assert_eq!(&dst[1..], vec![5, 80, 65, 84, 67, 72]); // value as string
+        assert_eq!(encoder.table.len(), 61); // table not altered
+    }
+
+    /// Should encode a header, where its name and value are provided in bytes,
+    /// into a literal header field representation without indexing ([6.2.2.],
+    /// figure 9). The indexing table should not be altered.
+    ///
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    #[test]
+    fn encodes_literal_without_indexing() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x1);
+        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), bar)
+        assert_eq!(dst[0], 0); // without indexing
+        assert_eq!(&dst[2..4], vec![148, 231]); // name as string
+        assert_eq!(&dst[4..], vec![3, 98, 97, 114]); // value as string
+        assert_eq!(encoder.table.len(), 61); // table not altered
+    }
+
+    /// Should encode a header, where its name is represented with an index and
+    /// the value is provided in bytes, into a never indexed literal header
+    /// field representation ([6.2.3.], figure 10). The indexing table should
+    /// not be altered.
+    ///
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    #[test]
+    fn encodes_indexed_name_never_indexed() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (13, b"PATCH".to_vec(), 0x8);
+        encoder.encode(field, &mut dst).unwrap(); // (:status, 501)
+        assert_eq!(dst[0] & 0b00010000, 16); // never indexed
+        assert_eq!(&dst[1..], vec![5, 80, 65, 84, 67, 72]); // value as string
+        assert_eq!(encoder.table.len(), 61); // table not altered
+    }
+
+    /// Should encode a header, where its name and value are provided in bytes,
+    /// into a never indexed literal header field representation ([6.2.3.],
+    /// figure 11). The indexing table should not be altered.
+    ///
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    #[test]
+    fn encodes_literal_never_indexed() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x8);
+        encoder.encode(field, &mut dst).unwrap(); // (foo, bar)
+        assert_eq!(dst[0], 0b00010000); // never indexed
+        assert_eq!(&dst[1..5], vec![3, 102, 111, 111]); // name as string
+        assert_eq!(&dst[5..], vec![3, 98, 97, 114]); // value as string
+        assert_eq!(encoder.table.len(), 61); // table not altered
+    }
+
+    /// Should encode a header, where its name and value are provided in bytes,
+    /// into the best header field representation.
+    #[test]
+    fn encodes_literal_automatically() {
+        let mut encoder = Encoder::default();
+        let fields = vec![
+            ((b":method".to_vec(), b"GET".to_vec(), 0x10), vec![130]), // (:method, GET) => index(2)
+            (
+                (b":method".to_vec(), b"DELETE".to_vec(), 0x10 | 0x4),
+                vec![66, 6, 68, 69, 76, 69, 84, 69],
+            ), // (:method, DELETE) => (index(2), DELETE)
+            (
+                (b"a".to_vec(), b"b".to_vec(), 0x10 | 0x1),
+                vec![0, 129, 31, 1, 98],
+            ), // (a, b) => (huffman(a), b)
+        ];
+        for (field, res) in fields {
+            let mut dst = Vec::new();
+            encoder.encode(field, &mut dst).unwrap();
+            assert_eq!(dst, res);
+        }
+        assert_eq!(encoder.table.len(), 62); // table altered only once
+    }
+
+    /// Should encode a list of headers into a single header block and stop at
+    /// the first invalid header.
+    #[test]
+    fn encodes_headers() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let fields = vec![
+            (b":method".to_vec(), b"GET".to_vec(), 0x10), // index(2)
+            (b"foo".to_vec(), b"bar".to_vec(), 0x4),      // literal with indexing
+            (b"foo".to_vec(), b"bar".to_vec(), 0x10),     // index(62)
+        ];
+        encoder.encode_headers(fields, &mut dst).unwrap();
+        assert_eq!(
+            dst,
+            vec![130, 64, 3, 102, 111, 111, 3, 98, 97, 114, 0x80 | 62]
+        );
+        assert_eq!(encoder.table.len(), 62); // table altered only once
+
+        let mut dst = Vec::new();
+        let res = encoder.encode_headers(vec![2, 100, 3], &mut dst);
+        assert_eq!(res, Err(EncoderError::InvalidIndex));
+        assert_eq!(dst, vec![0x80 | 2]); // only headers before the failing one
+    }
+
+    /// Should return the exact length of the encoded field for all the input
+    /// variants and flags.
+    #[test]
+    fn calculates_encoded_len() {
+        let long = vec![b'x'; 200];
+        let fields = vec![
+            EncoderInput::Indexed(2),
+            EncoderInput::IndexedNameBorrowed(2, b"PATCH", 0x0),
+            EncoderInput::IndexedNameBorrowed(60, b"PATCH", 0x2 | 0x4),
+            EncoderInput::IndexedNameOwned(20, long.clone(), 0x8),
+            EncoderInput::IndexedNameOwned(3, long.clone(), 0x2),
+            EncoderInput::LiteralBorrowed(b"foo", b"bar", 0x1 | 0x2),
+            EncoderInput::LiteralBorrowed(b"foo", &[0xfe, 0xff], 0x20),
+            EncoderInput::LiteralBorrowed(b":method", b"GET", 0x10),
+            EncoderInput::LiteralOwned(long.clone(), long.clone(), 0x4 | 0x8),
+            EncoderInput::LiteralOwned(b":method".to_vec(), b"PATCH".to_vec(), 0x2 | 0x10),
+            EncoderInput::LiteralOwned(b"foo".to_vec(), b"bar".to_vec(), 0x10),
+            EncoderInput::LiteralOwned(b"foo".to_vec(), b"bar".to_vec(), 0x4), // index(62)
+            EncoderInput::LiteralOwned(b"foo".to_vec(), b"bar".to_vec(), 0x10),
+            EncoderInput::LiteralOwned(b"foo".to_vec(), b"baz".to_vec(), 0x1 | 0x10),
+        ];
+        let mut encoder = Encoder::default();
+        for field in fields {
+            let len = encoder.encoded_len(&field);
+            let mut dst = Vec::new();
+            encoder.encode(field, &mut dst).unwrap();
+            assert_eq!(len, dst.len());
+        }
+    }
+
+    /// Should evict all the dynamic headers while keeping the maximum allowed
+    /// size of the dynamic table. The next indexed header should be inserted at
+    /// index 62 again.
+    #[test]
+    fn resets_dynamic_table() {
+        let mut encoder = Encoder::with_dynamic_size(100);
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        encoder
+            .encode((b"baz".to_vec(), b"qux".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.table.dynamic_len(), 2);
+        encoder.reset();
+        assert_eq!(encoder.table.dynamic_len(), 0);
+        assert_eq!(encoder.table.dynamic_size(), 0);
+        assert_eq!(encoder.table.len(), 61);
+        assert_eq!(encoder.max_dynamic_size(), 100);
+        encoder
+            .encode((b"baz".to_vec(), b"qux".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.table.get(62), Some((&b"baz"[..], &b"qux"[..])));
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"baz".to_vec(), b"qux".to_vec(), 0x10), &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x80 | 62]);
+    }
+
+    /// Should index a header with an interned value when the best literal
+    /// representation is requested so that its next occurrence is encoded as
+    /// an indexed reference.
+    #[test]
+    fn encodes_interned_value() {
+        let interner: Interner = vec![b"application/json".to_vec()].into_iter().collect();
+        let mut encoder = Encoder::default();
+        encoder.set_interner(Arc::new(interner));
+        let mut dst = Vec::new();
+        let field = (b"content-type".to_vec(), b"application/json".to_vec(), 0x10);
+        encoder.encode(field.clone(), &mut dst).unwrap(); // index(31) with indexing
+        assert_eq!(dst[0], 0x40 | 31);
+        assert_eq!(encoder.table.dynamic_len(), 1);
+        let mut dst = Vec::new();
+        encoder.encode(field, &mut dst).unwrap();
+        assert_eq!(dst, vec![0x80 | 62]);
+        let mut dst = Vec::new();
+        let field = (b"content-type".to_vec(), b"text/plain".to_vec(), 0x10);
+        encoder.encode(field, &mut dst).unwrap(); // not interned
+        assert_eq!(dst[0..2], [15, 16]); // index(31) without indexing
+        assert_eq!(encoder.table.dynamic_len(), 1);
+    }
+
+    /// Should encode headers with a sensitive name as never indexed literals
+    /// regardless of the provided flags and the letter case of the name.
+    #[test]
+    fn encodes_sensitive_headers() {
+        let mut encoder = Encoder::default();
+        encoder.mark_sensitive(b"Cookie");
+        encoder.table.insert(b"cookie".to_vec(), b"a=b".to_vec()); // index(62)
+        let fields = vec![
+            (b"cookie".to_vec(), b"a=b".to_vec(), 0x4 | 0x10),
+            (b"COOKIE".to_vec(), b"a=b".to_vec(), 0x4),
+        ];
+        let mut dst = Vec::new();
+        encoder.encode_headers(fields, &mut dst).unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                0x10 | 15,
+                47,
+                3,
+                97,
+                61,
+                98, // index(62) never indexed
+                0x10,
+                6,
+                67,
+                79,
+                79,
+                75,
+                73,
+                69,
+                3,
+                97,
+                61,
+                98, // literal never indexed
+            ]
+        );
+        let mut dst = Vec::new();
+        encoder
+            .encode((32, b"a=b".to_vec(), 0x4), &mut dst)
+            .unwrap(); // index(32) is cookie
+        assert_eq!(dst, vec![0x10 | 15, 17, 3, 97, 61, 98]);
+        assert_eq!(encoder.table.dynamic_len(), 1); // table not altered
+    }
+
+    /// Should reject invalid header names only when the validation is enabled.
+    #[test]
+    fn validates_header_names() {
+        let mut encoder = Encoder::default();
+        encoder.table.insert(b"X-Foo".to_vec(), b"bar".to_vec()); // index(62)
+        let mut dst = Vec::new();
+        encoder
+            .encode(
+                (b"Content-Type".to_vec(), b"text/plain".to_vec(), 0x0),
+                &mut dst,
+            )
+            .unwrap();
+        encoder.set_validation(true);
+        let fields = vec![
+            (b"Content-Type".to_vec(), b"text/plain".to_vec(), 0x4),
+            (b"".to_vec(), b"bar".to_vec(), 0x0),
+            (b"foo\r\n".to_vec(), b"bar".to_vec(), 0x0),
+        ];
+        for field in fields {
+            let mut dst = Vec::new();
+            let res = encoder.encode(field, &mut dst);
+            assert_eq!(res, Err(EncoderError::InvalidHeaderName));
+            assert!(dst.is_empty());
+        }
+        let mut dst = Vec::new();
+        let res = encoder.encode((62, b"baz".to_vec(), 0x4), &mut dst);
+        assert_eq!(res, Err(EncoderError::InvalidHeaderName));
+        assert_eq!(encoder.table.dynamic_len(), 1); // table not altered
+        encoder
+            .encode(
+                (b"content-type".to_vec(), b"text/plain".to_vec(), 0x4),
+                &mut dst,
+            )
+            .unwrap();
+        encoder
+            .encode((2, b"PATCH".to_vec(), 0x4), &mut dst)
+            .unwrap();
+    }
+
+    /// Should encode an HTTP/1.1 request head with pseudo-header fields first
+    /// ([C.4.1.]).
+    ///
+    /// [C.4.1.]: https://tools.ietf.org/html/rfc7541#appendix-C.4.1
+    #[test]
+    fn encodes_from_http1() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        let headers = vec![("Host", "www.example.com"), ("Connection", "keep-alive")];
+        encoder
+            .encode_from_http1("GET / HTTP/1.1", &headers, &mut dst)
+            .unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                0x82, 0x86, 0x84, 0x41, 0x8c, 0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab,
+                0x90, 0xf4, 0xff,
+            ]
+        );
+        let mut dst = Vec::new();
+        let headers = vec![("Host", "foo"), ("Accept", "*/*")];
+        encoder
+            .encode_from_http1("GET https://www.example.com HTTP/1.1", &headers, &mut dst)
+            .unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                0x82, // (:method, GET)
+                0x87, // (:scheme, https)
+                0x84, // (:path, /)
+                0xbe, // (:authority, www.example.com)
+                0x53, 0x83, 0xf9, 0x63, 0xe7, // (accept, */*)
+            ]
+        );
+        for line in ["GET /", "GET  HTTP/1.1", "GET / HTTP/2", "GET / HTTP/1.1 x"] {
+            let res = encoder.encode_from_http1(line, &[], &mut Vec::new());
+            assert_eq!(res, Err(EncoderError::InvalidInput));
+        }
+    }
+
+    /// Should encode headers against a custom static table where the dynamic
+    /// table starts right after the last custom entry.
+    #[test]
+    fn encodes_with_static_table() {
+        let entries = vec![
+            (b"x-a".to_vec(), b"1".to_vec()),
+            (b"x-b".to_vec(), b"2".to_vec()),
+        ];
+        let mut encoder = Encoder::with_static_table(entries, 4096);
+        let fields = vec![
+            (b"x-b".to_vec(), b"2".to_vec(), 0x10),       // index(2)
+            (b"x-a".to_vec(), b"3".to_vec(), 0x4 | 0x10), // index(1) with indexing
+            (b"x-a".to_vec(), b"3".to_vec(), 0x10),       // index(3)
+        ];
+        let mut dst = Vec::new();
+        encoder.encode_headers(fields, &mut dst).unwrap();
+        assert_eq!(dst, vec![0x80 | 2, 0x40 | 1, 1, 51, 0x80 | 3]);
+        let res = encoder.encode(4, &mut dst);
+        assert_eq!(res, Err(EncoderError::InvalidIndex));
+    }
+
+    /// Should encode strings with Huffman algorithm only when the result is
+    /// shorter than the plain text.
+    #[test]
+    fn encodes_huffman_if_shorter() {
+        let mut encoder = Encoder::default();
+        let binary = vec![0xfe, 0xff, 0x00, 0x01]; // Huffman is longer
+        let mut dst = Vec::new();
+        encoder
+            .encode((2, binary.clone(), 0x2 | 0x20), &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![2, 4, 0xfe, 0xff, 0x00, 0x01]); // plain text
+        let mut dst = Vec::new();
+        encoder.encode((2, binary.clone(), 0x2), &mut dst).unwrap();
+        assert_eq!(dst[1], 0x80 | 12); // Huffman encoded
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"foo".to_vec(), binary, 0x20), &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0, 0x80 | 2, 148, 231, 4, 0xfe, 0xff, 0x00, 0x01]);
+    }
+
+    /// Should choose between Huffman and the plain text independently for
+    /// the name and the value of a literal header.
+    #[test]
+    fn encodes_huffman_if_shorter_independently() {
+        let mut encoder = Encoder::default();
+        let name = b"x-custom-header-name".to_vec(); // Huffman is shorter
+        let value = vec![0xfe, 0xff]; // Huffman is longer
+        let mut dst = Vec::new();
+        encoder
+            .encode((name.clone(), value.clone(), 0x20), &mut dst)
+            .unwrap();
+        let name_len = huffman_len(&name);
+        assert!(name_len < name.len());
+        assert_eq!(dst[1], 0x80 | name_len as u8); // Huffman encoded name
+        assert_eq!(dst[2 + name_len..], [2, 0xfe, 0xff]); // plain text value
+        let mut dst = Vec::new();
+        encoder
+            .encode((value.clone(), name.clone(), 0x20), &mut dst)
+            .unwrap();
+        assert_eq!(dst[1..4], [2, 0xfe, 0xff]); // plain text name
+        assert_eq!(dst[4], 0x80 | name_len as u8); // Huffman encoded value
+        let mut fields = Vec::new();
+        Decoder::default().decode(&mut dst, &mut fields).unwrap();
+        assert_eq!(fields, vec![(value, name, 0x0)]);
+    }
+
+    /// Should list the headers of the indexing table with their indexes.
+    #[test]
+    fn lists_table_entries() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        let last = encoder.table_entries().last();
+        assert_eq!(last, Some((62, &b"foo"[..], &b"bar"[..])));
+        assert_eq!(encoder.table_entries().count(), 62);
+    }
+
+    /// Should track headers which have been indexed but never referenced
+    /// before their eviction.
+    #[test]
+    fn tracks_wasted_indexing() {
+        let mut encoder = Encoder::with_dynamic_size(76); // 76 can hold up to 2 headers
+        let mut dst = Vec::new();
+        let fields = vec![
+            (b"foo".to_vec(), b"bar".to_vec(), 0x4), // index(63) after next insert
+            (b"baz".to_vec(), b"qux".to_vec(), 0x4), // index(62)
+            (b"foo".to_vec(), b"bar".to_vec(), 0x10), // index(63)
+        ];
+        encoder.encode_headers(fields, &mut dst).unwrap();
+        assert_eq!(encoder.wasted_indexing_ratio(), 0.0);
+        encoder
+            .encode((b"a".to_vec(), b"b".to_vec(), 0x4), &mut dst)
+            .unwrap(); // evicts foo
+        assert_eq!(encoder.wasted_indexing_ratio(), 0.0);
+        encoder
+            .encode((b"c".to_vec(), b"d".to_vec(), 0x4), &mut dst)
+            .unwrap(); // evicts baz
+        assert_eq!(encoder.wasted_indexing_ratio(), 0.5);
+        encoder.reset(); // evicts a and c
+        assert_eq!(encoder.wasted_indexing_ratio(), 0.75);
+    }
+
+    /// Should allow sharing the encoder and the decoder between threads.
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Encoder>();
+        assert_send_sync::<crate::Decoder>();
+    }
+
+    /// Should report the representation of each encoded header.
+    #[test]
+    fn encodes_reported() {
+        let mut encoder = Encoder::default();
+        let fields = vec![
+            (
+                (b":method".to_vec(), b"GET".to_vec(), 0x10),
+                EncodedAs::Indexed,
+            ),
+            (
+                (b":method".to_vec(), b"PUT".to_vec(), 0x4 | 0x10),
+                EncodedAs::LiteralIndexed,
+            ),
+            (
+                (b":method".to_vec(), b"PUT".to_vec(), 0x10),
+                EncodedAs::Indexed,
+            ),
+            (
+                (b"foo".to_vec(), b"bar".to_vec(), 0x10),
+                EncodedAs::LiteralNoIndex,
+            ),
+            (
+                (b"foo".to_vec(), b"bar".to_vec(), 0x8 | 0x10),
+                EncodedAs::LiteralNeverIndex,
+            ),
+        ];
+        for (field, kind) in fields {
+            let mut dst = Vec::new();
+            assert_eq!(encoder.encode_reported(field, &mut dst), Ok(kind));
+        }
+    }
+
+    /// Should encode headers stored in a static array without copying them.
+    #[test]
+    fn encodes_static_headers() {
+        static INDEXED_NAMES: [(u32, &[u8], u8); 1] = [(2, b"PATCH", 0x0)];
+        static LITERALS: [(&[u8], &[u8], u8); 2] =
+            [(b":method", b"GET", 0x10), (b"foo", b"bar", 0x0)];
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        encoder
+            .encode_headers(INDEXED_NAMES.iter(), &mut dst)
+            .unwrap();
+        encoder.encode_headers(LITERALS.iter(), &mut dst).unwrap();
+        assert_eq!(
+            dst,
+            vec![
+                2,
+                5,
+                80,
+                65,
+                84,
+                67,
+                72,
+                0x80 | 2,
+                0,
+                3,
+                102,
+                111,
+                111,
+                3,
+                98,
+                97,
+                114
+            ]
+        );
+    }
+
+    /// Should reject a header which would exceed the maximum allowed size of
+    /// the header list until the counter is reset.
+    #[test]
+    fn limits_header_list_size() {
+        let mut encoder = Encoder::with_max_header_list_size(38 + 42 + 38);
+        let mut dst = Vec::new();
+        let fields = vec![
+            EncoderInput::from((b"foo".to_vec(), b"bar".to_vec(), 0x4)), // size 38
+            EncoderInput::from(2),                                       // (:method, GET), size 42
+            EncoderInput::from(62),                                      // (foo, bar), size 38
+        ];
+        encoder.encode_headers(fields, &mut dst).unwrap();
+        assert_eq!(encoder.header_list_size(), 118); // exactly at the limit
+        let res = encoder.encode((b"a".to_vec(), b"".to_vec(), 0x0), &mut dst);
+        assert_eq!(res, Err(EncoderError::HeaderListTooLarge));
+        assert_eq!(
+            dst,
+            vec![0x40, 3, 102, 111, 111, 3, 98, 97, 114, 0x80 | 2, 0x80 | 62]
+        );
+        encoder.reset_header_list_counter();
+        encoder
+            .encode((b"a".to_vec(), b"".to_vec(), 0x0), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.header_list_size(), 33);
+    }
+
+    /// Should evict all the dynamic headers and write a size update to 0
+    /// followed by a size update restoring the maximum size.
+    #[test]
+    fn clears_dynamic_table() {
+        let mut encoder = Encoder::with_dynamic_size(100);
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.table.dynamic_len(), 1);
+        let mut dst = Vec::new();
+        encoder.clear_dynamic_table(&mut dst).unwrap();
+        assert_eq!(encoder.table.dynamic_len(), 0);
+        assert_eq!(encoder.max_dynamic_size(), 100);
+        assert_eq!(dst, vec![0x20, 0x3f, 69]); // sizes 0 and 100
+    }
+
+    /// Should encode a header with a lazily produced value and never invoke the
+    /// closure for an invalid header name.
+    #[test]
+    fn encodes_lazy_value() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        encoder
+            .encode_lazy_value(b"foo", || b"bar".to_vec(), 0x4, &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x40, 3, 102, 111, 111, 3, 98, 97, 114]);
+        let mut dst = Vec::new();
+        encoder
+            .encode_lazy_value(b"foo", || b"bar".to_vec(), 0x10, &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x80 | 62]);
+        encoder.set_validation(true);
+        let mut invoked = false;
+        let res = encoder.encode_lazy_value(
+            b"Foo",
+            || {
+                invoked = true;
+                b"bar".to_vec()
+            },
+            0x10,
+            Vec::new(),
+        );
+        assert_eq!(res, Err(EncoderError::InvalidHeaderName));
+        assert!(!invoked);
+    }
+
+    /// Should compute the Huffman encoding of a repeated value only once when
+    /// the cache is set.
+    #[test]
+    fn reuses_cached_huffman() {
+        let mut encoder = Encoder::default();
+        encoder.set_huffman_cache(HuffmanCache::new());
+        let mut dst = Vec::new();
+        for _ in 0..2 {
+            encoder
+                .encode((b"foo".to_vec(), b"bar".to_vec(), 0x2), &mut dst)
+                .unwrap();
+        }
+        let cache = encoder.huffman_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        let mut expected = Vec::new();
+        for _ in 0..2 {
+            Encoder::default()
+                .encode((b"foo".to_vec(), b"bar".to_vec(), 0x2), &mut expected)
+                .unwrap();
+        }
+        assert_eq!(dst, expected);
+    }
+
+    /// Should evict a matching dynamic header so that it is no longer found.
+    #[test]
+    fn evicts_entry() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"secret".to_vec(), b"123".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.table.find(b"secret", b"123"), Some((63, true)));
+        assert!(encoder.evict_entry(b"secret", b"123"));
+        assert_eq!(encoder.table.dynamic_len(), 1);
+        assert_eq!(encoder.table.dynamic_size(), 38);
+        assert_eq!(encoder.table.find(b"secret", b"123"), None);
+        assert!(!encoder.evict_entry(b"secret", b"123"));
+    }
+
+    /// Should encode header blocks byte-identical to the nghttp2 deflater with
+    /// the default dynamic table size. The expected bytes have been captured
+    /// from nghttp2 1.67.
+    #[test]
+    fn encodes_nghttp2_compat() {
+        let mut encoder = Encoder::default();
+        encoder.set_compat(CompatMode::Nghttp2);
+        let blocks = [
+            (
+                vec![
+                    (":method", "GET"),
+                    (":scheme", "https"),
+                    (":path", "/index.html"),
+                    (":authority", "www.example.com"),
+                    ("user-agent", "httlib/1.0"),
+                    ("accept", "*/*"),
+                    ("cookie", "a=1"),
+                    ("authorization", "Bearer abc"),
+                    ("set-cookie", "id=123"),
+                ],
+                [
+                    "828785418cf1e3c2e5f23a6ba0ab90f4ff7a879d29a0d1b015c153032a2f2a",
+                    "1f11821c011f0887ba51d85b141c640f28853490044cff",
+                ]
+                .concat(),
+            ),
+            (
+                vec![
+                    (":method", "POST"),
+                    (":scheme", "https"),
+                    (":path", "/index.html"),
+                    (":authority", "www.example.com"),
+                    ("user-agent", "httlib/1.0"),
+                    ("content-length", "42"),
+                    ("x-custom", "foo"),
+                    ("cookie", "session=0123456789abcdef"),
+                    ("x-custom", "foo"),
+                ],
+                [
+                    "838785c0bf0f0d0234324086f2b12d424f4f8294e760914150831ea8001132d3",
+                    "6e3af3e38c92165fbf",
+                ]
+                .concat(),
+            ),
+        ];
+        for (headers, expected) in blocks.iter() {
+            let mut dst = Vec::new();
+            for (name, value) in headers {
+                let field = (name.as_bytes(), value.as_bytes(), 0x0);
+                encoder.encode(field, &mut dst).unwrap();
+            }
+            assert_eq!(dst, hex::decode(expected).unwrap());
+        }
     }
-}
 
-impl<'a> Default for Encoder<'a> {
-    fn default() -> Self {
-        Self {
-            table: Table::default(),
+    /// Should return the exact length of the encoded field in the nghttp2
+    /// compatibility mode.
+    #[test]
+    fn calculates_nghttp2_encoded_len() {
+        let mut encoder = Encoder::with_dynamic_size(100);
+        encoder.set_compat(CompatMode::Nghttp2);
+        let fields = vec![
+            (":path", "/", 0x0),
+            (":path", "/index.html", 0x0),
+            ("cookie", "a=1", 0x0),
+            ("cookie", "session=0123456789abcdef", 0x0),
+            ("x-custom", "foo", 0x0),
+            ("x-custom", "foo", 0x0),
+            ("x-custom", "bar", 0x8),
+            ("authorization", "Bearer abc", 0x4),
+            (
+                "x-large",
+                "0123456789012345678901234567890123456789012345",
+                0x4,
+            ),
+        ];
+        for (name, value, flags) in fields {
+            let field = EncoderInput::from((name.as_bytes(), value.as_bytes(), flags));
+            let len = encoder.encoded_len(&field);
+            let mut dst = Vec::new();
+            encoder.encode(field, &mut dst).unwrap();
+            assert_eq!(len, dst.len(), "{}", name);
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Should encode all the loaded sensitive names as never indexed literals
+    /// regardless of the requested flags.
+    #[test]
+    fn loads_sensitive_names() {
+        let mut encoder = Encoder::default();
+        encoder.load_sensitive_names(vec![b"authorization".to_vec(), b"cookie".to_vec()]);
+        let mut dst = Vec::new();
+        encoder
+            .encode(
+                (b"authorization".to_vec(), b"a".to_vec(), 0x4 | 0x10),
+                &mut dst,
+            )
+            .unwrap();
+        assert_eq!(dst, vec![0b00011111, 8, 1, 97]); // never indexed, name index 23
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"Cookie".to_vec(), b"a".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(dst[0], 0b00010000); // never indexed, literal name
+        assert_eq!(encoder.table.dynamic_len(), 0);
+    }
 
-    /// Should encode a header that exists in the indexing table into HPACK's
-    /// indexed header field representation ([6.1.], figure 5).
-    ///
-    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
+    /// Should encode a fully indexed header provided as a single item tuple
+    /// exactly as a plain index.
     #[test]
-    fn encodes_indexed() {
+    fn encodes_indexed_tuple() {
+        let mut encoder = Encoder::default();
+        let mut dst = Vec::new();
+        encoder.encode((2,), &mut dst).unwrap();
+        encoder.encode(2, &mut dst).unwrap();
+        assert_eq!(dst, vec![0x80 | 2, 0x80 | 2]);
+        assert!(matches!(EncoderInput::from((2,)), EncoderInput::Indexed(2)));
+    }
+
+    /// Should keep the indexing tables of the encoder and the decoder synced
+    /// after a block with indexed headers is decoded.
+    #[test]
+    fn syncs_with_decoder() {
+        let mut encoder = Encoder::with_dynamic_size(100);
+        let mut decoder = Decoder::with_dynamic_size(100);
+        assert!(encoder.is_synced_with(&decoder));
+        let mut buf = Vec::new();
+        for i in 0..5 {
+            let value = format!("value{}", i).into_bytes();
+            let field = (b"x-field".to_vec(), value, 0x4 | 0x2);
+            encoder.encode(field, &mut buf).unwrap();
+        }
+        encoder
+            .encode((b":method".to_vec(), b"GET".to_vec(), 0x4), &mut buf)
+            .unwrap();
+        assert!(!encoder.is_synced_with(&decoder));
+        decoder.decode(&mut buf, &mut Vec::new()).unwrap();
+        assert!(encoder.table_entries().count() > 61);
+        assert!(encoder.is_synced_with(&decoder));
+        encoder.evict_entry(b"x-field", b"value4");
+        assert!(!encoder.is_synced_with(&decoder));
+    }
+
+    /// Should pass through an already Huffman encoded value and produce the
+    /// same bytes as encoding the plain value with Huffman.
+    #[test]
+    fn encodes_indexed_name_prehuffman() {
         let mut encoder = Encoder::default();
+        let value = b"Mon, 21 Oct 2013 20:13:21 GMT";
+        let mut huffman_bytes = Vec::new();
+        httlib_huffman::encode(value, &mut huffman_bytes).unwrap();
+        let mut expected = Vec::new();
         encoder
-            .table
-            .insert(b"name62".to_vec(), b"value62".to_vec()); // add dynamic header
-        let fields = vec![
-            (2, vec![0x80 | 2]),   // (:method, GET)
-            (3, vec![0x80 | 3]),   // (:method, POST)
-            (14, vec![0x80 | 14]), // (:status, 500)
-            (62, vec![0x80 | 62]), // (name62, value62)
+            .encode_indexed_name(33, value, 0x2, &mut expected)
+            .unwrap();
+        let mut dst = Vec::new();
+        encoder
+            .encode_indexed_name_prehuffman(33, &huffman_bytes, &mut dst)
+            .unwrap();
+        assert_eq!(dst, expected);
+        assert_eq!(encoder.table.dynamic_len(), 0);
+        let mut decoder = crate::Decoder::default();
+        let mut fields = Vec::new();
+        decoder.decode(&mut dst, &mut fields).unwrap();
+        assert_eq!(fields, vec![(b"date".to_vec(), value.to_vec(), 0x0)]);
+        assert_eq!(
+            encoder.encode_indexed_name_prehuffman(100, &huffman_bytes, &mut dst),
+            Err(EncoderError::IndexOutOfRange)
+        );
+    }
+
+    /// Should encode each header with the smallest of all possible
+    /// representations without touching the dynamic table.
+    #[test]
+    fn encodes_minimal() {
+        let mut encoder = Encoder::default();
+        encoder.set_minimal(true);
+        let reference = Encoder::default();
+        let headers: Vec<(&[u8], &[u8])> = vec![
+            (b":method", b"GET"),
+            (b":method", b"PATCH"),
+            (b"accept-encoding", b"gzip, deflate"),
+            (b"user-agent", b"Mozilla/5.0 (X11; Linux x86_64)"),
+            (b"x-request-id", b"8b1f0c2e-6a0d-4a55-9a49-4f1c9d1e7c41"),
+            (b"x", b"\x00\xff"),
+            (b"date", b""),
         ];
-        for (index, res) in fields {
+        for (name, value) in headers {
+            let mut candidates = Vec::new();
+            if let Some((index, full)) = reference.table.find(name, value) {
+                if full {
+                    candidates.push(integer_len(index as u32, 7));
+                }
+                for flags in &[0x0, 0x2] {
+                    candidates.push(reference.indexed_name_len(index as u32, value, *flags));
+                }
+            }
+            for flags in &[0x0, 0x1, 0x2, 0x3] {
+                candidates.push(reference.literal_len(name, value, *flags));
+            }
+            let field = (name.to_vec(), value.to_vec(), 0x4);
+            assert_eq!(
+                encoder.encoded_len(&field.clone().into()),
+                *candidates.iter().min().unwrap()
+            );
             let mut dst = Vec::new();
-            encoder.encode(index, &mut dst).unwrap();
-            assert_eq!(dst, res);
+            encoder.encode(field, &mut dst).unwrap();
+            assert_eq!(dst.len(), *candidates.iter().min().unwrap());
+            let mut decoded = Vec::new();
+            Decoder::default().decode(&mut dst, &mut decoded).unwrap();
+            assert_eq!(decoded[0].0, name);
+            assert_eq!(decoded[0].1, value);
         }
-        assert_eq!(encoder.table.len(), 62); // only one header in dynamic table
+        let mut dst = Vec::new();
+        encoder.encode((2, b"PUT".to_vec(), 0x4), &mut dst).unwrap();
+        assert_eq!(dst, vec![0x02, 0x03, b'P', b'U', b'T']);
+        assert_eq!(encoder.table.dynamic_len(), 0);
     }
 
-    /// Should encode a header, where its name is represented with an index and
-    /// the value is provided in bytes, into a literal header field
-    /// representation with incremental indexing ([6.2.1.], figure 6).
-    ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// Should summarize the representations of all encoded headers.
     #[test]
-    fn encodes_indexed_name_with_indexing() {
+    fn encodes_headers_summarized() {
         let mut encoder = Encoder::default();
+        encoder.mark_sensitive(b"authorization");
+        let fields = vec![
+            (b":method".to_vec(), b"GET".to_vec(), 0x10), // indexed
+            (b":path".to_vec(), b"/about".to_vec(), 0x2 | 0x10), // literal, 1 Huffman
+            (b"x-custom".to_vec(), b"value".to_vec(), 0x1 | 0x2 | 0x4), // literal, 2 Huffman
+            (b"x-custom".to_vec(), b"value".to_vec(), 0x10), // indexed
+            (b"authorization".to_vec(), b"secret".to_vec(), 0x0), // never indexed
+        ];
         let mut dst = Vec::new();
-        let field = (
-            2, // index
-            b"PATCH".to_vec(),
-            0x2 | 0x4,
+        let summary = encoder
+            .encode_headers_summarized(fields.clone(), &mut dst)
+            .unwrap();
+        assert_eq!(
+            summary,
+            BlockSummary {
+                field_count: 5,
+                indexed: 2,
+                literal: 2,
+                never_indexed: 1,
+                bytes: dst.len(),
+                huffman_strings: 3,
+            }
         );
-        encoder.encode(field, &mut dst).unwrap(); // (:method, PATCH), Huffman
-        assert_eq!(dst[0] & 0b01000000, 64); // with incremental indexing
-        assert_eq!(dst[1] & 0b10000000, 128); // value encoded with Huffman
-        assert_eq!(&dst[2..], vec![215, 14, 251, 216, 255]); // value as huffman sequence
-        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
-        let entry = encoder.table.get(62).unwrap();
-        assert_eq!(entry.0, b":method"); // indexed name
-        assert_eq!(entry.1, b"PATCH"); // indexed value
+        let mut reference = Encoder::default();
+        reference.mark_sensitive(b"authorization");
+        let mut expected = Vec::new();
+        reference.encode_headers(fields, &mut expected).unwrap();
+        assert_eq!(dst, expected);
     }
 
-    /// Should encode a header, where its name and value are provided in bytes,
-    /// into a literal header field representation with incremental indexing
-    /// ([6.2.1.], figure 7).
-    ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// Should report the highest size the dynamic table has reached rather
+    /// than its current size.
     #[test]
-    fn encodes_literal_with_indexing() {
+    fn tracks_peak_dynamic_size() {
+        let mut encoder = Encoder::with_dynamic_size(100);
+        let mut dst = Vec::new();
+        assert_eq!(encoder.peak_dynamic_size(), 0);
+        encoder
+            .encode((b"x-a".to_vec(), b"1".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 36
+        encoder
+            .encode((b"x-b".to_vec(), b"1".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 72
+        assert_eq!(encoder.peak_dynamic_size(), 72);
+        encoder
+            .encode((b"x-c".to_vec(), b"1".to_vec(), 0x4), &mut dst)
+            .unwrap(); // evicts x-a
+        assert_eq!(encoder.table.dynamic_size(), 72);
+        assert_eq!(encoder.peak_dynamic_size(), 72);
+        encoder.evict_entry(b"x-c", b"1");
+        encoder.clear_dynamic_table(&mut dst).unwrap();
+        assert_eq!(encoder.table.dynamic_size(), 0);
+        assert_eq!(encoder.peak_dynamic_size(), 72);
+        encoder
+            .encode((b"x-long".to_vec(), b"12345678".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 46
+        assert_eq!(encoder.peak_dynamic_size(), 72);
+        encoder
+            .encode((b"x-long".to_vec(), b"87654321".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 92
+        assert_eq!(encoder.peak_dynamic_size(), 92);
+    }
+
+    /// Should encode pseudo-header fields before regular header fields and
+    /// reject misplaced pseudo-header fields.
+    #[test]
+    fn encodes_request_and_response() {
         let mut encoder = Encoder::default();
         let mut dst = Vec::new();
-        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x4 | 0x1 | 0x2);
-        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), huffman(bar))
-        assert_eq!(dst[0], 0b01000000); // with incremental indexing
-        assert_eq!(&dst[1..4], vec![130, 148, 231]); // name as huffman sequence
-        assert_eq!(&dst[4..], vec![131, 140, 118, 127]); // value as huffman sequence
-        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
-        let entry = encoder.table.get(62).unwrap();
-        assert_eq!(entry.0, b"foo"); // indexed name
-        assert_eq!(entry.1, b"bar"); // indexed value
+        let pseudo: Vec<(&[u8], &[u8])> = vec![(b":method", b"GET"), (b":path", b"/")];
+        let regular: Vec<(&[u8], &[u8])> = vec![(b"accept", b"*/*")];
+        encoder
+            .encode_request(&pseudo, &regular, 0x0, &mut dst)
+            .unwrap();
+        let mut decoder = Decoder::default();
+        let mut fields = Vec::new();
+        decoder.decode(&mut dst, &mut fields).unwrap();
+        let names: Vec<&[u8]> = fields.iter().map(|f| f.0.as_slice()).collect();
+        assert_eq!(names, vec![&b":method"[..], b":path", b"accept"]);
+
+        let mut dst = Vec::new();
+        let pseudo: Vec<(&[u8], &[u8])> = vec![(b":status", b"200")];
+        encoder
+            .encode_response(&pseudo, &[], 0x10, &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x88]);
+
+        let mut dst = Vec::new();
+        let invalid: Vec<(&[u8], &[u8])> = vec![(b"status", b"200")];
+        assert_eq!(
+            encoder.encode_response(&invalid, &[], 0x0, &mut dst),
+            Err(EncoderError::PseudoHeaderOrder)
+        );
+        assert_eq!(
+            encoder.encode_request(&[], &pseudo, 0x0, &mut dst),
+            Err(EncoderError::PseudoHeaderOrder)
+        );
+        assert!(dst.is_empty());
     }
 
-    /// Should encode a header, where its name and value are provided in
-    /// borrowed bytes, into a literal header field representation with
-    /// incremental indexing ([6.2.1.], figure 7).
-    ///
-    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    /// Should look up the index of a header in the static and the dynamic
+    /// tables.
     #[test]
-    fn encodes_borrowed_literal_with_indexing() {
+    fn finds_index_of_header() {
         let mut encoder = Encoder::default();
+        assert_eq!(encoder.index_of(b"x-foo", b"bar"), None);
+        encoder
+            .encode((b"x-foo".to_vec(), b"bar".to_vec(), 0x4), &mut Vec::new())
+            .unwrap();
+        assert_eq!(encoder.index_of(b"x-foo", b"bar"), Some((62, true)));
+        assert_eq!(encoder.index_of(b"x-foo", b"baz"), Some((62, false)));
+        assert_eq!(encoder.index_of(b":path", b"/"), Some((4, true)));
+    }
+
+    /// Should report the size of the dynamic table as the sum of the entry
+    /// sizes ([4.1.]).
+    ///
+    /// [4.1.]: https://tools.ietf.org/html/rfc7541#section-4.1
+    #[test]
+    fn reports_dynamic_size() {
+        let mut encoder = Encoder::with_dynamic_size(100);
         let mut dst = Vec::new();
-        let field = (b"foo".as_slice(), b"bar".as_slice(), 0x4 | 0x1 | 0x2);
-        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), huffman(bar))
-        assert_eq!(dst[0], 0b01000000); // with incremental indexing
-        assert_eq!(&dst[1..4], vec![130, 148, 231]); // name as huffman sequence
-        assert_eq!(&dst[4..], vec![131, 140, 118, 127]); // value as huffman sequence
-        assert_eq!(encoder.table.len(), 62); // inserted into indexing table
-        let entry = encoder.table.get(62).unwrap();
-        assert_eq!(entry.0, b"foo"); // indexed name
-        assert_eq!(entry.1, b"bar"); // indexed value
+        assert_eq!(encoder.dynamic_size(), 0);
+        encoder
+            .encode((b"x-foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 5 + 3 + 32
+        assert_eq!(encoder.dynamic_size(), 40);
+        encoder
+            .encode((b"x-a".to_vec(), b"12345".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 3 + 5 + 32
+        assert_eq!(encoder.dynamic_size(), 80);
+        encoder
+            .encode((b"x-b".to_vec(), b"1".to_vec(), 0x4), &mut dst)
+            .unwrap(); // 3 + 1 + 32, evicts the first entry
+        assert_eq!(encoder.dynamic_size(), 76);
+        assert_eq!(encoder.max_dynamic_size(), 100);
     }
 
-    /// Should encode a header, where its name is represented with an index and
-    /// the value is provided in bytes, into a literal header field
-    /// representation without indexing ([6.2.2.], figure 8). The indexing table
-    /// should not be altered.
+    /// Should encode a header which does not fit into the dynamic table
+    /// without indexing and leave the dynamic table untouched ([4.4.]).
     ///
-    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    /// [4.4.]: https://tools.ietf.org/html/rfc7541#section-4.4
     #[test]
-    fn encodes_indexed_name_without_indexing() {
+    fn skips_indexing_oversized_headers() {
+        let mut encoder = Encoder::with_dynamic_size(64);
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"x-a".to_vec(), b"1".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        let value = vec![b'a'; 64];
+        let mut dst = Vec::new();
+        encoder
+            .encode((b"x-big".to_vec(), value.clone(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(dst[0], 0x0); // without indexing
+        let mut dst = Vec::new();
+        encoder
+            .encode(
+                (b"user-agent".to_vec(), value.clone(), 0x4 | 0x10),
+                &mut dst,
+            )
+            .unwrap();
+        assert_eq!(dst[0], 0x0f); // without indexing, index(58)
+        let field = EncoderInput::from((b"user-agent".to_vec(), value, 0x4 | 0x10));
+        assert_eq!(encoder.encoded_len(&field), dst.len());
+        assert_eq!(encoder.dynamic_size(), 36);
+        assert_eq!(
+            encoder.table_entries().nth(61),
+            Some((62, &b"x-a"[..], &b"1"[..]))
+        );
+    }
+
+    /// Should build the encoder input with named flag methods.
+    #[test]
+    fn builds_encoder_input() {
+        let field = FieldBuilder::literal("x-foo", b"bar")
+            .huffman_name()
+            .huffman_value()
+            .with_indexing();
+        assert!(matches!(
+            EncoderInput::from(field),
+            EncoderInput::LiteralBorrowed(b"x-foo", b"bar", 0x7)
+        ));
+        let value = b"/index.html".to_vec();
+        let field = FieldBuilder::indexed_name(4, &value)
+            .never_indexed()
+            .best_format()
+            .huffman_if_shorter();
+        assert!(matches!(
+            EncoderInput::from(field),
+            EncoderInput::IndexedNameBorrowed(4, v, 0x38) if v == b"/index.html"
+        ));
+
         let mut encoder = Encoder::default();
         let mut dst = Vec::new();
-        let field = (13, b"PATCH".to_vec(), 0x0);
-        encoder.encode(field, &mut dst).unwrap(); // (:status, PATCH)
-        assert_eq!(dst[0], 13); // without indexing (matches index value)
-        assert_eq!(&dst[1..], vec![5, 80, 65, 84, 67, 72]); // value as string
-        assert_eq!(encoder.table.len(), 61); // table not altered
+        let field = FieldBuilder::literal(b":method", b"GET").best_format();
+        encoder.encode(field, &mut dst).unwrap();
+        assert_eq!(dst, vec![0x82]);
     }
 
-    /// Should encode a header, where its name and value are provided in bytes,
-    /// into a literal header field representation without indexing ([6.2.2.],
-    /// figure 9). The indexing table should not be altered.
-    ///
-    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    /// Should encode all strings as plain text while Huffman is disabled and
+    /// calculate the encoded length accordingly.
     #[test]
-    fn encodes_literal_without_indexing() {
+    fn disables_huffman() {
         let mut encoder = Encoder::default();
+        let field = (b"x-foo".to_vec(), b"bar".to_vec(), 0x1 | 0x2 | 0x20);
+        encoder.disable_huffman();
         let mut dst = Vec::new();
-        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x1);
-        encoder.encode(field, &mut dst).unwrap(); // (huffman(foo), bar)
-        assert_eq!(dst[0], 0); // without indexing
-        assert_eq!(&dst[2..4], vec![148, 231]); // name as string
-        assert_eq!(&dst[4..], vec![3, 98, 97, 114]); // value as string
-        assert_eq!(encoder.table.len(), 61); // table not altered
+        encoder.encode(field.clone(), &mut dst).unwrap();
+        assert_eq!(dst, [&[0x0, 0x5][..], b"x-foo", &[0x3], b"bar"].concat());
+        assert_eq!(encoder.encoded_len(&field.clone().into()), dst.len());
+        let mut dst = Vec::new();
+        encoder
+            .encode(
+                (b":path".to_vec(), b"/index.php".to_vec(), 0x2 | 0x10),
+                &mut dst,
+            )
+            .unwrap();
+        assert_eq!(dst, [&[0x04, 0x0a][..], b"/index.php"].concat());
+        encoder.enable_huffman();
+        let mut dst = Vec::new();
+        encoder.encode(field.clone(), &mut dst).unwrap();
+        assert_eq!(dst[1], 0x80 | 4); // Huffman encoded name
+        assert_eq!(encoder.encoded_len(&field.into()), dst.len());
     }
 
-    /// Should encode a header, where its name is represented with an index and
-    /// the value is provided in bytes, into a never indexed literal header
-    /// field representation ([6.2.3.], figure 10). The indexing table should
-    /// not be altered.
-    ///
-    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    /// Should clone the encoder into an independent snapshot whose dynamic
+    /// table can change without affecting the original encoder.
     #[test]
-    fn encodes_indexed_name_never_indexed() {
+    fn clones_independently() {
         let mut encoder = Encoder::default();
         let mut dst = Vec::new();
-        let field = (13, b"PATCH".to_vec(), 0x8);
-        encoder.encode(field, &mut dst).unwrap(); // (:status, 501)
-        assert_eq!(dst[0] & 0b00010000, 16); // never indexed
-        assert_eq!(&dst[1..], vec![5, 80, 65, 84, 67, 72]); // value as string
-        assert_eq!(encoder.table.len(), 61); // table not altered
+        encoder
+            .encode((b"a0".to_vec(), b"b0".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        let mut snapshot = encoder.clone();
+        snapshot
+            .encode((b"a1".to_vec(), b"b1".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        assert_eq!(encoder.dynamic_size(), 36);
+        assert_eq!(snapshot.dynamic_size(), 72);
+        assert_eq!(encoder.table_entries().count(), 62);
+        assert_eq!(snapshot.table_entries().count(), 63);
     }
 
-    /// Should encode a header, where its name and value are provided in bytes,
-    /// into a never indexed literal header field representation ([6.2.3.],
-    /// figure 11). The indexing table should not be altered.
-    ///
-    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    /// Should choose between the static and the dynamic table based on the
+    /// match preference when the best literal representation is requested.
     #[test]
-    fn encodes_literal_never_indexed() {
+    fn honors_match_preference() {
+        let examples = vec![
+            (
+                MatchPreference::LowestIndex,
+                vec![0x80 | 62],
+                vec![0x80 | 2],
+            ),
+            (
+                MatchPreference::StaticFirst,
+                vec![0x2, 0x3, 0x50, 0x55, 0x54], // static name, regardless of history
+                vec![0x80 | 2],
+            ),
+            (
+                MatchPreference::DynamicFirst,
+                vec![0x80 | 62],
+                vec![0x80 | 63],
+            ),
+        ];
+        for (preference, put, get) in examples {
+            let mut encoder = Encoder::default();
+            encoder.set_match_preference(preference);
+            let mut dst = Vec::new();
+            encoder
+                .encode((b":method".to_vec(), b"GET".to_vec(), 0x4), &mut dst)
+                .unwrap();
+            encoder
+                .encode((b":method".to_vec(), b"PUT".to_vec(), 0x4), &mut dst)
+                .unwrap();
+            dst.clear();
+            encoder
+                .encode((b":method".to_vec(), b"PUT".to_vec(), 0x10), &mut dst)
+                .unwrap();
+            assert_eq!(dst, put);
+            dst.clear();
+            encoder
+                .encode((b":method".to_vec(), b"GET".to_vec(), 0x10), &mut dst)
+                .unwrap();
+            assert_eq!(dst, get);
+        }
+    }
+
+    /// Should reject the index `0` and an index beyond the indexing table when
+    /// encoding a header with an indexed name, including a prehuffman value.
+    #[test]
+    fn rejects_indexed_name_out_of_range() {
         let mut encoder = Encoder::default();
         let mut dst = Vec::new();
-        let field = (b"foo".to_vec(), b"bar".to_vec(), 0x8);
-        encoder.encode(field, &mut dst).unwrap(); // (foo, bar)
-        assert_eq!(dst[0], 0b00010000); // never indexed
-        assert_eq!(&dst[1..5], vec![3, 102, 111, 111]); // name as string
-        assert_eq!(&dst[5..], vec![3, 98, 97, 114]); // value as string
-        assert_eq!(encoder.table.len(), 61); // table not altered
+        let res = encoder.encode_indexed_name(0, b"GET", 0x0, &mut dst);
+        assert_eq!(res, Err(EncoderError::IndexOutOfRange));
+        let res = encoder.encode_indexed_name(62, b"GET", 0x0, &mut dst);
+        assert_eq!(res, Err(EncoderError::IndexOutOfRange));
+        let res = encoder.encode((62, b"GET".to_vec(), 0x4), &mut dst);
+        assert_eq!(res, Err(EncoderError::IndexOutOfRange));
+        let res = encoder.encode_indexed_name_prehuffman(0, &[0x1c, 0x64], &mut dst);
+        assert_eq!(res, Err(EncoderError::IndexOutOfRange));
+        let res = encoder.encode_indexed_name_prehuffman(62, &[0x1c, 0x64], &mut dst);
+        assert_eq!(res, Err(EncoderError::IndexOutOfRange));
+        assert!(dst.is_empty());
+        encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .unwrap();
+        dst.clear();
+        encoder
+            .encode_indexed_name(62, b"baz", 0x0, &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x0f, 47, 3, 98, 97, 122]);
     }
 
-    /// Should encode a header, where its name and value are provided in bytes,
-    /// into the best header field representation.
+    /// Should write nothing into the destination when encoding fails because
+    /// of an invalid input.
     #[test]
-    fn encodes_literal_automatically() {
+    fn writes_nothing_on_failure() {
         let mut encoder = Encoder::default();
-        let fields = vec![
-            ((b":method".to_vec(), b"GET".to_vec(), 0x10), vec![130]), // (:method, GET) => index(2)
+        encoder.set_validation(true);
+        let mut dst = Vec::new();
+        assert!(encoder.encode(100, &mut dst).is_err());
+        assert!(encoder
+            .encode((100, b"bar".to_vec(), 0x4), &mut dst)
+            .is_err());
+        assert!(encoder
+            .encode((b"Foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .is_err());
+        encoder.set_max_header_list_size(10);
+        assert!(encoder
+            .encode((b"foo".to_vec(), b"bar".to_vec(), 0x4), &mut dst)
+            .is_err());
+        assert!(dst.is_empty());
+        assert_eq!(encoder.dynamic_size(), 0);
+        assert!(encoder.is_clean());
+    }
+
+    /// Should write nothing and leave the indexing table untouched when the
+    /// destination rejects a write, so that encoding can be retried.
+    #[test]
+    fn writes_atomically() {
+        /// A writer which rejects writes exceeding its capacity.
+        struct Limited {
+            buf: Vec<u8>,
+            limit: usize,
+        }
+        impl Write for Limited {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                if self.buf.len() + data.len() > self.limit {
+                    return Err(std::io::ErrorKind::WriteZero.into());
+                }
+                self.buf.extend_from_slice(data);
+                Ok(data.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = vec![b'x'; 200]; // assembled on the heap
+        let fields: Vec<(Vec<u8>, Vec<u8>, u8)> = vec![
+            (b"foo".to_vec(), b"bar".to_vec(), 0x4),
+            (b"foo".to_vec(), value.clone(), 0x4 | 0x2),
+            (b"foo".to_vec(), b"bar".to_vec(), 0x8 | 0x1),
+        ];
+        for limit in 0..3 {
+            for (name, value, flags) in &fields {
+                let mut encoder = Encoder::default();
+                let mut dst = Limited {
+                    buf: Vec::new(),
+                    limit,
+                };
+                let res = encoder.encode_literal(name, value, *flags, &mut dst);
+                assert_eq!(res, Err(EncoderError::IoError));
+                let res = encoder.encode_indexed_name(2, value, *flags, &mut dst);
+                assert_eq!(res, Err(EncoderError::IoError));
+                assert!(dst.buf.is_empty());
+                assert_eq!(encoder.dynamic_size(), 0);
+            }
+        }
+
+        let mut encoder = Encoder::default();
+        let mut dst = Limited {
+            buf: Vec::new(),
+            limit: 1,
+        };
+        assert!(encoder.encode_indexed(2, &mut dst).is_ok());
+        assert!(encoder.update_max_dynamic_size(4096, &mut dst).is_err());
+        assert!(encoder
+            .encode_indexed_name_prehuffman(2, &[0x1c, 0x64], &mut dst)
+            .is_err());
+        assert_eq!(dst.buf, vec![0x80 | 2]);
+        let mut dst = Limited {
+            buf: Vec::new(),
+            limit: 64,
+        };
+        encoder
+            .encode_literal(b"foo", &value[..50], 0x4, &mut dst)
+            .unwrap();
+        encoder
+            .encode_indexed_name(2, b"PUT", 0x4 | 0x2, &mut dst)
+            .unwrap();
+        let mut decoder = crate::Decoder::default();
+        let mut fields = Vec::new();
+        decoder.decode(&mut dst.buf, &mut fields).unwrap();
+        assert_eq!(fields[1], (b":method".to_vec(), b"PUT".to_vec(), 0x4));
+        assert_eq!(encoder.dynamic_size(), 32 + 53 + 32 + 10);
+    }
+
+    /// Should compare encoder inputs by their content regardless of whether
+    /// the bytes are owned or borrowed.
+    #[test]
+    fn compares_inputs() {
+        let examples: Vec<(EncoderInput, EncoderInput, bool)> = vec![
+            (2.into(), EncoderInput::Indexed(2), true),
+            (2.into(), 3.into(), false),
             (
-                (b":method".to_vec(), b"DELETE".to_vec(), 0x10 | 0x4),
-                vec![66, 6, 68, 69, 76, 69, 84, 69],
-            ), // (:method, DELETE) => (index(2), DELETE)
+                (2, b"GET".to_vec(), 0x4).into(),
+                (2, &b"GET"[..], 0x4).into(),
+                true,
+            ),
             (
-                (b"a".to_vec(), b"b".to_vec(), 0x10 | 0x1),
-                vec![0, 129, 31, 1, 98],
-            ), // (a, b) => (huffman(a), b)
+                (2, b"GET".to_vec(), 0x4).into(),
+                (2, &b"GET"[..], 0x0).into(),
+                false,
+            ),
+            ((2, b"".to_vec(), 0x0).into(), 2.into(), false),
+            (
+                (b"a".to_vec(), b"b".to_vec(), 0x0).into(),
+                (&b"a"[..], &b"b"[..], 0x0).into(),
+                true,
+            ),
+            (
+                (b"a".to_vec(), b"b".to_vec(), 0x0).into(),
+                (&b"a"[..], &b"c"[..], 0x0).into(),
+                false,
+            ),
+            (
+                (b"".to_vec(), b"b".to_vec(), 0x0).into(),
+                (1, b"b".to_vec(), 0x0).into(),
+                false,
+            ),
         ];
-        for (field, res) in fields {
-            let mut dst = Vec::new();
-            encoder.encode(field, &mut dst).unwrap();
-            assert_eq!(dst, res);
+        for (a, b, equal) in examples {
+            assert_eq!(a == b, equal);
+            assert_eq!(b == a, equal);
         }
-        assert_eq!(encoder.table.len(), 62); // table altered only once
+        let field = FieldBuilder::literal(b"a", b"b").with_indexing();
+        assert_eq!(
+            EncoderInput::from(field),
+            (&b"a"[..], &b"b"[..], 0x4).into()
+        );
+        assert_eq!(field, FieldBuilder::literal(b"a", b"b").with_indexing());
     }
 
     /// Should encode a dynamic table size update signal.
@@ -604,7 +3229,7 @@ mod test {
         encoder.table.insert(b"a".to_vec(), b"a".to_vec()); // size: +34
         encoder.table.insert(b"b".to_vec(), b"b".to_vec()); // size: +34
         let mut dst = Vec::new();
-        encoder.update_max_dynamic_size(50, &mut dst).unwrap();
+        assert_eq!(encoder.update_max_dynamic_size(50, &mut dst), Ok(1));
         assert_eq!(dst[0] & 0b00100000, 32); // size update
         assert_eq!(dst, vec![63, 19]); // encoded size
         assert_eq!(encoder.table.dynamic_len(), 1); // 1 header evicted
@@ -0,0 +1,105 @@
+use std::io::{self, Write};
+
+/// Provides options describing the representation of an encoded header field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodedAs {
+    /// Represents an indexed header field ([6.1.]).
+    ///
+    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
+    Indexed,
+
+    /// Represents a literal header field with incremental indexing ([6.2.1.]).
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    LiteralIndexed,
+
+    /// Represents a literal header field without indexing ([6.2.2.]).
+    ///
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    LiteralNoIndex,
+
+    /// Represents a literal header field never indexed ([6.2.3.]).
+    ///
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    LiteralNeverIndex,
+}
+
+impl EncodedAs {
+    /// Returns the representation identified by the first `octet` of an
+    /// encoded header field.
+    pub(crate) fn from_octet(octet: u8) -> Self {
+        if octet & 128 == 128 {
+            Self::Indexed
+        } else if octet & 64 == 64 {
+            Self::LiteralIndexed
+        } else if octet & 16 == 16 {
+            Self::LiteralNeverIndex
+        } else {
+            Self::LiteralNoIndex
+        }
+    }
+}
+
+/// Provides the composition of a header block encoded by an encoder.
+///
+/// Every encoded header field is counted exactly once as indexed, literal or
+/// never indexed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSummary {
+    /// The number of encoded header fields.
+    pub field_count: usize,
+
+    /// The number of fully indexed header fields.
+    pub indexed: usize,
+
+    /// The number of literal header fields with or without indexing.
+    pub literal: usize,
+
+    /// The number of literal header fields never indexed.
+    pub never_indexed: usize,
+
+    /// The total number of written octets.
+    pub bytes: usize,
+
+    /// The number of Huffman encoded name and value strings.
+    pub huffman_strings: usize,
+}
+
+/// A writer which passes the data to the underlying writer, remembers the
+/// first written octet and counts the written octets.
+pub(crate) struct FirstOctet<W> {
+    /// The underlying writer.
+    inner: W,
+
+    /// The first written octet.
+    pub octet: Option<u8>,
+
+    /// The number of written octets.
+    pub len: usize,
+}
+
+impl<W: Write> FirstOctet<W> {
+    /// Returns a new writer wrapping the `inner` writer.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            octet: None,
+            len: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for FirstOctet<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.inner.write(buf)?;
+        if self.octet.is_none() && size > 0 {
+            self.octet = Some(buf[0]);
+        }
+        self.len += size;
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,30 @@
+/// Provides policies for choosing between the static and the dynamic table
+/// when both hold a header matching the encoded header.
+///
+/// The policy is only honored when the best literal representation is
+/// requested with the `0x10` flag. Every policy is deterministic, thus the
+/// same sequence of headers always results in the same output. The policies
+/// differ in how much the output of a header depends on the headers encoded
+/// before it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchPreference {
+    /// Indicates that a fully matching header is preferred over a header with
+    /// a matching name only, and that the lowest index is used among equal
+    /// matches. A lower index requires fewer octets, which results in the best
+    /// compression.
+    #[default]
+    LowestIndex,
+
+    /// Indicates that any match in the static table, even a name match, is
+    /// preferred over a full match in the dynamic table. A header with a name
+    /// found in the static table is thus always encoded the same way,
+    /// regardless of the headers encoded before it, at the cost of compression.
+    StaticFirst,
+
+    /// Indicates that the dynamic table is preferred over the static table
+    /// when both hold a match of the same kind, where the most recently
+    /// inserted header is preferred. A full match is still preferred over a
+    /// name match. This favors the headers indexed by the application over the
+    /// generic static headers.
+    DynamicFirst,
+}
@@ -0,0 +1,127 @@
+use super::primitives::{integer_len, string_len};
+use crate::table::{entry_size, Table};
+
+/// Returns the list of headers which should be inserted into the dynamic table
+/// in advance (prewarmed) to achieve the best amortized compression.
+///
+/// The `profile` holds the headers with the number of times each header is
+/// expected to be sent. The function calculates how many octets every use of a
+/// header would save if the header was represented with an index instead of a
+/// literal, multiplies that by its number of uses and then selects the headers
+/// with the highest savings per octet of the dynamic table until the entries
+/// fill the `max_size` of the table. Headers which are fully indexed by the
+/// static table already are never selected.
+///
+/// The literal representation is always calculated without indexing where the
+/// name and the value are encoded in the shorter of the plain and the Huffman
+/// string form. The indexed representation is approximated with a single octet
+/// (index `62`) for every header, although the headers at index `127` and
+/// above, thus beyond the first 65 dynamic entries, require two octets. Since
+/// the index a header receives depends on the order of insertion, the savings
+/// of large tables are slightly overestimated. Savings saturate at `u64::MAX`.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::recommend_prewarm;
+///
+/// let profile = vec![
+///     (b"x-request-source".to_vec(), b"mobile".to_vec(), 1000),
+///     (b"x-debug".to_vec(), b"1".to_vec(), 1),
+/// ];
+/// let headers = recommend_prewarm(&profile, 64);
+/// assert_eq!(headers, vec![
+///     (b"x-request-source".to_vec(), b"mobile".to_vec()),
+/// ]);
+/// ```
+pub fn recommend_prewarm(
+    profile: &[(Vec<u8>, Vec<u8>, u64)],
+    max_size: u32,
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let table = Table::default();
+
+    let mut candidates = Vec::new();
+    for (name, value, count) in profile {
+        let index = table.find(name, value);
+        let literal = match index {
+            Some((_, true)) => continue, // already in the static table
+            Some((index, false)) => integer_len(index as u32, 4) + string_size(value),
+            None => 1 + string_size(name) + string_size(value),
+        };
+        let savings = ((literal - integer_len(62, 7)) as u64).saturating_mul(*count);
+        if savings == 0 {
+            continue;
+        }
+        candidates.push((name, value, savings, entry_size(name, value)));
+    }
+
+    // highest savings per octet first (compared with cross multiplication)
+    candidates.sort_by(|a, b| {
+        let (a_savings, a_entry) = (u128::from(a.2), a.3 as u128);
+        let (b_savings, b_entry) = (u128::from(b.2), b.3 as u128);
+        (b_savings * a_entry).cmp(&(a_savings * b_entry))
+    });
+
+    let mut size = 0;
+    let mut headers = Vec::new();
+    for (name, value, _, entry) in candidates {
+        if size + entry <= max_size as usize {
+            size += entry;
+            headers.push((name.to_vec(), value.to_vec()));
+        }
+    }
+
+    headers
+}
+
+/// Returns the number of octets of the shortest string representation of the
+/// `data`.
+fn string_size(data: &[u8]) -> usize {
+    std::cmp::min(string_len(data, false), string_len(data, true))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should prefer frequent and compressible headers over rare ones while
+    /// keeping the selected entries within the provided table size.
+    #[test]
+    fn recommends_prewarm() {
+        let profile = vec![
+            (b"x-rare".to_vec(), b"value".to_vec(), 1),
+            (b"x-frequent".to_vec(), b"some long value".to_vec(), 500),
+            (b":method".to_vec(), b"GET".to_vec(), 1000), // static
+            (b"user-agent".to_vec(), b"httlib".to_vec(), 100), // indexed name
+        ];
+        let headers = recommend_prewarm(&profile, 105);
+        assert_eq!(
+            headers,
+            vec![
+                (b"x-frequent".to_vec(), b"some long value".to_vec()), // 57 octets
+                (b"user-agent".to_vec(), b"httlib".to_vec()),          // 48 octets
+            ]
+        );
+        let headers = recommend_prewarm(&profile, 200);
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers[2], (b"x-rare".to_vec(), b"value".to_vec()));
+        let headers = recommend_prewarm(&profile, 50);
+        assert_eq!(headers, vec![(b"user-agent".to_vec(), b"httlib".to_vec())]);
+        let headers = recommend_prewarm(&profile, 0);
+        assert!(headers.is_empty());
+    }
+
+    /// Should not overflow when the expected number of uses is huge.
+    #[test]
+    fn recommends_prewarm_with_huge_counts() {
+        let profile = vec![
+            (b"x-a".to_vec(), b"1".to_vec(), u64::MAX),
+            (b"x-long".to_vec(), b"some long value".to_vec(), u64::MAX),
+            (b"x-b".to_vec(), b"2".to_vec(), u64::MAX / 2),
+        ];
+        let headers = recommend_prewarm(&profile, 36);
+        assert_eq!(headers, vec![(b"x-a".to_vec(), b"1".to_vec())]);
+        let headers = recommend_prewarm(&profile, 1000);
+        assert_eq!(headers.len(), 3);
+    }
+}
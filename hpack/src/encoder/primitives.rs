@@ -1,4 +1,4 @@
-use std::io::Write;
+use std::{borrow::Cow, io::Write};
 
 use super::*;
 
@@ -13,10 +13,18 @@ use super::*;
 /// supports numbers up to the maximum value of the unsigned 32-bit integer.
 ///
 /// We can specify `flags` which represent the leading bits of the first octet.
+/// Only the bits above the prefix are used (e.g. `0x80` for the indexed header
+/// field representation with a 7-bit prefix) while the bits which overlap the
+/// prefix are ignored.
 ///
-/// The `prefix` is a parameter of the integer representation. Its  size must be
-/// between 1 and 8 bits. An integer starting at an octet boundary will have an
-/// 8-bit prefix.
+/// The `prefix_size` is a parameter of the integer representation. Its size
+/// must be between 1 and 8 bits, otherwise the `InvalidPrefix` error is
+/// returned. It defines the number of the lowest bits of the first octet which
+/// hold the value. When the value doesn't fit into the prefix, all the prefix
+/// bits are set and the rest of the value is written in the following octets,
+/// 7 bits at a time, with the most significant bit of each octet indicating
+/// that another octet follows. An integer starting at an octet boundary will
+/// have an 8-bit prefix.
 ///
 /// **Integer value encoded within the 5-bit prefix ([5.1.], figure 2):**
 ///
@@ -42,8 +50,18 @@ use super::*;
 /// +---+---------------------------+
 /// ```
 ///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::encode_integer;
+///
+/// let mut dst = Vec::new();
+/// encode_integer(1337, 0x80, 5, &mut dst).unwrap();
+/// assert_eq!(dst, vec![0x80 | 31, 154, 10]);
+/// ```
+///
 /// [5.1.]: https://tools.ietf.org/html/rfc7541#section-5.1
-pub(crate) fn encode_integer<W: Write>(
+pub fn encode_integer<W: Write>(
     value: u32,
     flags: u8,
     prefix_size: u8,
@@ -89,35 +107,177 @@ pub(crate) fn encode_integer<W: Write>(
 /// +-------------------------------+
 /// ```
 ///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::encode_string;
+///
+/// let mut dst = Vec::new();
+/// encode_string(b"foo", true, &mut dst).unwrap();
+/// assert_eq!(dst, vec![130, 148, 231]);
+/// ```
+///
+/// A string longer than `u32::MAX` octets results in the `StringTooLong` error.
+///
 /// [5.2.]: https://tools.ietf.org/html/rfc7541#section-5.2
-pub(crate) fn encode_string<W: Write>(
-    data: &[u8],
+pub fn encode_string<W: Write>(data: &[u8], huffman: bool, dst: W) -> Result<(), EncoderError> {
+    check_string_len(data)?;
+
+    let bytes = if huffman {
+        let mut dst = Vec::new();
+        httlib_huffman::encode(&data, &mut dst)?;
+        Cow::Owned(dst)
+    } else {
+        Cow::Borrowed(data)
+    };
+
+    encode_string_bytes(&bytes, huffman, dst)
+}
+
+/// Writes the string representation of already encoded `bytes` where `huffman`
+/// tells whether the `bytes` hold the Huffman encoding of the string literal.
+pub(crate) fn encode_string_bytes<W: Write>(
+    bytes: &[u8],
     huffman: bool,
     mut dst: W,
 ) -> Result<(), EncoderError> {
-    let (flags, bytes) = if huffman {
-        let mut dst = Vec::new();
-        httlib_huffman::encode(&data, &mut dst)?;
-        (0x80, dst) // set MSB to 1 indicating Huffman encoded literal
+    let flags = if huffman {
+        0x80 // set MSB to 1 indicating Huffman encoded literal
     } else {
-        (0, data.to_vec()) // set MSB to 0 indicating plain text
+        0 // set MSB to 0 indicating plain text
     };
 
-    let len = bytes.len();
-    if len > u32::MAX as usize {
-        return Err(EncoderError::IntegerOverflow);
-    }
+    check_string_len(bytes)?;
 
-    encode_integer(len as u32, flags, 7, &mut dst)?; // first byte
-    dst.write_all(bytes.as_slice())?; // the rest of bytes
+    encode_integer(bytes.len() as u32, flags, 7, &mut dst)?; // first byte
+    dst.write_all(bytes)?; // the rest of bytes
 
     return Ok(());
 }
 
+/// Writes a representation of exactly `len` octets, assembled by the `encode`
+/// closure, into `dst` with a single write, thus nothing is written when the
+/// closure fails. Short representations are assembled on the stack without
+/// allocating.
+pub(crate) fn write_whole<W, F>(len: usize, mut dst: W, encode: F) -> Result<(), EncoderError>
+where
+    W: Write,
+    F: FnOnce(&mut &mut [u8]) -> Result<(), EncoderError>,
+{
+    let mut stack = [0; 128];
+    let mut heap = Vec::new();
+    let buf = if len <= stack.len() {
+        &mut stack[..len]
+    } else {
+        heap.resize(len, 0);
+        &mut heap[..]
+    };
+
+    let mut rest = &mut buf[..];
+    encode(&mut rest)?;
+    debug_assert!(rest.is_empty()); // the length must be exact
+    dst.write_all(buf)?;
+    Ok(())
+}
+
+/// Verifies that the length of the `data` fits into the `u32` length prefix of
+/// a string literal, otherwise the `StringTooLong` error is returned.
+pub(crate) fn check_string_len(data: &[u8]) -> Result<(), EncoderError> {
+    check_len(data.len())
+}
+
+/// Verifies that the string length `len` fits into `u32`.
+fn check_len(len: usize) -> Result<(), EncoderError> {
+    if len as u64 > u64::from(u32::MAX) {
+        Err(EncoderError::StringTooLong)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the number of octets of the integer representation of the `value`
+/// with the provided prefix size ([5.1.]).
+///
+/// The result matches the length of the output of the `encode_integer` function
+/// for a valid `prefix_size` without writing anything.
+///
+/// [5.1.]: https://tools.ietf.org/html/rfc7541#section-5.1
+pub(crate) fn integer_len(value: u32, prefix_size: u8) -> usize {
+    let mask = (1u32 << prefix_size) - 1; // max possible value of the first byte
+    if value < mask {
+        return 1;
+    }
+
+    let mut value = value - mask;
+    let mut len = 2; // first and last byte
+    while value >= 128 {
+        len += 1; // byte with continuation flag
+        value >>= 7;
+    }
+    len
+}
+
+/// Returns the number of octets of the Huffman encoded `data` without encoding
+/// it.
+pub(crate) fn huffman_len(data: &[u8]) -> usize {
+    httlib_huffman::encoded_len(data)
+}
+
+/// Returns the number of octets of the string representation of the `data`
+/// ([5.2.]).
+///
+/// The result matches the length of the output of the `encode_string` function
+/// without writing anything.
+///
+/// [5.2.]: https://tools.ietf.org/html/rfc7541#section-5.2
+pub(crate) fn string_len(data: &[u8], huffman: bool) -> usize {
+    let len = if huffman {
+        huffman_len(data)
+    } else {
+        data.len()
+    };
+
+    integer_len(len as u32, 7) + len
+}
+
+/// Returns `true` if the `name` is a valid HTTP/2 header name ([8.1.2.]).
+///
+/// A valid name is a non-empty sequence of lowercase token characters as
+/// defined by [RFC 7230]. A pseudo-header name starts with the `:` character.
+///
+/// [8.1.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2
+/// [RFC 7230]: https://tools.ietf.org/html/rfc7230#section-3.2.6
+pub(crate) fn is_valid_name(name: &[u8]) -> bool {
+    let name = match name.split_first() {
+        Some((b':', rest)) => rest,
+        _ => name,
+    };
+
+    !name.is_empty()
+        && name.iter().all(|b| {
+            matches!(b,
+                b'a'..=b'z' | b'0'..=b'9' | b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*'
+                | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+            )
+        })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// Should reject string lengths which do not fit into `u32`.
+    #[test]
+    fn checks_string_len() {
+        assert_eq!(check_len(0), Ok(()));
+        assert_eq!(check_len(u32::MAX as usize), Ok(()));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            check_len(u32::MAX as usize + 1),
+            Err(EncoderError::StringTooLong)
+        );
+    }
+
     /// Should encode an integer into the integer representation defined by
     /// HPACK ([5.1.]).
     ///
@@ -167,4 +327,56 @@ mod test {
             assert_eq!(dst, bytes);
         }
     }
+
+    /// Should return the exact length of the integer and the string
+    /// representations without encoding them.
+    #[test]
+    fn calculates_len() {
+        for value in vec![0, 1, 30, 31, 32, 157, 158, 1337, 16414, u32::MAX] {
+            for prefix in 1..=8 {
+                let mut dst = Vec::new();
+                encode_integer(value, 0, prefix, &mut dst).unwrap();
+                assert_eq!(integer_len(value, prefix), dst.len());
+            }
+        }
+        let mut long = Vec::new();
+        for i in 0..300 {
+            long.push(i as u8);
+        }
+        for data in [b"".to_vec(), b"foo".to_vec(), b"\x00\xff".to_vec(), long] {
+            for huffman in [false, true] {
+                let mut dst = Vec::new();
+                encode_string(&data, huffman, &mut dst).unwrap();
+                assert_eq!(string_len(&data, huffman), dst.len());
+            }
+        }
+    }
+
+    /// Should accept only lowercase HTTP/2 header names with allowed
+    /// characters ([8.1.2.]).
+    ///
+    /// [8.1.2.]: https://tools.ietf.org/html/rfc7540#section-8.1.2
+    #[test]
+    fn validates_name() {
+        for name in [
+            &b"content-type"[..],
+            b":method",
+            b"x-foo_bar.1~",
+            b"!#$%&'*+^`|",
+        ] {
+            assert!(is_valid_name(name));
+        }
+        for name in [
+            &b""[..],
+            b":",
+            b"Content-Type",
+            b"foo bar",
+            b"foo:",
+            b"foo\x00",
+            b"foo\x7f",
+            b"\xc3\xa9",
+        ] {
+            assert!(!is_valid_name(name));
+        }
+    }
 }
@@ -0,0 +1,111 @@
+/// Provides the representations of a header field or an instruction within a
+/// header block as identified by their first octet ([6.]).
+///
+/// Each representation starts with a pattern of bits followed by an integer
+/// with a prefix of a different size, which is returned by the `prefix_bits`
+/// method.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_hpack::Representation;
+///
+/// let repr = Representation::from_prefix(0x80 | 2);
+/// assert_eq!(repr, Representation::Indexed);
+/// assert_eq!(repr.prefix_bits(), 7);
+/// ```
+///
+/// [6.]: https://tools.ietf.org/html/rfc7541#section-6
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Representation {
+    /// Represents an indexed header field (`1xxxxxxx`, [6.1.]).
+    ///
+    /// [6.1.]: https://tools.ietf.org/html/rfc7541#section-6.1
+    Indexed,
+
+    /// Represents a literal header field with incremental indexing
+    /// (`01xxxxxx`, [6.2.1.]).
+    ///
+    /// [6.2.1.]: https://tools.ietf.org/html/rfc7541#section-6.2.1
+    LiteralWithIndexing,
+
+    /// Represents a dynamic table size update (`001xxxxx`, [6.3.]).
+    ///
+    /// [6.3.]: https://tools.ietf.org/html/rfc7541#section-6.3
+    SizeUpdate,
+
+    /// Represents a literal header field never indexed (`0001xxxx`,
+    /// [6.2.3.]).
+    ///
+    /// [6.2.3.]: https://tools.ietf.org/html/rfc7541#section-6.2.3
+    LiteralNeverIndexed,
+
+    /// Represents a literal header field without indexing (`0000xxxx`,
+    /// [6.2.2.]).
+    ///
+    /// [6.2.2.]: https://tools.ietf.org/html/rfc7541#section-6.2.2
+    LiteralWithoutIndexing,
+}
+
+impl Representation {
+    /// Returns the representation identified by the first `byte` of an encoded
+    /// header field or instruction. Every byte identifies a representation.
+    pub fn from_prefix(byte: u8) -> Self {
+        if byte & 128 == 128 {
+            Self::Indexed
+        } else if byte & 64 == 64 {
+            Self::LiteralWithIndexing
+        } else if byte & 32 == 32 {
+            Self::SizeUpdate
+        } else if byte & 16 == 16 {
+            Self::LiteralNeverIndexed
+        } else {
+            Self::LiteralWithoutIndexing
+        }
+    }
+
+    /// Returns the size of the integer prefix which follows the pattern of
+    /// the representation in the first octet. The integer holds the index, or
+    /// the new maximum size in case of the size update.
+    pub fn prefix_bits(&self) -> u8 {
+        match self {
+            Self::Indexed => 7,
+            Self::LiteralWithIndexing => 6,
+            Self::SizeUpdate => 5,
+            Self::LiteralNeverIndexed | Self::LiteralWithoutIndexing => 4,
+        }
+    }
+}
+
+impl From<u8> for Representation {
+    fn from(byte: u8) -> Self {
+        Self::from_prefix(byte)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should classify every first octet by its pattern of bits.
+    #[test]
+    fn classifies_prefixes() {
+        let examples = vec![
+            (0b1000_0000, Representation::Indexed, 7),
+            (0b1111_1111, Representation::Indexed, 7),
+            (0b0100_0000, Representation::LiteralWithIndexing, 6),
+            (0b0111_1111, Representation::LiteralWithIndexing, 6),
+            (0b0010_0000, Representation::SizeUpdate, 5),
+            (0b0011_1111, Representation::SizeUpdate, 5),
+            (0b0001_0000, Representation::LiteralNeverIndexed, 4),
+            (0b0001_1111, Representation::LiteralNeverIndexed, 4),
+            (0b0000_0000, Representation::LiteralWithoutIndexing, 4),
+            (0b0000_1111, Representation::LiteralWithoutIndexing, 4),
+        ];
+        for (byte, repr, bits) in examples {
+            assert_eq!(Representation::from_prefix(byte), repr);
+            assert_eq!(Representation::from(byte), repr);
+            assert_eq!(repr.prefix_bits(), bits);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Implements `Serialize` and `Deserialize` for `EncoderInput` when the `serde`
+//! feature is enabled.
+//!
+//! A header field is represented as an object with the `type` of the
+//! representation, and the `name`, the `value` and the `flags` where these
+//! apply. Names and values are strings unless they are not valid UTF-8, in
+//! which case they are arrays of bytes. Flags are represented as an object of
+//! booleans where missing flags are unset:
+//!
+//! ```json
+//! {
+//!   "type": "literal",
+//!   "name": "content-type",
+//!   "value": "text/html",
+//!   "flags": { "huffman_value": true, "index": true }
+//! }
+//! ```
+//!
+//! Borrowed variants serialize exactly as the owned ones, while deserializing
+//! always produces the owned variants. Flag bits unknown to the encoder are
+//! not preserved.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Encoder, EncoderInput};
+
+/// Represents the encoder input format options.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Repr {
+    Indexed {
+        index: u32,
+    },
+    IndexedName {
+        index: u32,
+        value: Bytes,
+        #[serde(default)]
+        flags: Flags,
+    },
+    Literal {
+        name: Bytes,
+        value: Bytes,
+        #[serde(default)]
+        flags: Flags,
+    },
+}
+
+/// Represents a header name or value as text when possible.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Bytes {
+    Text(String),
+    Raw(Vec<u8>),
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(data: &[u8]) -> Self {
+        match std::str::from_utf8(data) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Raw(data.to_vec()),
+        }
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(data: Bytes) -> Self {
+        match data {
+            Bytes::Text(text) => text.into_bytes(),
+            Bytes::Raw(raw) => raw,
+        }
+    }
+}
+
+/// Represents the encoder flags in a readable form.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Flags {
+    #[serde(skip_serializing_if = "is_false")]
+    huffman_name: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    huffman_value: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    index: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    never_index: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    best_format: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    huffman_if_shorter: bool,
+}
+
+impl From<u8> for Flags {
+    fn from(flags: u8) -> Self {
+        Self {
+            huffman_name: flags & Encoder::HUFFMAN_NAME != 0,
+            huffman_value: flags & Encoder::HUFFMAN_VALUE != 0,
+            index: flags & Encoder::WITH_INDEXING != 0,
+            never_index: flags & Encoder::NEVER_INDEXED != 0,
+            best_format: flags & Encoder::BEST_FORMAT != 0,
+            huffman_if_shorter: flags & Encoder::HUFFMAN_IF_SHORTER != 0,
+        }
+    }
+}
+
+impl From<Flags> for u8 {
+    fn from(flags: Flags) -> Self {
+        [
+            (flags.huffman_name, Encoder::HUFFMAN_NAME),
+            (flags.huffman_value, Encoder::HUFFMAN_VALUE),
+            (flags.index, Encoder::WITH_INDEXING),
+            (flags.never_index, Encoder::NEVER_INDEXED),
+            (flags.best_format, Encoder::BEST_FORMAT),
+            (flags.huffman_if_shorter, Encoder::HUFFMAN_IF_SHORTER),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag)
+    }
+}
+
+/// Returns `true` if the flag is not set.
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
+impl<'a> Serialize for EncoderInput<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match self {
+            Self::Indexed(index) => Repr::Indexed { index: *index },
+            Self::IndexedNameOwned(index, value, flags) => Repr::IndexedName {
+                index: *index,
+                value: value.as_slice().into(),
+                flags: (*flags).into(),
+            },
+            Self::IndexedNameBorrowed(index, value, flags) => Repr::IndexedName {
+                index: *index,
+                value: (*value).into(),
+                flags: (*flags).into(),
+            },
+            Self::LiteralOwned(name, value, flags) => Repr::Literal {
+                name: name.as_slice().into(),
+                value: value.as_slice().into(),
+                flags: (*flags).into(),
+            },
+            Self::LiteralBorrowed(name, value, flags) => Repr::Literal {
+                name: (*name).into(),
+                value: (*value).into(),
+                flags: (*flags).into(),
+            },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, 'a> Deserialize<'de> for EncoderInput<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Indexed { index } => Self::Indexed(index),
+            Repr::IndexedName {
+                index,
+                value,
+                flags,
+            } => Self::IndexedNameOwned(index, value.into(), flags.into()),
+            Repr::Literal { name, value, flags } => {
+                Self::LiteralOwned(name.into(), value.into(), flags.into())
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should serialize header fields with readable flags and deserialize them
+    /// into the owned variants.
+    #[test]
+    fn serializes_encoder_input() {
+        let field = EncoderInput::LiteralBorrowed(b"content-type", b"text/html", 0x2 | 0x4);
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"literal","name":"content-type","value":"text/html","flags":{"huffman_value":true,"index":true}}"#
+        );
+        let field: EncoderInput = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            format!("{:?}", field),
+            format!(
+                "{:?}",
+                EncoderInput::LiteralOwned(b"content-type".to_vec(), b"text/html".to_vec(), 0x6)
+            )
+        );
+
+        let field = EncoderInput::IndexedNameOwned(4, vec![0xff, 0x2f], 0x8 | 0x10);
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"indexed_name","index":4,"value":[255,47],"flags":{"never_index":true,"best_format":true}}"#
+        );
+        let field: EncoderInput = serde_json::from_str(&json).unwrap();
+        assert!(matches!(field, EncoderInput::IndexedNameOwned(4, v, 0x18) if v == [0xff, 0x2f]));
+
+        let field: EncoderInput = serde_json::from_str(r#"{"type":"indexed","index":2}"#).unwrap();
+        assert!(matches!(field, EncoderInput::Indexed(2)));
+        let field: EncoderInput =
+            serde_json::from_str(r#"{"type":"literal","name":"a","value":"b"}"#).unwrap();
+        assert!(matches!(field, EncoderInput::LiteralOwned(_, _, 0x0)));
+        assert!(serde_json::from_str::<EncoderInput>(
+            r#"{"type":"literal","name":"a","value":"b","flags":{"huffman":true}}"#
+        )
+        .is_err());
+    }
+}
@@ -71,6 +71,12 @@
 //! }
 //! ```
 //!
+//! ## Features
+//!
+//! The `serde` feature implements `Serialize` and `Deserialize` for the
+//! `EncoderInput` which allows for describing header fields in fixtures (e.g.
+//! JSON files).
+//!
 //! ## Articles
 //!
 //! * [HPACK: The secret ingredient of HTTP/2](https://dev.to/xpepermint/hpack-the-secret-ingredient-of-http-2-4np6)
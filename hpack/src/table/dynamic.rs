@@ -1,20 +1,36 @@
 use std::collections::VecDeque;
 
+use super::entry_size;
+
 /// Represents a [dynamic table] with header fields maintained in first-in,
 /// first-out order.
 ///
 /// [dynamic table]: https://tools.ietf.org/html/rfc7541#section-2.3.2
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DynamicTable {
     /// A sequential list of dynamic headers where the newest entry in at the
     /// lowest index. It can contain duplicate entries.
     inner: VecDeque<(Vec<u8>, Vec<u8>)>,
 
+    /// A list of flags, parallel to the list of headers, indicating whether the
+    /// header has been referenced since its insertion.
+    referenced: VecDeque<bool>,
+
+    /// The total number of headers evicted from the table.
+    evicted: usize,
+
+    /// The total number of headers evicted from the table without ever being
+    /// referenced.
+    wasted: usize,
+
     /// The sum of the size of its entries in the table. The size of an entry is
     /// the sum of its name and value in octets without any Huffman encoding
     /// applied, and 32.
     size: usize,
 
+    /// The highest size the table has reached after an insertion.
+    peak: usize,
+
     /// The maximum size that the encoder is permitted to use for the dynamic
     /// table. In HTTP/2, this value is advertised through the SETTINGS frame by
     /// the SETTINGS_HEADER_TABLE_SIZE field. The encoder can use less than or
@@ -28,7 +44,11 @@ impl DynamicTable {
     pub fn with_size(max_size: u32) -> Self {
         Self {
             inner: VecDeque::new(),
+            referenced: VecDeque::new(),
+            evicted: 0,
+            wasted: 0,
             size: 0,
+            peak: 0,
             max_size,
         }
     }
@@ -43,6 +63,11 @@ impl DynamicTable {
         self.size as u32
     }
 
+    /// Returns the highest size (in octets) the table has reached.
+    pub fn peak(&self) -> u32 {
+        self.peak as u32
+    }
+
     /// Returns the maximum allowed table size.
     pub fn max_size(&self) -> u32 {
         self.max_size
@@ -59,6 +84,60 @@ impl DynamicTable {
         self.consolidate(); // evict entries if necessary
     }
 
+    /// Reserves capacity for at least `additional` more entries so that
+    /// inserting them does not reallocate the list of entries.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+        self.referenced.reserve(additional);
+    }
+
+    /// Evicts all entries from the table while keeping its maximum allowed
+    /// size. The entries are counted as evicted.
+    pub fn clear(&mut self) {
+        self.evicted += self.inner.len();
+        self.wasted += self.referenced.iter().filter(|r| !**r).count();
+        self.inner.clear();
+        self.referenced.clear();
+        self.size = 0;
+    }
+
+    /// Removes all the entries matching the header `name` and `value` and
+    /// returns `true` if any has been removed. Removed entries are not counted
+    /// as evicted.
+    pub fn remove(&mut self, name: &[u8], value: &[u8]) -> bool {
+        let len = self.inner.len();
+        let mut index = 0;
+        while index < self.inner.len() {
+            let (n, v) = &self.inner[index];
+            if n == name && v == value {
+                self.size -= entry_size(name, value);
+                self.inner.remove(index);
+                self.referenced.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+        self.inner.len() != len
+    }
+
+    /// Returns the total number of headers evicted from the table.
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    /// Returns the total number of headers evicted from the table which have
+    /// never been referenced.
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+
+    /// Marks the header at `index` as referenced.
+    pub fn mark_referenced(&mut self, index: u32) {
+        if let Some(referenced) = self.referenced.get_mut(index as usize) {
+            *referenced = true;
+        }
+    }
+
     /// Finds a header by its index.
     pub fn get(&self, index: u32) -> Option<(&[u8], &[u8])> {
         match self.inner.get(index as usize) {
@@ -83,10 +162,12 @@ impl DynamicTable {
     ///
     /// [4.1.]: https://tools.ietf.org/html/rfc7541#section-4.1
     pub fn insert(&mut self, name: Vec<u8>, value: Vec<u8>) {
-        self.size += name.len() + value.len() + 32;
+        self.size += entry_size(&name, &value);
         self.inner.push_front((name, value));
+        self.referenced.push_front(false);
 
         self.consolidate(); // evict entries if necessary
+        self.peak = std::cmp::max(self.peak, self.size);
     }
 
     /// Consolidates the table entries so that the table size is below the
@@ -95,7 +176,11 @@ impl DynamicTable {
     fn consolidate(&mut self) {
         while self.size > self.max_size as usize {
             if let Some(header) = self.inner.pop_back() {
-                self.size -= header.0.len() + header.1.len() + 32;
+                self.size -= entry_size(&header.0, &header.1);
+                self.evicted += 1;
+                if let Some(false) = self.referenced.pop_back() {
+                    self.wasted += 1;
+                }
             }
         }
     }
@@ -167,4 +252,39 @@ mod test {
         let h2 = tbl.get(0).unwrap();
         assert_eq!(vec![h2.0, h2.1], vec![b"a2", b"b2"]);
     }
+
+    /// Evicted headers which have never been referenced should be counted as
+    /// wasted.
+    #[test]
+    fn counts_wasted_headers() {
+        let mut tbl = DynamicTable::with_size(72); // 72 can hold up to 2 headers
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        tbl.insert(b"a1".to_vec(), b"b1".to_vec());
+        tbl.mark_referenced(1); // header a0
+        tbl.insert(b"a2".to_vec(), b"b2".to_vec()); // evicts a0
+        assert_eq!(tbl.evicted(), 1);
+        assert_eq!(tbl.wasted(), 0);
+        tbl.insert(b"a3".to_vec(), b"b3".to_vec()); // evicts a1
+        assert_eq!(tbl.evicted(), 2);
+        assert_eq!(tbl.wasted(), 1);
+        tbl.mark_referenced(0); // header a3
+        tbl.clear(); // evicts a3 and a2
+        assert_eq!(tbl.evicted(), 4);
+        assert_eq!(tbl.wasted(), 2);
+    }
+
+    /// Should remove all the matching headers and reduce the table size.
+    #[test]
+    fn removes_headers() {
+        let mut tbl = DynamicTable::with_size(1000);
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        tbl.insert(b"a1".to_vec(), b"b1".to_vec());
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        assert!(tbl.remove(b"a0", b"b0"));
+        assert_eq!(tbl.len(), 1);
+        assert_eq!(tbl.size(), 36);
+        assert_eq!(tbl.get(0), Some((&b"a1"[..], &b"b1"[..])));
+        assert!(!tbl.remove(b"a0", b"b0"));
+        assert_eq!(tbl.evicted(), 0);
+    }
 }
@@ -74,9 +74,19 @@ use dynamic::DynamicTable;
 pub use iter::TableIter;
 use r#static::{StaticTable, STATIC_TABLE};
 
+/// Returns the size of a header entry in the indexing table.
+///
+/// The size of an entry is the sum of its name and value in octets without any
+/// Huffman encoding applied, and 32 ([4.1.]).
+///
+/// [4.1.]: https://tools.ietf.org/html/rfc7541#section-4.1
+pub fn entry_size(name: &[u8], value: &[u8]) -> usize {
+    name.len() + value.len() + 32
+}
+
 /// A table representing a single index address space for headers where the
 /// static and the dynamic table are combined.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Table<'a> {
     /// THe static table with predefined headers.
     static_table: StaticTable<'a>,
@@ -90,7 +100,18 @@ impl<'a> Table<'a> {
     /// size of the dynamic table.
     pub fn with_dynamic_size(max_dynamic_size: u32) -> Self {
         Self {
-            static_table: STATIC_TABLE,
+            static_table: StaticTable::Borrowed(STATIC_TABLE),
+            dynamic_table: DynamicTable::with_size(max_dynamic_size),
+        }
+    }
+
+    /// Returns a new header table instance where the static table holds the
+    /// provided `entries` instead of the header fields defined by the HPACK
+    /// specification. The dynamic table thus starts at the index following the
+    /// last custom entry.
+    pub fn with_static_table(entries: Vec<(Vec<u8>, Vec<u8>)>, max_dynamic_size: u32) -> Self {
+        Self {
+            static_table: StaticTable::Owned(entries),
             dynamic_table: DynamicTable::with_size(max_dynamic_size),
         }
     }
@@ -112,6 +133,11 @@ impl<'a> Table<'a> {
         self.dynamic_table.size()
     }
 
+    /// Returns the highest size (in octets) the dynamic table has reached.
+    pub fn peak_dynamic_size(&self) -> u32 {
+        self.dynamic_table.peak()
+    }
+
     /// Returns the maximum allowed size of the dynamic table.
     pub fn max_dynamic_size(&self) -> u32 {
         self.dynamic_table.max_size()
@@ -122,6 +148,44 @@ impl<'a> Table<'a> {
         self.dynamic_table.update_max_size(size);
     }
 
+    /// Reserves capacity for at least `additional` more headers in the dynamic
+    /// table. This is only a performance hint and has no effect on eviction.
+    pub fn reserve(&mut self, additional: usize) {
+        self.dynamic_table.reserve(additional);
+    }
+
+    /// Returns the total number of headers evicted from the dynamic table.
+    pub fn evicted_len(&self) -> usize {
+        self.dynamic_table.evicted()
+    }
+
+    /// Returns the total number of headers evicted from the dynamic table
+    /// which have never been referenced after their insertion.
+    pub fn wasted_len(&self) -> usize {
+        self.dynamic_table.wasted()
+    }
+
+    /// Marks the header at `index` as referenced. Only headers of the dynamic
+    /// table are tracked, other indexes are ignored.
+    pub fn mark_referenced(&mut self, index: u32) {
+        let static_len = self.static_table.len() as u32;
+        if index > static_len {
+            self.dynamic_table.mark_referenced(index - static_len - 1);
+        }
+    }
+
+    /// Evicts all the headers of the dynamic table. The maximum allowed size of
+    /// the dynamic table stays unchanged.
+    pub fn clear_dynamic(&mut self) {
+        self.dynamic_table.clear();
+    }
+
+    /// Removes all the headers of the dynamic table matching the header `name`
+    /// and `value`, and returns `true` if any has been removed.
+    pub fn remove_dynamic(&mut self, name: &[u8], value: &[u8]) -> bool {
+        self.dynamic_table.remove(name, value)
+    }
+
     /// Returns an iterator through all the headers.
     ///
     /// It includes entries stored in the static and the dynamic table. Since
@@ -136,6 +200,15 @@ impl<'a> Table<'a> {
         }
     }
 
+    /// Returns an iterator through all the headers with their indexes.
+    ///
+    /// Similar to `iter`, entries of the static table are followed by entries
+    /// of the dynamic table, but each item also holds the HPACK index of the
+    /// header, starting at `1`. The table is not modified while iterating.
+    pub fn entries(&self) -> impl Iterator<Item = (u32, &[u8], &[u8])> {
+        (1..=self.len() as u32).filter_map(move |i| self.get(i).map(|(n, v)| (i, n, v)))
+    }
+
     /// Finds a header by its index.
     ///
     /// According to the HPACK specification, the index `0` must be treated as
@@ -150,7 +223,7 @@ impl<'a> Table<'a> {
 
         let static_len = self.static_table.len() as u32;
         if index < static_len {
-            Some(self.static_table[index as usize])
+            self.static_table.get(index as usize)
         } else {
             self.dynamic_table.get(index - static_len)
         }
@@ -164,9 +237,39 @@ impl<'a> Table<'a> {
     /// header in the table and a boolean indicating whether the value of the
     /// header also matched.
     pub fn find(&self, name: &[u8], value: &[u8]) -> Option<(usize, bool)> {
+        self.find_range(name, value, 0, self.len())
+    }
+
+    /// Searches only the static table for the provided header exactly as the
+    /// `find` method does.
+    pub fn find_static(&self, name: &[u8], value: &[u8]) -> Option<(usize, bool)> {
+        self.find_range(name, value, 0, self.static_table.len())
+    }
+
+    /// Searches only the dynamic table for the provided header exactly as the
+    /// `find` method does. The returned index is the index of the header in
+    /// the whole table.
+    pub fn find_dynamic(&self, name: &[u8], value: &[u8]) -> Option<(usize, bool)> {
+        self.find_range(
+            name,
+            value,
+            self.static_table.len(),
+            self.dynamic_table.len(),
+        )
+    }
+
+    /// Searches `len` headers, starting at the `skip` position, for the
+    /// provided header.
+    fn find_range(
+        &self,
+        name: &[u8],
+        value: &[u8],
+        skip: usize,
+        len: usize,
+    ) -> Option<(usize, bool)> {
         let mut name_match = None;
 
-        for (i, h) in self.iter().enumerate() {
+        for (i, h) in self.iter().enumerate().skip(skip).take(len) {
             if name == h.0 {
                 if value == h.1 {
                     return Some((i + 1, true)); // name and value matched
@@ -182,16 +285,37 @@ impl<'a> Table<'a> {
         }
     }
 
+    /// Searches the static and the dynamic tables for a header with the
+    /// provided `name` regardless of its value.
+    ///
+    /// The lowest matching index is returned, thus a header of the static table
+    /// is preferred over a header of the dynamic table, and the most recently
+    /// inserted header is preferred within the dynamic table.
+    pub fn find_name(&self, name: &[u8]) -> Option<u32> {
+        self.iter().position(|h| name == h.0).map(|i| i as u32 + 1)
+    }
+
     /// Inserts a new header at the beginning of the dynamic table.
     pub fn insert(&mut self, name: Vec<u8>, value: Vec<u8>) {
         self.dynamic_table.insert(name, value);
     }
+
+    /// Inserts the provided headers into the dynamic table in order.
+    ///
+    /// Each header is inserted as by the `insert` method, thus headers are
+    /// evicted when the table size is exceeded and the last header of
+    /// `entries` ends up with the lowest dynamic index.
+    pub fn seed(&mut self, entries: Vec<(Vec<u8>, Vec<u8>)>) {
+        for (name, value) in entries {
+            self.insert(name, value);
+        }
+    }
 }
 
 impl<'a> Default for Table<'a> {
     fn default() -> Self {
         Self {
-            static_table: STATIC_TABLE,
+            static_table: StaticTable::Borrowed(STATIC_TABLE),
             dynamic_table: DynamicTable::default(),
         }
     }
@@ -253,6 +377,21 @@ mod test {
         assert_eq!(vec![h62.0, h62.1], vec![b"a0", b"b0"]);
     }
 
+    /// The returned iterator should walk through all entries in the static and
+    /// the dynamic table with their indexes.
+    #[test]
+    fn iters_through_entries() {
+        let mut tbl = Table::default();
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        tbl.insert(b"a1".to_vec(), b"b1".to_vec());
+        let entries: Vec<_> = tbl.entries().collect();
+        assert_eq!(entries.len(), 63);
+        assert_eq!(entries[0], (1, &b":authority"[..], &b""[..]));
+        assert_eq!(entries[61], (62, &b"a1"[..], &b"b1"[..]));
+        assert_eq!(entries[62], (63, &b"a0"[..], &b"b0"[..]));
+        assert_eq!(tbl.dynamic_size(), 72); // table not altered
+    }
+
     /// The table should search the static and the dynamic tables for a possible
     /// header match. It should try to match both the header name and value to
     /// one of the headers in the table. If no such header exists, then it
@@ -277,4 +416,79 @@ mod test {
         let m = tbl.find(b"x", b"x"); // not indexed
         assert_eq!(m, None); // not found
     }
+
+    /// The table should use the custom static table where the dynamic table
+    /// starts right after the last custom entry.
+    #[test]
+    fn uses_custom_static_table() {
+        let entries = vec![
+            (b"x-a".to_vec(), b"1".to_vec()),
+            (b"x-b".to_vec(), b"2".to_vec()),
+        ];
+        let mut tbl = Table::with_static_table(entries, 4096);
+        tbl.insert(b"x-a".to_vec(), b"3".to_vec());
+        assert_eq!(tbl.len(), 3);
+        assert_eq!(tbl.get(2), Some((&b"x-b"[..], &b"2"[..])));
+        assert_eq!(tbl.get(3), Some((&b"x-a"[..], &b"3"[..])));
+        assert_eq!(tbl.get(4), None);
+        assert_eq!(tbl.find(b"x-a", b"3"), Some((3, true)));
+        assert_eq!(tbl.find(b":method", b"GET"), None);
+    }
+
+    /// Should find the lowest index of a header name where the static table
+    /// is preferred over the dynamic table.
+    #[test]
+    fn finds_header_names() {
+        let mut tbl = Table::default();
+        tbl.insert(b":method".to_vec(), b"PUT".to_vec());
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        tbl.insert(b"a0".to_vec(), b"b1".to_vec());
+        assert_eq!(tbl.find_name(b":method"), Some(2));
+        assert_eq!(tbl.find_name(b"a0"), Some(62));
+        assert_eq!(tbl.find_name(b"a1"), None);
+        assert_eq!(tbl.find(b":method", b"PUT"), Some((64, true)));
+    }
+
+    /// Should clone the table into an independent snapshot which does not
+    /// share the dynamic entries with the original.
+    #[test]
+    fn clones_independently() {
+        let mut tbl = Table::default();
+        tbl.insert(b"a0".to_vec(), b"b0".to_vec());
+        let mut snapshot = tbl.clone();
+        snapshot.insert(b"a1".to_vec(), b"b1".to_vec());
+        assert_eq!(tbl.len(), 62);
+        assert_eq!(snapshot.len(), 63);
+        assert_eq!(tbl.find(b"a1", b"b1"), None);
+        assert_eq!(snapshot.find(b"a0", b"b0"), Some((63, true)));
+    }
+
+    /// Should insert headers in order so that the last header gets the lowest
+    /// dynamic index and old headers are evicted.
+    #[test]
+    fn seeds_dynamic_table() {
+        let mut tbl = Table::with_dynamic_size(70);
+        tbl.seed(vec![
+            (b"a0".to_vec(), b"b0".to_vec()),
+            (b"a1".to_vec(), b"b1".to_vec()),
+            (b"a2".to_vec(), b"b2".to_vec()),
+        ]);
+        assert_eq!(tbl.len(), 62);
+        assert_eq!(tbl.get(62), Some((&b"a2"[..], &b"b2"[..])));
+        assert_eq!(tbl.find(b"a0", b"b0"), None);
+    }
+
+    /// Should search the static and the dynamic tables separately while
+    /// returning indexes of the whole table.
+    #[test]
+    fn finds_headers_per_table() {
+        let mut tbl = Table::default();
+        tbl.insert(b":method".to_vec(), b"PUT".to_vec());
+        tbl.insert(b":method".to_vec(), b"GET".to_vec());
+        assert_eq!(tbl.find_static(b":method", b"PUT"), Some((2, false)));
+        assert_eq!(tbl.find_dynamic(b":method", b"PUT"), Some((63, true)));
+        assert_eq!(tbl.find_dynamic(b":method", b"POST"), Some((62, false)));
+        assert_eq!(tbl.find_dynamic(b":path", b"/"), None);
+        assert_eq!(tbl.find(b":method", b"GET"), Some((2, true)));
+    }
 }
@@ -1,5 +1,31 @@
-/// Represents a static table.
-pub type StaticTable<'a> = &'a [(&'a [u8], &'a [u8])];
+/// Represents a static table which is either the predefined table of the HPACK
+/// specification or a custom list of header fields.
+#[derive(Debug, Clone)]
+pub enum StaticTable<'a> {
+    /// A borrowed list of header fields (e.g. `STATIC_TABLE`).
+    Borrowed(&'a [(&'a [u8], &'a [u8])]),
+
+    /// An owned custom list of header fields.
+    Owned(Vec<(Vec<u8>, Vec<u8>)>),
+}
+
+impl<'a> StaticTable<'a> {
+    /// Returns the total number of entries.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Borrowed(entries) => entries.len(),
+            Self::Owned(entries) => entries.len(),
+        }
+    }
+
+    /// Finds a header by its zero-based position in the table.
+    pub fn get(&self, index: usize) -> Option<(&[u8], &[u8])> {
+        match self {
+            Self::Borrowed(entries) => entries.get(index).copied(),
+            Self::Owned(entries) => entries.get(index).map(|h| (&h.0[..], &h.1[..])),
+        }
+    }
+}
 
 /// Represents a predefined and unchangeable list of header fields defined by
 /// the HPACK specification ([Appendix A]). The static table was created from
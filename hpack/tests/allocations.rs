@@ -0,0 +1,89 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use httlib_hpack::Encoder;
+
+/// A global allocator counting the number of performed allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// A lock which prevents tests from counting each other's allocations.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Should not allocate while encoding a plain text header with an indexed name
+/// which is not inserted into the indexing table.
+#[test]
+fn encodes_indexed_name_without_allocation() {
+    let _serial = SERIAL.lock().unwrap();
+    let mut encoder = Encoder::default();
+    let mut dst = Vec::with_capacity(64);
+
+    for flags in [0x0, 0x8] {
+        let before = ALLOCATIONS.load(Ordering::SeqCst);
+        encoder
+            .encode_indexed_name(2, b"PATCH", flags, &mut dst)
+            .unwrap();
+        assert_eq!(ALLOCATIONS.load(Ordering::SeqCst), before);
+    }
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    encoder
+        .encode_indexed_name(2, b"PATCH", 0x4, &mut dst)
+        .unwrap();
+    assert!(ALLOCATIONS.load(Ordering::SeqCst) > before); // inserted into the table
+}
+
+/// Should allocate less while indexing many headers when the capacity of the
+/// dynamic table has been reserved in advance.
+#[test]
+fn reserves_dynamic_table() {
+    let _serial = SERIAL.lock().unwrap();
+    let headers: Vec<(Vec<u8>, Vec<u8>, u8)> = (0..64)
+        .map(|i| (format!("x-{}", i).into_bytes(), b"1".to_vec(), 0x4))
+        .collect();
+    let mut dst = Vec::with_capacity(1024);
+
+    let mut encoder = Encoder::with_dynamic_size(4096);
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    for (name, value, flags) in &headers {
+        encoder
+            .encode_literal(name, value, *flags, &mut dst)
+            .unwrap();
+    }
+    let growing = ALLOCATIONS.load(Ordering::SeqCst) - before;
+
+    let mut encoder = Encoder::with_dynamic_size(4096);
+    encoder.reserve(headers.len());
+    dst.clear();
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    for (name, value, flags) in &headers {
+        encoder
+            .encode_literal(name, value, *flags, &mut dst)
+            .unwrap();
+    }
+    let reserved = ALLOCATIONS.load(Ordering::SeqCst) - before;
+
+    assert_eq!(encoder.table_entries().count(), 61 + headers.len());
+    assert_eq!(reserved, 2 * headers.len()); // only the names and values
+    assert!(reserved < growing);
+}
@@ -0,0 +1,70 @@
+//! The benchmark compares decoding many short literals with a new reader per
+//! literal against decoding them with a single reader which is reset between
+//! literals.
+//!
+//! ```txt
+//! cargo bench -p httlib-huffman --bench reuse
+//! ```
+
+extern crate httlib_huffman;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use httlib_huffman::{encode, DecodeReader, DecoderSpeed};
+
+const LITERALS: usize = 10_000;
+
+fn main() {
+    let sources: Vec<Vec<u8>> = (0..LITERALS)
+        .map(|i| {
+            let mut dst = Vec::new();
+            encode(format!("value-{}", i).as_bytes(), &mut dst).unwrap();
+            dst
+        })
+        .collect();
+
+    for speed in DecoderSpeed::known() {
+        let construct = measure(|| {
+            let mut dst = Vec::new();
+            for src in &sources {
+                let mut reader = DecodeReader::new(speed);
+                dst.clear();
+                for byte in src {
+                    reader.decode(*byte, &mut dst).unwrap();
+                }
+                reader.finalize(&mut dst).unwrap();
+                black_box(&dst);
+            }
+        });
+        let reuse = measure(|| {
+            let mut reader = DecodeReader::new(speed);
+            let mut dst = Vec::new();
+            for src in &sources {
+                reader.reset();
+                dst.clear();
+                for byte in src {
+                    reader.decode(*byte, &mut dst).unwrap();
+                }
+                reader.finalize(&mut dst).unwrap();
+                black_box(&dst);
+            }
+        });
+        println!(
+            "{:?}: construct {:?}, reuse {:?} ({} literals)",
+            speed, construct, reuse, LITERALS
+        );
+    }
+}
+
+/// Returns the shortest time of several runs of the provided function.
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    (0..10)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
@@ -0,0 +1,52 @@
+//! Provides a function for counting octet frequencies over a corpus of
+//! samples.
+//!
+//! The frequencies can be passed to the `parser::build_canonical` function for
+//! building a [canonical Huffman] table trained on the samples. Unlike the
+//! `parser` module, this module does not require `std`.
+//!
+//! [canonical Huffman]: https://en.wikipedia.org/wiki/Canonical_Huffman_code
+
+/// Counts how many times each octet appears in the provided `samples`. The
+/// result holds a frequency for each of 256 octets and the EOS symbol (index
+/// `256`), which never appears in the data thus its frequency is always `0`.
+///
+/// The frequencies can be passed to the `parser::build_canonical` function for
+/// building a Huffman table trained on the samples.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::analyze;
+///
+/// let freqs = analyze(&[b"gzip, deflate", b"text/html"]);
+/// assert_eq!(freqs[b'e' as usize], 3);
+/// assert_eq!(freqs[256], 0); // EOS
+/// ```
+pub fn analyze(samples: &[&[u8]]) -> [u64; 257] {
+    let mut freqs = [0; 257];
+    for sample in samples {
+        for byte in sample.iter() {
+            freqs[*byte as usize] += 1;
+        }
+    }
+    freqs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should count the frequencies of octets across all samples.
+    #[test]
+    fn analyzes_samples() {
+        let freqs = analyze(&[b"aab", b"", &[0, 255, 255]]);
+        assert_eq!(freqs[b'a' as usize], 2);
+        assert_eq!(freqs[b'b' as usize], 1);
+        assert_eq!(freqs[0], 1);
+        assert_eq!(freqs[255], 2);
+        assert_eq!(freqs[256], 0);
+        assert_eq!(freqs.iter().sum::<u64>(), 6);
+        assert_eq!(analyze(&[]), [0; 257]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{error, fmt};
+use core::fmt;
 
 /// Contains error options that can be encountered while performing the decoding
 /// operations.
@@ -7,14 +7,91 @@ pub enum DecoderError {
     /// Indicates that the decoder received an invalid Huffman code. This should
     /// never happen in the input is encoded according to the HPACK spec.
     InvalidInput,
+
+    /// Indicates that the sequence ends with a padding of the most significant
+    /// bits of the EOS symbol which is longer than 7 bits. It holds the offset
+    /// of the byte where the failure was detected.
+    InvalidPadding(usize),
+
+    /// Indicates that the EOS symbol has been found in the sequence. It holds
+    /// the offset of the byte where the failure was detected.
+    EosEncountered(usize),
+
+    /// Indicates that the sequence ends with bits which do not correspond to
+    /// the most significant bits of the EOS symbol, thus with an incomplete
+    /// code. It holds the offset of the byte where the failure was detected.
+    IncompleteCode(usize),
+
+    /// Indicates that the decoded sequence would exceed the maximum allowed
+    /// length.
+    LimitExceeded,
+
+    /// Indicates that the decoded sequence is not a valid UTF-8 string.
+    InvalidUtf8,
+
+    /// Indicates that reading the encoded sequence from the source failed. It
+    /// holds the kind of the I/O error, which tells e.g. a timeout from a reset
+    /// connection. The variant is available only with the `std` feature.
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+
+    /// Indicates that the decoded sequence does not fit into the provided
+    /// output buffer.
+    BufferTooSmall,
 }
 
 impl fmt::Display for DecoderError {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidInput => write!(fmt, "Invalid Huffman sequence."),
+            Self::InvalidPadding(offset) => write!(fmt, "Invalid padding at byte {}.", offset),
+            Self::EosEncountered(offset) => write!(fmt, "EOS symbol at byte {}.", offset),
+            Self::IncompleteCode(offset) => write!(fmt, "Incomplete code at byte {}.", offset),
+            Self::LimitExceeded => write!(fmt, "Decoded length exceeds limit."),
+            Self::InvalidUtf8 => write!(fmt, "Invalid UTF-8 sequence."),
+            #[cfg(feature = "std")]
+            Self::Io(kind) => write!(fmt, "Read operation failed: {}.", kind),
+            Self::BufferTooSmall => write!(fmt, "Output buffer too small."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecoderError {}
+
+/// Contains error options that can be encountered while decoding a sequence
+/// with the `decode_with_limit_detailed` function.
+#[derive(Debug, PartialEq)]
+pub enum LimitedDecoderError {
+    /// Indicates that the sequence is invalid. It holds the cause, which is
+    /// never the `LimitExceeded` error.
+    Decoder(DecoderError),
+
+    /// Indicates that the decoded sequence would exceed the maximum allowed
+    /// length. It holds the number of decoded bytes written into the output
+    /// before the limit was hit and the number of bytes of the sequence
+    /// consumed, including the byte which would exceed the limit.
+    LimitExceeded { written: usize, consumed: usize },
+}
+
+impl From<DecoderError> for LimitedDecoderError {
+    fn from(err: DecoderError) -> Self {
+        Self::Decoder(err)
+    }
+}
+
+impl fmt::Display for LimitedDecoderError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Decoder(err) => err.fmt(fmt),
+            Self::LimitExceeded { written, consumed } => write!(
+                fmt,
+                "Decoded length exceeds limit after {} bytes written and {} bytes consumed.",
+                written, consumed
+            ),
         }
     }
 }
 
-impl error::Error for DecoderError {}
+#[cfg(feature = "std")]
+impl std::error::Error for LimitedDecoderError {}
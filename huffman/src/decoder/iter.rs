@@ -0,0 +1,82 @@
+use alloc::vec::Vec;
+use core::slice;
+
+use super::{DecodeReader, DecoderError, DecoderSpeed};
+
+/// Provides an iterator which lazily decodes Huffman's sequence one byte at a
+/// time.
+///
+/// The iterator wraps the `DecodeReader` and buffers only the few bytes that
+/// a single byte of the sequence can decode into. An invalid sequence yields
+/// an error as the last item, which includes the error of the terminal
+/// padding validation.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_iter};
+///
+/// let src = vec![148, 231]; // foo
+/// let dst: Result<Vec<u8>, _> = decode_iter(&src, DecoderSpeed::FourBits).collect();
+/// assert_eq!(dst.unwrap(), b"foo");
+/// ```
+#[derive(Debug)]
+pub struct DecodeIter<'a> {
+    /// The remaining bytes of the sequence.
+    src: slice::Iter<'a, u8>,
+
+    /// The underlying reader.
+    reader: DecodeReader,
+
+    /// A buffer of decoded bytes which have not been yielded yet.
+    buf: Vec<u8>,
+
+    /// The position of the next byte to be yielded from the buffer.
+    pos: usize,
+
+    /// Whether the sequence has been finalized or an error has been yielded.
+    done: bool,
+}
+
+impl<'a> Iterator for DecodeIter<'a> {
+    type Item = Result<u8, DecoderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(byte) = self.buf.get(self.pos) {
+                self.pos += 1;
+                return Some(Ok(*byte));
+            } else if self.done {
+                return None;
+            }
+
+            self.buf.clear();
+            self.pos = 0;
+            let res = match self.src.next() {
+                Some(byte) => self.reader.decode(*byte, &mut self.buf),
+                None => {
+                    self.done = true;
+                    self.reader.finalize(&mut self.buf)
+                }
+            };
+            if let Err(err) = res {
+                self.done = true;
+                self.buf.clear();
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Returns an iterator which lazily decodes Huffman's `src` sequence. The
+/// `speed` parameter tells the decoder how many bits should be read and decoded
+/// at a time.
+pub fn decode_iter(src: &[u8], speed: DecoderSpeed) -> DecodeIter<'_> {
+    DecodeIter {
+        src: src.iter(),
+        reader: DecodeReader::new(speed),
+        buf: Vec::with_capacity(8),
+        pos: 0,
+        done: false,
+    }
+}
@@ -67,11 +67,16 @@
 //! individual character is 5 bits long, it’s optimal, for the best ratio
 //! between speed and used resources, to read 4 bits at a time. More bits at a
 //! time mean faster decoding but at the same time a larger translation table
-//! and with it a higher memory footprint.
+//! and with it a higher memory footprint. Throughput-bound applications can
+//! read 5 bits at a time with the `DecoderSpeed::FiveBits` option.
 //!
 //! [canonical Huffman]: https://en.wikipedia.org/wiki/Canonical_Huffman_code
 
+use alloc::{string::String, vec::Vec};
+
 mod error;
+mod iter;
+mod options;
 mod reader;
 mod speed;
 pub mod table1;
@@ -81,7 +86,9 @@ pub mod table4;
 pub mod table5;
 
 pub use error::*;
-use reader::*;
+pub use iter::*;
+pub use options::*;
+pub use reader::*;
 pub use speed::*;
 
 /// Decodes Huffman's `src` sequence into `dst` vector of bytes. The `speed`
@@ -99,7 +106,35 @@ pub use speed::*;
 /// decode(&src, &mut dst, speed).unwrap();
 /// ```
 pub fn decode(src: &[u8], dst: &mut Vec<u8>, speed: DecoderSpeed) -> Result<(), DecoderError> {
-    let mut reader = DecodeReader::new(speed as usize);
+    decode_with(src, dst, &DecodeOptions::new(speed))
+}
+
+/// Decodes Huffman's `src` sequence into `dst` vector of bytes as configured by
+/// the provided `options`.
+///
+/// This is the most flexible decoding function which combines the decoding
+/// speed, the limit of the decoded length and the padding tolerance. See the
+/// `DecodeOptions` for the security implications of each option.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecodeOptions, decode_with};
+///
+/// let options = DecodeOptions {
+///     lenient_padding: true,
+///     ..DecodeOptions::default()
+/// };
+/// let mut dst = Vec::new();
+/// decode_with(&[148, 231, 255], &mut dst, &options).unwrap();
+/// assert_eq!(dst, b"foo");
+/// ```
+pub fn decode_with(
+    src: &[u8],
+    dst: &mut Vec<u8>,
+    options: &DecodeOptions,
+) -> Result<(), DecoderError> {
+    let mut reader = DecodeReader::with_options(options);
 
     for byte in src {
         reader.decode(*byte, dst)?;
@@ -109,9 +144,238 @@ pub fn decode(src: &[u8], dst: &mut Vec<u8>, speed: DecoderSpeed) -> Result<(),
     Ok(())
 }
 
+/// Decodes Huffman's `src` sequence into the `dst` slice of bytes and returns
+/// the number of bytes written. The `speed` parameter is used to tell the
+/// decoder how many bits should be read and decoded at a time.
+///
+/// Nothing is allocated, which is useful in `no_std` environments and with
+/// manually managed memory. The decoded sequence is never longer than
+/// `src.len() * 8 / 5` bytes since the shortest Huffman code is 5 bits long.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_into};
+///
+/// let mut dst = [0; 8];
+/// let len = decode_into(&[148, 231], &mut dst, DecoderSpeed::FourBits).unwrap();
+/// assert_eq!(&dst[..len], b"foo");
+/// ```
+///
+/// The `BufferTooSmall` error is returned as soon as the decoded sequence does
+/// not fit into the `dst`. The content of the `dst` is unspecified in that
+/// case.
+pub fn decode_into(src: &[u8], dst: &mut [u8], speed: DecoderSpeed) -> Result<usize, DecoderError> {
+    let mut reader = DecodeReader::new(speed);
+    let mut dst = SliceOutput { dst, len: 0 };
+
+    for byte in src {
+        reader.decode_to(*byte, &mut dst)?;
+    }
+    reader.finalize_to(&mut dst)?;
+
+    Ok(dst.len)
+}
+
+/// Decodes Huffman's `src` sequence into `dst` vector of bytes exactly as the
+/// `decode` function does while tolerating padding which is up to one byte
+/// longer than the specification allows.
+///
+/// Only the length of the padding is relaxed. The padding must still consist
+/// of the most significant bits of the EOS symbol and the EOS symbol in the
+/// sequence is still rejected. See `DecodeReader::lenient` for details.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_lenient};
+///
+/// let mut dst = Vec::new();
+/// let src = vec![148, 231, 255]; // foo with 8 bits of padding
+/// decode_lenient(&src, &mut dst, DecoderSpeed::FourBits).unwrap();
+/// assert_eq!(dst, b"foo");
+/// ```
+pub fn decode_lenient(
+    src: &[u8],
+    dst: &mut Vec<u8>,
+    speed: DecoderSpeed,
+) -> Result<(), DecoderError> {
+    let options = DecodeOptions {
+        lenient_padding: true,
+        ..DecodeOptions::new(speed)
+    };
+    decode_with(src, dst, &options)
+}
+
+/// Decodes Huffman's `src` sequence into a UTF-8 string.
+///
+/// The decoded bytes are validated and moved into the returned string without
+/// copying. An invalid UTF-8 sequence results in the `InvalidUtf8` error. Use
+/// the `decode_binary` function for values which are not necessarily text.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_to_string};
+///
+/// let text = decode_to_string(&[148, 231], DecoderSpeed::FourBits).unwrap();
+/// assert_eq!(text, "foo");
+/// ```
+pub fn decode_to_string(src: &[u8], speed: DecoderSpeed) -> Result<String, DecoderError> {
+    String::from_utf8(decode_binary(src, speed)?).map_err(|_| DecoderError::InvalidUtf8)
+}
+
+/// Decodes Huffman's `src` sequence into a vector of arbitrary bytes.
+///
+/// The decoded bytes are never interpreted as UTF-8, thus the function never
+/// fails with the `InvalidUtf8` error. This is the right choice for binary
+/// header values (e.g. the `grpc-status-details-bin` header), while the
+/// `decode_to_string` function should be used when a value must be text.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_binary, encode};
+///
+/// let mut src = Vec::new();
+/// encode(&[0, 159, 255], &mut src).unwrap();
+/// let bytes = decode_binary(&src, DecoderSpeed::FourBits).unwrap();
+/// assert_eq!(bytes, vec![0, 159, 255]);
+/// ```
+pub fn decode_binary(src: &[u8], speed: DecoderSpeed) -> Result<Vec<u8>, DecoderError> {
+    let mut dst = Vec::new();
+    decode(src, &mut dst, speed)?;
+
+    Ok(dst)
+}
+
+/// Decodes Huffman's `src` sequence into `dst` vector of bytes exactly as the
+/// `decode` function does while limiting the number of decoded bytes.
+///
+/// The limit is checked incrementally, before each decoded byte is written, so
+/// the `LimitExceeded` error is returned as soon as the output would surpass
+/// `max_len` bytes. This bounds the memory used for a sequence received from an
+/// untrusted peer.
+///
+/// In HTTP/2, a header name or value can never be longer than the header list
+/// size the endpoint is willing to accept, so `max_len` should not be set above
+/// the advertised `SETTINGS_MAX_HEADER_LIST_SIZE` less the 32 octets of the
+/// header field overhead.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderError, DecoderSpeed, decode_with_limit};
+///
+/// let mut dst = Vec::new();
+/// let src = vec![148, 231]; // foo
+/// let res = decode_with_limit(&src, &mut dst, DecoderSpeed::FourBits, 2);
+/// assert_eq!(res, Err(DecoderError::LimitExceeded));
+/// ```
+pub fn decode_with_limit(
+    src: &[u8],
+    dst: &mut Vec<u8>,
+    speed: DecoderSpeed,
+    max_len: usize,
+) -> Result<(), DecoderError> {
+    let options = DecodeOptions {
+        max_len: Some(max_len),
+        ..DecodeOptions::new(speed)
+    };
+    decode_with(src, dst, &options)
+}
+
+/// Decodes Huffman's `src` sequence into `dst` vector of bytes exactly as the
+/// `decode_with_limit` function does while reporting how far the decoding got
+/// when the limit is hit.
+///
+/// The bytes decoded before the limit was hit are kept in the `dst`, and the
+/// `LimitExceeded` error holds their number together with the number of bytes
+/// of the `src` consumed. This allows for logging how much a peer tried to
+/// expand a sequence. Other failures are returned as the `Decoder` error.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, LimitedDecoderError, decode_with_limit_detailed};
+///
+/// let mut dst = Vec::new();
+/// let src = vec![148, 231]; // foo
+/// let res = decode_with_limit_detailed(&src, &mut dst, DecoderSpeed::FourBits, 1);
+/// assert_eq!(res, Err(LimitedDecoderError::LimitExceeded { written: 1, consumed: 2 }));
+/// assert_eq!(dst, b"f");
+/// ```
+pub fn decode_with_limit_detailed(
+    src: &[u8],
+    dst: &mut Vec<u8>,
+    speed: DecoderSpeed,
+    max_len: usize,
+) -> Result<(), LimitedDecoderError> {
+    let mut reader = DecodeReader::with_limit(speed, max_len);
+    let start = dst.len();
+    let exceeded = |dst: &Vec<u8>, consumed| LimitedDecoderError::LimitExceeded {
+        written: dst.len() - start,
+        consumed,
+    };
+
+    for (i, byte) in src.iter().enumerate() {
+        match reader.decode(*byte, dst) {
+            Err(DecoderError::LimitExceeded) => return Err(exceeded(dst, i + 1)),
+            res => res?,
+        }
+    }
+    match reader.finalize(dst) {
+        Err(DecoderError::LimitExceeded) => Err(exceeded(dst, src.len())),
+        res => Ok(res?),
+    }
+}
+
+/// Decodes Huffman's sequence read from the `src` reader into `dst` vector of
+/// bytes exactly as the `decode` function does.
+///
+/// The sequence is read in chunks of a fixed size, thus the whole encoded
+/// sequence is never held in memory. Interrupted reads are retried, while any
+/// other read failure results in the `Io` error.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecoderSpeed, decode_from};
+///
+/// let mut dst = Vec::new();
+/// let src: &[u8] = &[148, 231]; // foo
+/// decode_from(src, &mut dst, DecoderSpeed::FourBits).unwrap();
+/// assert_eq!(dst, b"foo");
+/// ```
+#[cfg(feature = "std")]
+pub fn decode_from<R: std::io::Read>(
+    mut src: R,
+    dst: &mut Vec<u8>,
+    speed: DecoderSpeed,
+) -> Result<(), DecoderError> {
+    let mut reader = DecodeReader::new(speed);
+    let mut buf = [0; 4096];
+
+    loop {
+        let size = match src.read(&mut buf) {
+            Ok(0) => break,
+            Ok(size) => size,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(DecoderError::Io(err.kind())),
+        };
+        for byte in &buf[..size] {
+            reader.decode(*byte, dst)?;
+        }
+    }
+    reader.finalize(dst)?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use alloc::vec;
 
     fn decode(bytes: &[u8], speed: DecoderSpeed) -> Result<Vec<u8>, DecoderError> {
         let mut dst = Vec::new();
@@ -455,25 +719,51 @@ mod test {
         )]
     }
 
-    fn invalid_encodings() -> Vec<Vec<u8>> {
+    fn invalid_encodings() -> Vec<(Vec<u8>, DecoderError)> {
+        // NOTES:
+        // * Errors hold the offset as detected when reading 1 bit at a time.
         vec![
-            vec![0, 23, 122],
-            vec![
-                73, 124, 165, 137, 211, 77, 31, 67, 174, 186, 12, 65, 164, 199, 169, 143, 51, 166,
-                154, 63, 223, 154, 104, 250, 29, 117, 208, 98, 13, 38, 61, 76, 121, 166, 143, 190,
-                208, 1, 119, 254, 190, 88, 249, 251, 237, 0, 23, 122,
-            ],
-            vec![0b11111111, 0b11111111], // EOS (padding > 7 bits)
-            vec![0b00011111, 0b11111111, 0b11111111, 0b11111111, 0b11100000], // a, EOS, +5
-            vec![
-                0b11111111, 0b10011111, 0b11111111, 0b11111111, 0b11111111, 0b10000000,
-            ], // |, EOS, +7
-            vec![0b11111111, 0b00111111, 0b11111111, 0b11111111, 0b11111111], // ?, EOS
-            vec![0b11111111, 0b11111111, 0b11111111, 0b11111100], // EOS, +2
-            vec![
-                0b11111111, 0b00111111, 0b11111111, 0b11111111, 0b11111111, 0b0,
-            ], // ?, EOS, +8
-            vec![0b11111111, 0b11111111, 0b11111111, 0b11111100, 0b0], // EOS, +10
+            (vec![0, 23, 122], DecoderError::IncompleteCode(2)),
+            (
+                vec![
+                    73, 124, 165, 137, 211, 77, 31, 67, 174, 186, 12, 65, 164, 199, 169, 143, 51,
+                    166, 154, 63, 223, 154, 104, 250, 29, 117, 208, 98, 13, 38, 61, 76, 121, 166,
+                    143, 190, 208, 1, 119, 254, 190, 88, 249, 251, 237, 0, 23, 122,
+                ],
+                DecoderError::IncompleteCode(47),
+            ),
+            (
+                vec![0b11111111, 0b11111111], // EOS (padding > 7 bits)
+                DecoderError::InvalidPadding(1),
+            ),
+            (
+                vec![0b00011111, 0b11111111, 0b11111111, 0b11111111, 0b11100000], // a, EOS, +5
+                DecoderError::EosEncountered(4),
+            ),
+            (
+                vec![
+                    0b11111111, 0b10011111, 0b11111111, 0b11111111, 0b11111111, 0b10000000,
+                ], // |, EOS, +7
+                DecoderError::EosEncountered(5),
+            ),
+            (
+                vec![0b11111111, 0b00111111, 0b11111111, 0b11111111, 0b11111111], // ?, EOS
+                DecoderError::EosEncountered(4),
+            ),
+            (
+                vec![0b11111111, 0b11111111, 0b11111111, 0b11111100], // EOS, +2
+                DecoderError::EosEncountered(3),
+            ),
+            (
+                vec![
+                    0b11111111, 0b00111111, 0b11111111, 0b11111111, 0b11111111, 0b0,
+                ], // ?, EOS, +8
+                DecoderError::EosEncountered(4),
+            ),
+            (
+                vec![0b11111111, 0b11111111, 0b11111111, 0b11111100, 0b0], // EOS, +10
+                DecoderError::EosEncountered(3),
+            ),
         ]
     }
 
@@ -502,10 +792,294 @@ mod test {
                 // passes
                 assert_eq!(data, decode(&code, speed).unwrap());
             }
-            for encoding in invalid_encodings() {
-                // throws
-                assert_eq!(Err(DecoderError::InvalidInput), decode(&encoding, speed));
+            for (encoding, err) in invalid_encodings() {
+                // throws (the offset of detection depends on the speed)
+                let res = decode(&encoding, speed).unwrap_err();
+                assert_eq!(core::mem::discriminant(&err), core::mem::discriminant(&res));
+                if speed == DecoderSpeed::OneBit {
+                    assert_eq!(err, res);
+                }
+            }
+        }
+    }
+
+    /// Should decode Huffman sequences fed byte by byte with a reader which is
+    /// reused for every sequence.
+    #[test]
+    fn decodes_incrementally() {
+        for speed in DecoderSpeed::known() {
+            let mut reader = DecodeReader::new(speed);
+            for (data, code) in valid_literals() {
+                let mut dst = Vec::new();
+                for byte in code {
+                    reader.decode(byte, &mut dst).unwrap();
+                }
+                reader.finalize(&mut dst).unwrap();
+                assert_eq!(data, dst);
             }
         }
     }
+
+    /// Should decode every byte value encoded with the Huffman encoder and
+    /// match the sequences of the valid literals produced by the encoder.
+    #[test]
+    fn round_trips_encoder() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for speed in DecoderSpeed::known() {
+            for byte in &bytes {
+                let mut code = Vec::new();
+                crate::encode(&[*byte], &mut code).unwrap();
+                assert_eq!(vec![*byte], decode(&code, speed).unwrap());
+            }
+            let mut code = Vec::new();
+            crate::encode(&bytes, &mut code).unwrap();
+            assert_eq!(bytes, decode(&code, speed).unwrap());
+        }
+        for (data, code) in valid_literals() {
+            let mut dst = Vec::new();
+            crate::encode(&data, &mut dst).unwrap();
+            assert_eq!(code, dst);
+        }
+    }
+
+    /// Should stop decoding as soon as the output would exceed the limit while
+    /// allowing the output of exactly the limit length.
+    #[test]
+    fn limits_decoded_len() {
+        for speed in DecoderSpeed::known() {
+            for (data, code) in valid_literals() {
+                let mut dst = Vec::new();
+                super::decode_with_limit(&code, &mut dst, speed, data.len()).unwrap();
+                assert_eq!(data, dst);
+                if !data.is_empty() {
+                    let mut dst = Vec::new();
+                    let res = super::decode_with_limit(&code, &mut dst, speed, data.len() - 1);
+                    assert_eq!(res, Err(DecoderError::LimitExceeded));
+                    assert_eq!(dst.len(), data.len() - 1);
+                }
+            }
+        }
+    }
+
+    /// Should decode a Huffman sequence into a string and reject an invalid
+    /// UTF-8 sequence.
+    #[test]
+    fn decodes_to_string() {
+        let mut code = Vec::new();
+        crate::encode("Žemlja".as_bytes(), &mut code).unwrap();
+        let text = super::decode_to_string(&code, DecoderSpeed::FiveBits).unwrap();
+        assert_eq!(text, "Žemlja");
+        let mut code = Vec::new();
+        crate::encode(&[0xff, 0xfe], &mut code).unwrap();
+        let res = super::decode_to_string(&code, DecoderSpeed::FiveBits);
+        assert_eq!(res, Err(DecoderError::InvalidUtf8));
+    }
+
+    /// Should lazily decode Huffman sequences and yield an error as the last
+    /// item of an invalid sequence.
+    #[test]
+    fn decodes_lazily() {
+        for speed in DecoderSpeed::known() {
+            for (data, code) in valid_literals() {
+                let dst: Result<Vec<u8>, _> = super::decode_iter(&code, speed).collect();
+                assert_eq!(data, dst.unwrap());
+            }
+            for (encoding, _) in invalid_encodings() {
+                let items: Vec<_> = super::decode_iter(&encoding, speed).collect();
+                assert!(items.last().unwrap().is_err());
+                assert_eq!(items.iter().filter(|item| item.is_err()).count(), 1);
+            }
+        }
+    }
+
+    /// Should decode a sequence read from a reader in multiple chunks and
+    /// report read failures.
+    #[cfg(feature = "std")]
+    #[test]
+    fn decodes_from_reader() {
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::TimedOut.into())
+            }
+        }
+
+        let text = "Lorem ipsum dolor sit amet. ".repeat(1000);
+        let mut src = Vec::new();
+        crate::encode(text.as_bytes(), &mut src).unwrap();
+        assert!(src.len() > 4096);
+        let mut dst = Vec::new();
+        decode_from(std::io::Cursor::new(&src), &mut dst, DecoderSpeed::FourBits).unwrap();
+        assert_eq!(dst, text.as_bytes());
+        let mut dst = Vec::new();
+        let res = decode_from(Failing, &mut dst, DecoderSpeed::FourBits);
+        assert_eq!(res, Err(DecoderError::Io(std::io::ErrorKind::TimedOut)));
+        let eos = [0xff, 0xff, 0xff, 0xff];
+        let mut dst = Vec::new();
+        let res = decode_from(&eos[..], &mut dst, DecoderSpeed::FourBits).map(|_| dst);
+        assert_eq!(res, decode(&eos, DecoderSpeed::FourBits));
+        assert!(matches!(res, Err(DecoderError::EosEncountered(_))));
+    }
+
+    /// Should decode a sequence into a slice and fail when the slice is too
+    /// small.
+    #[test]
+    fn decodes_into_slice() {
+        let expected = b"https://www.example.com/index.html".to_vec();
+        let mut src = Vec::new();
+        crate::encode(&expected, &mut src).unwrap();
+        for speed in DecoderSpeed::known() {
+            let mut dst = [0; 64];
+            let len = decode_into(&src, &mut dst, speed).unwrap();
+            assert_eq!(&dst[..len], expected.as_slice());
+            let mut dst = vec![0; expected.len()];
+            assert_eq!(decode_into(&src, &mut dst, speed), Ok(expected.len()));
+            let mut dst = vec![0; expected.len() - 1];
+            assert_eq!(
+                decode_into(&src, &mut dst, speed),
+                Err(DecoderError::BufferTooSmall)
+            );
+        }
+        assert_eq!(decode_into(&[], &mut [], DecoderSpeed::FourBits), Ok(0));
+    }
+
+    /// Should tolerate padding which is at most one byte too long only when
+    /// decoding leniently, while still rejecting invalid sequences.
+    #[test]
+    fn decodes_leniently() {
+        for speed in DecoderSpeed::known() {
+            let mut dst = Vec::new();
+            decode_lenient(&[148, 231, 255], &mut dst, speed).unwrap();
+            assert_eq!(dst, b"foo");
+            assert_eq!(
+                decode(&[148, 231, 255], speed),
+                Err(DecoderError::InvalidPadding(2))
+            );
+            let mut dst = Vec::new();
+            assert_eq!(
+                decode_lenient(&[148, 231, 255, 255], &mut dst, speed),
+                Err(DecoderError::InvalidPadding(3))
+            );
+            let mut dst = Vec::new();
+            assert_eq!(
+                decode_lenient(&[148, 231, 254], &mut dst, speed),
+                Err(DecoderError::IncompleteCode(2))
+            );
+            for (bytes, _) in invalid_encodings() {
+                let mut dst = Vec::new();
+                let res = decode_lenient(&bytes, &mut dst, speed);
+                assert_eq!(res.map(|_| dst), decode(&bytes, speed));
+            }
+        }
+    }
+
+    /// Should reuse a reader for independent sequences after resetting it,
+    /// also after a failed or abandoned sequence.
+    #[test]
+    fn resets_reader() {
+        for speed in DecoderSpeed::known() {
+            let mut reader = DecodeReader::new(speed);
+            let mut dst = Vec::new();
+            for byte in [0b11111111, 0b11111111] {
+                reader.decode(byte, &mut dst).unwrap();
+            }
+            assert!(reader.finalize(&mut dst).is_err());
+            reader.reset();
+            reader.decode(148, &mut dst).unwrap(); // abandoned
+            for (expected, bytes) in valid_literals() {
+                reader.reset();
+                let mut dst = Vec::new();
+                for byte in bytes {
+                    reader.decode(byte, &mut dst).unwrap();
+                }
+                reader.finalize(&mut dst).unwrap();
+                assert_eq!(dst, expected);
+            }
+        }
+    }
+
+    /// Should decode arbitrary bytes without interpreting them as UTF-8.
+    #[test]
+    fn decodes_binary() {
+        let expected: Vec<u8> = (0..=255).collect();
+        let mut src = Vec::new();
+        crate::encode(&expected, &mut src).unwrap();
+        for speed in DecoderSpeed::known() {
+            assert_eq!(decode_binary(&src, speed), Ok(expected.clone()));
+            assert_eq!(
+                decode_to_string(&src, speed),
+                Err(DecoderError::InvalidUtf8)
+            );
+        }
+    }
+
+    /// Should report the number of written and consumed bytes when the limit
+    /// is hit.
+    #[test]
+    fn reports_limit_exceeded() {
+        let src = vec![148, 231]; // foo
+        for speed in DecoderSpeed::known() {
+            let mut dst = Vec::new();
+            assert_eq!(decode_with_limit_detailed(&src, &mut dst, speed, 3), Ok(()));
+            assert_eq!(dst, b"foo");
+            let mut dst = b"x".to_vec();
+            let res = decode_with_limit_detailed(&src, &mut dst, speed, 0);
+            assert!(matches!(
+                res,
+                Err(LimitedDecoderError::LimitExceeded {
+                    written: 0,
+                    consumed: 1..=2, // depends on the speed
+                })
+            ));
+            assert_eq!(dst, b"x");
+            let mut dst = Vec::new();
+            let res = decode_with_limit_detailed(&[0b11111111, 0b11111111], &mut dst, speed, 3);
+            assert!(matches!(
+                res,
+                Err(LimitedDecoderError::Decoder(DecoderError::InvalidPadding(
+                    _
+                )))
+            ));
+        }
+        let mut dst = Vec::new();
+        let res = decode_with_limit_detailed(&src, &mut dst, DecoderSpeed::FourBits, 2);
+        assert_eq!(
+            res,
+            Err(LimitedDecoderError::LimitExceeded {
+                written: 2,
+                consumed: 2
+            })
+        );
+    }
+
+    /// Should decode a sequence as configured by the options.
+    #[test]
+    fn decodes_with_options() {
+        assert_eq!(
+            DecodeOptions::default(),
+            DecodeOptions::new(DecoderSpeed::FourBits)
+        );
+        let src = vec![148, 231, 255]; // foo with 8 bits of padding
+        for speed in DecoderSpeed::known() {
+            let strict = DecodeOptions::new(speed);
+            let mut dst = Vec::new();
+            let res = decode_with(&src, &mut dst, &strict);
+            assert_eq!(res, Err(DecoderError::InvalidPadding(2)));
+            let lenient = DecodeOptions {
+                lenient_padding: true,
+                ..strict
+            };
+            let mut dst = Vec::new();
+            decode_with(&src, &mut dst, &lenient).unwrap();
+            assert_eq!(dst, b"foo");
+            let limited = DecodeOptions {
+                max_len: Some(2),
+                ..lenient
+            };
+            let mut dst = Vec::new();
+            let res = decode_with(&src, &mut dst, &limited);
+            assert_eq!(res, Err(DecoderError::LimitExceeded));
+            assert_eq!(dst, b"fo");
+        }
+    }
 }
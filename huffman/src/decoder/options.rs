@@ -0,0 +1,57 @@
+use super::DecoderSpeed;
+
+/// Provides options for decoding a Huffman sequence with the `decode_with`
+/// function or the `DecodeReader`.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecodeOptions, DecoderSpeed, decode_with};
+///
+/// let options = DecodeOptions {
+///     max_len: Some(64),
+///     ..DecodeOptions::new(DecoderSpeed::FiveBits)
+/// };
+/// let mut dst = Vec::new();
+/// decode_with(&[148, 231], &mut dst, &options).unwrap();
+/// assert_eq!(dst, b"foo");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecodeOptions {
+    /// The number of bits read at a time. The speed only trades the decoding
+    /// time for the size of the translation table and has no security
+    /// implications, since every speed decodes and rejects the same sequences.
+    pub speed: DecoderSpeed,
+
+    /// The maximum allowed number of decoded bytes or `None` for no limit.
+    /// A short Huffman sequence can expand up to 8/5 times, thus the limit
+    /// should always be set for sequences received from an untrusted peer to
+    /// bound the used memory.
+    pub max_len: Option<usize>,
+
+    /// Whether the padding may be up to 15 bits long, thus one byte longer
+    /// than the specification allows, which some non-conforming encoders emit.
+    /// The padding must still consist of the most significant bits of the EOS
+    /// symbol. Accepting non-conforming input weakens the protection against
+    /// malformed sequences, thus it should only be enabled for peers known to
+    /// emit such padding.
+    pub lenient_padding: bool,
+}
+
+impl DecodeOptions {
+    /// Returns new options with the provided `speed` where the decoded length
+    /// is not limited and the padding is validated strictly.
+    pub fn new(speed: DecoderSpeed) -> Self {
+        Self {
+            speed,
+            max_len: None,
+            lenient_padding: false,
+        }
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self::new(DecoderSpeed::optimal())
+    }
+}
@@ -1,8 +1,31 @@
-use super::DecoderError;
+use alloc::vec::Vec;
+
+use super::{DecodeOptions, DecoderError, DecoderSpeed};
 
 /// Provides a mechanics for decoding Huffman sequence back to the original
 /// form.
-pub(crate) struct DecodeReader {
+///
+/// The reader decodes the sequence incrementally, one byte at a time, which is
+/// useful when the bytes arrive across multiple network reads. Once the last
+/// byte of the sequence is consumed (e.g. the length of the HPACK string
+/// literal is reached), the `finalize` method must be called to decode the
+/// remaining bits and to validate the padding.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{DecodeReader, DecoderSpeed};
+///
+/// let mut reader = DecodeReader::new(DecoderSpeed::FourBits);
+/// let mut dst = Vec::new();
+/// for byte in [148, 231] { // first network read
+///     reader.decode(byte, &mut dst).unwrap();
+/// }
+/// reader.finalize(&mut dst).unwrap(); // sequence length reached
+/// assert_eq!(dst, b"foo");
+/// ```
+#[derive(Debug)]
+pub struct DecodeReader {
     /// The number of bits that the reader should read at a time.
     speed: usize,
 
@@ -23,24 +46,97 @@ pub(crate) struct DecodeReader {
 
     /// The number of bits stored in the tail variable.
     tail_size: usize,
+
+    /// The number of bytes of the sequence received so far.
+    offset: usize,
+
+    /// The number of characters of the sequence decoded so far.
+    len: usize,
+
+    /// The maximum allowed number of decoded characters of a sequence.
+    max_len: Option<usize>,
+
+    /// Whether the padding may be up to one byte longer than the
+    /// specification allows.
+    lenient: bool,
 }
 
 impl DecodeReader {
-    /// Returns a new reader instance.
-    pub fn new(speed: usize) -> Self {
+    /// Returns a new reader instance which reads the provided number of bits at
+    /// a time.
+    pub fn new(speed: DecoderSpeed) -> Self {
+        Self::with_options(&DecodeOptions::new(speed))
+    }
+
+    /// Returns a new reader instance configured by the provided `options`.
+    pub fn with_options(options: &DecodeOptions) -> Self {
         Self {
-            speed,
+            speed: options.speed as usize,
             id: 0,
             buf: 0,
             buf_size: 0,
             tail: 0,
             tail_size: 0,
+            offset: 0,
+            len: 0,
+            max_len: options.max_len,
+            lenient: options.lenient_padding,
         }
     }
 
-    /// Decodes the entiry buffer of bits (N-bits where N=speed). If leftovers
-    /// are found in the sequence, some bits < speed will remain in the buffer.
+    /// Returns a new reader instance which reads the provided number of bits at
+    /// a time and fails with the `LimitExceeded` error as soon as a sequence
+    /// would decode into more than `max_len` characters.
+    pub fn with_limit(speed: DecoderSpeed, max_len: usize) -> Self {
+        Self::with_options(&DecodeOptions {
+            max_len: Some(max_len),
+            ..DecodeOptions::new(speed)
+        })
+    }
+
+    /// Returns a new reader instance which reads the provided number of bits at
+    /// a time and tolerates over-long padding emitted by some non-conforming
+    /// encoders.
+    ///
+    /// The padding may be up to 15 bits long, thus at most one byte longer
+    /// than the [specification] allows. The padding must still consist of the
+    /// most significant bits of the EOS symbol, otherwise the `IncompleteCode`
+    /// error is returned, and the EOS symbol in the sequence still results in
+    /// the `EosEncountered` error. No other deviations are tolerated.
+    ///
+    /// Accepting non-conforming input weakens the protection against malformed
+    /// sequences, thus the leniency should only be enabled for peers that are
+    /// known to emit such padding.
+    ///
+    /// [specification]: https://tools.ietf.org/html/rfc7541#section-5.2
+    pub fn lenient(speed: DecoderSpeed) -> Self {
+        Self::with_options(&DecodeOptions {
+            lenient_padding: true,
+            ..DecodeOptions::new(speed)
+        })
+    }
+
+    /// Decodes the next `byte` of the sequence and writes the decoded
+    /// characters into `dst`.
+    ///
+    /// The entire buffer of bits is decoded in chunks of N-bits where N=speed.
+    /// If leftovers are found in the sequence, some bits < speed will remain
+    /// in the buffer until the next byte or the `finalize` call.
+    ///
+    /// An EOS symbol found in the sequence results in the `EosEncountered`
+    /// error holding the offset of the byte where the symbol was detected.
     pub fn decode(&mut self, byte: u8, dst: &mut Vec<u8>) -> Result<(), DecoderError> {
+        self.decode_to(byte, dst)
+    }
+
+    /// Decodes the next `byte` of the sequence exactly as the `decode` method
+    /// does while writing the decoded characters into any output.
+    pub(crate) fn decode_to<O: Output>(
+        &mut self,
+        byte: u8,
+        dst: &mut O,
+    ) -> Result<(), DecoderError> {
+        self.offset += 1;
         self.buf <<= 8; // make space for new chunk
         self.buf_size += 8;
         self.buf |= byte as usize; // apply new chunk
@@ -64,10 +160,22 @@ impl DecodeReader {
     /// buffer size is extended to the remaining speed size so the last chunk
     /// can be processed by the `decode` method. The extended bits are treated
     /// as a buffer bits of value 1.
+    ///
+    /// The method also validates the trailing padding which must consist of the
+    /// most significant bits of the EOS symbol, otherwise the `IncompleteCode`
+    /// error is returned, and must be shorter than 8 bits (16 bits for a
+    /// lenient reader), otherwise the `InvalidPadding` error is returned. The
+    /// errors hold the offset of the last byte. The reader is reset afterwards
+    /// and can be reused for the next sequence.
     pub fn finalize(&mut self, dst: &mut Vec<u8>) -> Result<(), DecoderError> {
-        let shift_len = (self.buf_size as f64 / self.speed as f64).ceil() as usize
-            * self.speed as usize
-            - self.buf_size; // how much missing to chunk size
+        self.finalize_to(dst)
+    }
+
+    /// Decodes the last buffer chunk exactly as the `finalize` method does
+    /// while writing the decoded characters into any output.
+    pub(crate) fn finalize_to<O: Output>(&mut self, dst: &mut O) -> Result<(), DecoderError> {
+        // how much missing to chunk size
+        let shift_len = self.buf_size.div_ceil(self.speed) * self.speed - self.buf_size;
 
         self.buf <<= shift_len; // expand buffer to chunk size
         self.buf_size += shift_len;
@@ -91,25 +199,58 @@ impl DecodeReader {
         self.buf = 0;
         self.buf_size = 0;
 
-        if !vec![0, 1, 3, 7, 15, 31, 63, 127]
-            .iter()
-            .any(|p| *p == self.tail)
-        {
-            // validate padding
-            return Err(DecoderError::InvalidInput);
+        // validate padding
+        if self.tail != (1 << self.tail_size) - 1 {
+            return Err(DecoderError::IncompleteCode(self.position()));
+        } else if self.tail_size > if self.lenient { 15 } else { 7 } {
+            return Err(DecoderError::InvalidPadding(self.position()));
         }
 
-        self.tail = 0; // reset (make object reusable)
-        self.tail_size = 0;
+        self.reset(); // make object reusable
 
         Ok(())
     }
 
+    /// Resets the reader so it can decode the next independent sequence.
+    ///
+    /// The leftover bits, the padding state and the counters of the current
+    /// sequence are cleared while the speed, the limit and the leniency are
+    /// kept. The `finalize` method resets the reader after a successfully
+    /// decoded sequence, so resetting is needed only after an error or when
+    /// a sequence is abandoned. Reusing a reader avoids constructing a new one
+    /// for each of many short sequences.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_huffman::{DecodeReader, DecoderSpeed};
+    ///
+    /// let mut reader = DecodeReader::new(DecoderSpeed::FourBits);
+    /// let mut dst = Vec::new();
+    /// reader.decode(148, &mut dst).unwrap(); // abandoned sequence
+    /// reader.reset();
+    /// dst.clear();
+    /// for byte in [148, 231] {
+    ///     reader.decode(byte, &mut dst).unwrap();
+    /// }
+    /// reader.finalize(&mut dst).unwrap();
+    /// assert_eq!(dst, b"foo");
+    /// ```
+    pub fn reset(&mut self) {
+        self.id = 0;
+        self.buf = 0;
+        self.buf_size = 0;
+        self.tail = 0;
+        self.tail_size = 0;
+        self.offset = 0;
+        self.len = 0;
+    }
+
     /// Tries to decode the next chunk of N bits where N represents the speed.
     ///
     /// This function expects that the `buf_size` is grater or equal to 1. You
     /// should not call this function if this condition is not meet.
-    fn decode_next(&mut self, dst: &mut Vec<u8>) -> Result<(), DecoderError> {
+    fn decode_next<O: Output>(&mut self, dst: &mut O) -> Result<(), DecoderError> {
         let key = self.buf >> self.buf_size - self.speed;
         let (next_id, ascii, leftover) = self.find_target(key)?;
 
@@ -126,10 +267,13 @@ impl DecodeReader {
             self.tail_size = 0;
             if ascii < 256 {
                 // valid character
-                dst.push(ascii as u8);
-                Ok(())
+                if self.max_len == Some(self.len) {
+                    return Err(DecoderError::LimitExceeded);
+                }
+                self.len += 1;
+                dst.put(ascii as u8)
             } else {
-                Err(DecoderError::InvalidInput)
+                Err(DecoderError::EosEncountered(self.position()))
             }
         } else if let Some(next_id) = next_id {
             // transition
@@ -140,6 +284,11 @@ impl DecodeReader {
         }
     }
 
+    /// Returns the offset of the last received byte.
+    fn position(&self) -> usize {
+        self.offset.saturating_sub(1)
+    }
+
     /// Returns the translation target tuple based on reader speed.
     fn find_target(&self, key: usize) -> Result<(Option<u8>, Option<u16>, u8), DecoderError> {
         match self.speed {
@@ -181,3 +330,39 @@ impl DecodeReader {
         }
     }
 }
+
+/// Provides a destination for the decoded characters.
+pub(crate) trait Output {
+    /// Writes the decoded `byte` into the output.
+    fn put(&mut self, byte: u8) -> Result<(), DecoderError>;
+}
+
+impl Output for Vec<u8> {
+    fn put(&mut self, byte: u8) -> Result<(), DecoderError> {
+        self.push(byte);
+        Ok(())
+    }
+}
+
+/// Provides an output which writes the decoded characters into a slice of a
+/// fixed size.
+pub(crate) struct SliceOutput<'a> {
+    /// The slice of bytes which receives the decoded characters.
+    pub dst: &'a mut [u8],
+
+    /// The number of bytes written so far.
+    pub len: usize,
+}
+
+impl<'a> Output for SliceOutput<'a> {
+    fn put(&mut self, byte: u8) -> Result<(), DecoderError> {
+        match self.dst.get_mut(self.len) {
+            Some(slot) => {
+                *slot = byte;
+                self.len += 1;
+                Ok(())
+            }
+            None => Err(DecoderError::BufferTooSmall),
+        }
+    }
+}
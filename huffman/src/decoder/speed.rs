@@ -1,3 +1,5 @@
+use alloc::{vec, vec::Vec};
+
 /// Provides available decoding speed options which represent the number of bits
 /// that the decoder can read at a time.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -29,4 +31,52 @@ impl DecoderSpeed {
             DecoderSpeed::FiveBits,
         ]
     }
+
+    /// Returns the recommended decoding speed option.
+    ///
+    /// Reading 4 bits at a time gives the best ratio between speed and used
+    /// resources. The shortest Huffman code is 5 bits long, thus reading 5 bits
+    /// at a time is only slightly faster while the translation matrix doubles
+    /// in size.
+    pub fn optimal() -> Self {
+        DecoderSpeed::FourBits
+    }
+
+    /// Returns the decoding speed option which reads the provided number of
+    /// `bits` at a time or `None` if such option does not exist.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_huffman::DecoderSpeed;
+    ///
+    /// assert_eq!(DecoderSpeed::from_bits(3), Some(DecoderSpeed::ThreeBits));
+    /// assert_eq!(DecoderSpeed::from_bits(6), None);
+    /// ```
+    pub fn from_bits(bits: usize) -> Option<Self> {
+        match bits {
+            1 => Some(DecoderSpeed::OneBit),
+            2 => Some(DecoderSpeed::TwoBits),
+            3 => Some(DecoderSpeed::ThreeBits),
+            4 => Some(DecoderSpeed::FourBits),
+            5 => Some(DecoderSpeed::FiveBits),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should map each known decoding speed option to its number of bits.
+    #[test]
+    fn converts_from_bits() {
+        for speed in DecoderSpeed::known() {
+            assert_eq!(DecoderSpeed::from_bits(speed as usize), Some(speed));
+        }
+        assert_eq!(DecoderSpeed::from_bits(0), None);
+        assert_eq!(DecoderSpeed::from_bits(6), None);
+        assert_eq!(DecoderSpeed::optimal(), DecoderSpeed::FourBits);
+    }
 }
@@ -1,4 +1,4 @@
-use std::{error, fmt};
+use core::fmt;
 
 /// Contains error options that can be encountered while performing the encoding
 /// operations.
@@ -17,4 +17,5 @@ impl fmt::Display for EncoderError {
     }
 }
 
-impl error::Error for EncoderError {}
+#[cfg(feature = "std")]
+impl std::error::Error for EncoderError {}
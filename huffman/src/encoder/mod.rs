@@ -100,10 +100,16 @@
 //! [canonical Huffman]: https://en.wikipedia.org/wiki/Canonical_Huffman_code
 //! [ASCII]: https://en.wikipedia.org/wiki/ASCII
 
+use alloc::vec::Vec;
+
 mod error;
 pub mod table;
+#[cfg(feature = "std")]
+mod writer;
 
 pub use error::*;
+#[cfg(feature = "std")]
+pub use writer::*;
 
 /// Encodes the provided `src` bytes and populates the `dst` with the sequance
 /// of Huffman codes.
@@ -148,9 +154,34 @@ pub fn encode(src: &[u8], dst: &mut Vec<u8>) -> Result<(), EncoderError> {
     Ok(())
 }
 
+/// Returns the number of bytes of the Huffman sequence of the provided `src`
+/// bytes without encoding them.
+///
+/// The length is calculated by summing the code lengths of all the bytes and
+/// rounding the result up to whole bytes, which accounts for the padding. The
+/// function does not allocate and can thus be used to cheaply decide whether
+/// the Huffman encoding is worthwhile.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::encoded_len;
+///
+/// assert_eq!(encoded_len(b"Hello world!"), 10);
+/// ```
+pub fn encoded_len(src: &[u8]) -> usize {
+    let codings = self::table::ENCODE_TABLE;
+    let bits: usize = src
+        .iter()
+        .map(|&byte| codings[byte as usize].0 as usize)
+        .sum();
+    bits.div_ceil(8) // padding up to the byte boundary
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use alloc::vec;
 
     /// Should encode ASCII character into Huffman format.
     #[test]
@@ -269,4 +300,17 @@ mod test {
         encode(b"Hello world!", &mut dst).unwrap();
         assert_eq!(dst, &[198, 90, 40, 58, 158, 15, 101, 18, 127, 31,]);
     }
+
+    /// Should calculate the length of the Huffman sequence which matches the
+    /// length of the encoded bytes.
+    #[test]
+    fn calculates_encoded_len() {
+        assert_eq!(encoded_len(b""), 0);
+        for byte in 0..=255u8 {
+            let data = [byte, byte, b'a'];
+            let mut dst = Vec::new();
+            encode(&data, &mut dst).unwrap();
+            assert_eq!(encoded_len(&data), dst.len());
+        }
+    }
 }
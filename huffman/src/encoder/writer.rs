@@ -0,0 +1,129 @@
+use std::io::{self, Write};
+
+use super::table::ENCODE_TABLE;
+
+/// Provides a mechanics for encoding a stream of bytes into the Huffman
+/// sequence which is written straight into the underlying writer.
+///
+/// The writer encodes the bytes incrementally, thus the encoded sequence is
+/// never collected in memory, which is useful for writing large values into a
+/// socket. Bits of a code which do not fill the whole octet are kept until the
+/// next write. Once all the bytes are written, the `finalize` method must be
+/// called to pad the last octet with the most significant bits of the EOS
+/// symbol, exactly as the `encode` function does.
+///
+/// **Example:**
+///
+/// ```rust
+/// use std::io::Write;
+/// use httlib_huffman::EncodeWriter;
+///
+/// let mut writer = EncodeWriter::new(Vec::new());
+/// writer.write_all(b"fo").unwrap(); // first chunk
+/// writer.write_all(b"o").unwrap();
+/// let dst = writer.finalize().unwrap();
+/// assert_eq!(dst, vec![148, 231]);
+/// ```
+///
+/// A failed write leaves the sequence incomplete, thus the writer should not
+/// be used afterwards.
+#[derive(Debug)]
+pub struct EncodeWriter<W: Write> {
+    /// The underlying writer receiving the encoded octets.
+    inner: W,
+
+    /// Internal buffer of encoded bits which have not been written yet.
+    bits: u64,
+
+    /// The number of free bits in the buffer before the 40-bit boundary.
+    bits_left: u8,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Returns a new writer instance which writes the encoded sequence into
+    /// the `inner` writer.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bits: 0,
+            bits_left: 40,
+        }
+    }
+
+    /// Pads the last octet with the EOS symbol, writes it into the underlying
+    /// writer and returns the writer.
+    pub fn finalize(mut self) -> io::Result<W> {
+        if self.bits_left != 40 {
+            // finalize with EOS
+            self.bits |= (1 << self.bits_left) - 1; // add EOS and padding
+            self.inner.write_all(&[(self.bits >> 32) as u8])?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    /// Encodes all the bytes of the `buf` and writes the complete octets into
+    /// the underlying writer.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            let (code_len, code) = ENCODE_TABLE[byte as usize];
+
+            self.bits |= (code as u64) << (self.bits_left - code_len); // shift and add old and new numbers
+            self.bits_left -= code_len;
+
+            let mut octets = [0; 4];
+            let mut len = 0;
+            while self.bits_left <= 32 {
+                octets[len] = (self.bits >> 32) as u8;
+                len += 1;
+
+                self.bits <<= 8; // add more room for the next character
+                self.bits_left += 8;
+            }
+            self.inner.write_all(&octets[..len])?;
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Flushes the underlying writer. Bits which do not fill the whole octet
+    /// are kept until the `finalize` call.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Should write the same sequence as the `encode` function regardless of
+    /// how the bytes are split into chunks.
+    #[test]
+    fn encodes_incrementally() {
+        let samples: Vec<Vec<u8>> = vec![
+            b"".to_vec(),
+            b"foo".to_vec(),
+            b"!$%&A".to_vec(),
+            b"https://www.example.com/index.html".to_vec(),
+            (0..=255).collect(),
+        ];
+        for src in samples {
+            let mut expected = Vec::new();
+            crate::encode(&src, &mut expected).unwrap();
+            for chunk_size in 1..5 {
+                let mut writer = EncodeWriter::new(Vec::new());
+                for chunk in src.chunks(chunk_size) {
+                    writer.write_all(chunk).unwrap();
+                }
+                let dst = writer.finalize().unwrap();
+                assert_eq!(dst, expected);
+                let mut decoded = Vec::new();
+                crate::decode(&dst, &mut decoded, crate::DecoderSpeed::FourBits).unwrap();
+                assert_eq!(decoded, src);
+            }
+        }
+    }
+}
@@ -120,7 +120,40 @@
 //! [HPACK]: https://tools.ietf.org/html/rfc7541
 //! [ASCII]: https://en.wikipedia.org/wiki/ASCII
 
-use crate::DecoderSpeed;
+use crate::{encoder::table::ENCODE_TABLE, DecoderSpeed};
+
+/// Represents a translation matrix generated by the `flatten` function.
+///
+/// Each row of the matrix represents a node of the Huffman tree where the row
+/// `0` is the root. The position of the row in the matrix is its `ID`. A row
+/// holds `2^N` transitions, one for every value of the `N` bits read at a
+/// time, thus the index of the transition is the chunk of bits read. Each
+/// transition is a tuple of:
+///
+/// * the `ID` of the next row, which is `None` when a symbol is decoded;
+/// * the decoded symbol (`SYM`) or `None` when more bits are needed;
+/// * the number of leftover bits (`LFT`) which belong to the next code.
+///
+/// The layout is the same as the one of the `DECODE_TABLE` constants in the
+/// `decoder` module.
+pub type FlattenTable = Vec<Vec<(Option<u8>, Option<u16>, u8)>>;
+
+/// Generates the translation matrix of the HPACK's static Huffman table for
+/// the provided `speed`. The result is equal to the matching `DECODE_TABLE`
+/// constant of the `decoder` module.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::DecoderSpeed;
+/// use httlib_huffman::flattener::build;
+///
+/// let table = build(DecoderSpeed::FourBits);
+/// assert_eq!(table.len(), 54);
+/// ```
+pub fn build(speed: DecoderSpeed) -> FlattenTable {
+    flatten(&ENCODE_TABLE, speed)
+}
 
 /// Generates a translation matrix that can be used to decode an encoded
 /// content. The function expects the `speed` attribute which represents the
@@ -138,15 +171,15 @@ use crate::DecoderSpeed;
 /// let speed = DecoderSpeed::FourBits; // decoder will read 4 bits at a time
 /// let table = flatten(&ENCODE_TABLE, speed);
 /// ```
-pub fn flatten(
-    codings: &[(u8, u32)],
-    speed: DecoderSpeed,
-) -> Vec<Vec<(Option<u8>, Option<u16>, u8)>> {
+///
+/// Custom Huffman tables, e.g. those built by the `parser::build_canonical`
+/// function, can be flattened the same way.
+pub fn flatten(codings: &[(u8, u32)], speed: DecoderSpeed) -> FlattenTable {
     // next_id, ascii, leftover
     let speed = speed as usize;
     let blank_transition = generate_blank_transition(speed);
 
-    let mut table: Vec<Vec<(Option<u8>, Option<u16>, u8)>> = Vec::new();
+    let mut table: FlattenTable = Vec::new();
     table.push(blank_transition.clone());
 
     for (ascii, coding) in codings.iter().enumerate() {
@@ -318,6 +351,48 @@ mod test {
         assert_eq!(target.2, 0);
     }
 
+    /// Should generate translation matrices equal to the static decoding
+    /// tables.
+    #[test]
+    fn builds_static_tables() {
+        use crate::decoder::{table1, table2, table3, table4, table5};
+        assert_eq!(
+            build(DecoderSpeed::OneBit),
+            table1::DECODE_TABLE
+                .iter()
+                .map(|r| r.to_vec())
+                .collect::<FlattenTable>()
+        );
+        assert_eq!(
+            build(DecoderSpeed::TwoBits),
+            table2::DECODE_TABLE
+                .iter()
+                .map(|r| r.to_vec())
+                .collect::<FlattenTable>()
+        );
+        assert_eq!(
+            build(DecoderSpeed::ThreeBits),
+            table3::DECODE_TABLE
+                .iter()
+                .map(|r| r.to_vec())
+                .collect::<FlattenTable>()
+        );
+        assert_eq!(
+            build(DecoderSpeed::FourBits),
+            table4::DECODE_TABLE
+                .iter()
+                .map(|r| r.to_vec())
+                .collect::<FlattenTable>()
+        );
+        assert_eq!(
+            build(DecoderSpeed::FiveBits),
+            table5::DECODE_TABLE
+                .iter()
+                .map(|r| r.to_vec())
+                .collect::<FlattenTable>()
+        );
+    }
+
     /// Should generate all key paths variants for the codings with leftover.
     #[test]
     fn generates_coding_paths() {
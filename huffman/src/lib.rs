@@ -57,6 +57,22 @@
 //! decode(&src, &mut dst, speed).unwrap();
 //! ```
 //!
+//! ## `no_std`
+//!
+//! The crate supports `no_std` environments with the `alloc` crate when the
+//! default `std` feature is disabled. The encoder (`encode`, `encoded_len`),
+//! the decoder (`decode`, `decode_with`, `decode_into`, `decode_with_limit`,
+//! `decode_with_limit_detailed`, `decode_lenient`, `decode_binary`,
+//! `decode_to_string`, `decode_iter`, `DecodeReader`), the `analyze` function
+//! and the translation tables remain available, while the `flattener` and the
+//! `parser` modules, which are used for generating the tables, the
+//! `EncodeWriter` and the `std::error::Error` implementations require `std`.
+//!
+//! ```toml
+//! [dependencies]
+//! httlib-huffman = { version = "*", default-features = false }
+//! ```
+//!
 //! ## Articles
 //!
 //! * [HPACK: Huffman encoder](https://dev.to/xpepermint/hpack-huffman-encoder-3i7c)
@@ -69,10 +85,18 @@
 //! [Huffman code]: https://en.wikipedia.org/wiki/Huffman_coding
 //! [canonical Huffman]: https://en.wikipedia.org/wiki/Canonical_Huffman_code
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod analyze;
 pub mod decoder;
 pub mod encoder;
+#[cfg(feature = "std")]
 pub mod flattener;
+#[cfg(feature = "std")]
 pub mod parser;
 
+pub use analyze::analyze;
 pub use decoder::*;
 pub use encoder::*;
@@ -11,9 +11,25 @@
 //!
 //! You will probably never use this module while developing applications.
 //!
+//! The module can also build a [canonical Huffman] code from custom symbol
+//! frequencies with the `build_canonical` function. The output has the same
+//! format as the ENCODE_TABLE constant thus it can be passed to the
+//! `flattener` module for generating a translation matrix. The frequencies can
+//! be counted over a corpus of samples with the `analyze` function.
+//!
 //! [ASCII]: https://en.wikipedia.org/wiki/ASCII
 //! [HPACK]: https://tools.ietf.org/html/rfc7541
 //! [Huffman code]: https://tools.ietf.org/html/rfc7541#appendix-B
+//! [canonical Huffman]: https://en.wikipedia.org/wiki/Canonical_Huffman_code
+
+use std::cmp::Reverse;
+
+pub use crate::analyze::analyze;
+
+/// The default maximum length (in bits) of a code built by the
+/// `build_canonical` function. This is the length of the longest code in the
+/// HPACK's static Huffman table.
+pub const DEFAULT_MAX_CODE_LEN: u8 = 30;
 
 /// Parses the HPACK's static Huffman table. The function expects data to be in
 /// format as provided by the spec (7.2).
@@ -61,6 +77,89 @@ fn parse_line(line: &str) -> (u16, u32) {
     (len, lsb)
 }
 
+/// Builds a canonical Huffman table for 256 octets and the EOS symbol (index
+/// `256`) from the provided symbol frequencies. Codes are at most
+/// `DEFAULT_MAX_CODE_LEN` bits long.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_huffman::{analyze, parser::build_canonical};
+///
+/// let mut freqs = [1; 257];
+/// freqs[b'a' as usize] = 1000;
+/// let codings = build_canonical(&freqs);
+/// assert_eq!(codings[b'a' as usize], (1, 0x0));
+/// let codings = build_canonical(&analyze(&[b"gzip, deflate"]));
+/// ```
+pub fn build_canonical(freqs: &[u64; 257]) -> [(u8, u32); 257] {
+    build_canonical_limited(freqs, DEFAULT_MAX_CODE_LEN)
+}
+
+/// Builds a canonical Huffman table from the provided symbol frequencies where
+/// codes are at most `max_len` bits long. The output contains a tuple of the
+/// number of bits and the MSB value of the code for each symbol, exactly as
+/// the ENCODE_TABLE constant.
+///
+/// Code lengths are calculated with the package-merge algorithm which finds
+/// the optimal code under the length limit. Every symbol receives a code, thus
+/// a symbol with zero frequency is treated as if it appeared once. Codes are
+/// then assigned in the canonical order, sorted by their length and symbol.
+///
+/// The function panics if the `max_len` is not between `9` (the shortest
+/// length which fits 257 symbols) and `32` bits.
+pub fn build_canonical_limited(freqs: &[u64; 257], max_len: u8) -> [(u8, u32); 257] {
+    assert!(
+        (9..=32).contains(&max_len),
+        "max_len must be between 9 and 32; {}",
+        max_len
+    );
+
+    let mut leaves: Vec<(u128, Vec<usize>)> = freqs
+        .iter()
+        .enumerate()
+        .map(|(symbol, freq)| (u128::from((*freq).max(1)), vec![symbol]))
+        .collect();
+    leaves.sort_by_key(|leaf| (leaf.0, Reverse(leaf.1[0]))); // ties favor lower symbols
+
+    // package-merge: pair the items of the previous level into packages and
+    // merge them with the leaves, once for every additional bit of length
+    let mut items = leaves.clone();
+    for _ in 1..max_len {
+        let packages = items.chunks_exact(2).map(|pair| {
+            let mut symbols = pair[0].1.clone();
+            symbols.extend_from_slice(&pair[1].1);
+            (pair[0].0 + pair[1].0, symbols)
+        });
+        let mut merged: Vec<(u128, Vec<usize>)> = leaves.iter().cloned().chain(packages).collect();
+        merged.sort_by_key(|item| item.0); // stable, leaves first
+        items = merged;
+    }
+
+    let mut lens = [0u8; 257];
+    for item in items.iter().take(2 * freqs.len() - 2) {
+        for symbol in &item.1 {
+            lens[*symbol] += 1;
+        }
+    }
+
+    let mut order: Vec<usize> = (0..freqs.len()).collect();
+    order.sort_by_key(|symbol| (lens[*symbol], *symbol));
+
+    let mut codings = [(0, 0); 257];
+    let mut code: u64 = 0;
+    let mut prev_len = lens[order[0]];
+    for symbol in order {
+        let len = lens[symbol];
+        code <<= len - prev_len;
+        codings[symbol] = (len, code as u32);
+        code += 1;
+        prev_len = len;
+    }
+
+    codings
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs, path::Path};
@@ -89,4 +188,55 @@ mod tests {
         assert_eq!(item.0, 30);
         assert_eq!(item.1, 0x3fffffff);
     }
+
+    /// Should build a complete prefix code where frequent symbols receive
+    /// shorter codes and no code exceeds the length limit.
+    #[test]
+    fn builds_canonical_table() {
+        let mut freqs = [0; 257];
+        for (symbol, freq) in freqs.iter_mut().enumerate() {
+            *freq = 1 << (symbol % 32);
+        }
+        for max_len in [9, 12, DEFAULT_MAX_CODE_LEN, 32].iter() {
+            let codings = build_canonical_limited(&freqs, *max_len);
+            let kraft: f64 = codings.iter().map(|c| 0.5f64.powi(c.0 as i32)).sum();
+            assert!((kraft - 1.0).abs() < 1e-12);
+            assert!(codings.iter().all(|c| c.0 >= 1 && c.0 <= *max_len));
+            assert!(codings[31].0 <= codings[30].0);
+            assert!(codings[31].0 <= codings[0].0);
+            for (i, a) in codings.iter().enumerate() {
+                for b in codings.iter().skip(i + 1) {
+                    let (short, long) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+                    assert_ne!(long.1 >> (long.0 - short.0), short.1); // prefix free
+                }
+            }
+        }
+
+        let codings = build_canonical(&[7; 257]);
+        assert_eq!(codings[0], (8, 0x0));
+        assert_eq!(codings[254], (8, 0xfe));
+        assert_eq!(codings[255], (9, 0x1fe));
+        assert_eq!(codings[256], (9, 0x1ff));
+        let table = crate::flattener::flatten(&codings, crate::DecoderSpeed::FourBits);
+        assert_eq!(table[0][0], (Some(1), None, 0));
+    }
+
+    /// Should build a table from the frequencies counted by the `analyze`
+    /// function, even huge ones, and flatten it into a translation matrix.
+    #[test]
+    fn builds_canonical_table_from_samples() {
+        let samples: &[&[u8]] = &[b"gzip, deflate, br", b"text/html; charset=utf-8"];
+        let mut freqs = crate::analyze(samples);
+        let codings = build_canonical(&freqs);
+        assert!(codings[b'e' as usize].0 < codings[b'~' as usize].0);
+        let kraft: f64 = codings.iter().map(|c| 0.5f64.powi(c.0 as i32)).sum();
+        assert!((kraft - 1.0).abs() < 1e-12);
+        let table = crate::flattener::flatten(&codings, crate::DecoderSpeed::FourBits);
+        assert!(!table.is_empty());
+
+        freqs.iter_mut().for_each(|freq| *freq = u64::MAX);
+        let codings = build_canonical(&freqs);
+        assert_eq!(codings[0], (8, 0x0));
+        assert_eq!(codings[256], (9, 0x1ff));
+    }
 }
@@ -20,6 +20,11 @@ pub enum DecoderError {
     /// A tag number must be unique per message and the value can be between `1`
     /// and `2^29 - 1`.
     InvalidTag,
+
+    /// Indicates that an element of a packed repeated field is incomplete or
+    /// out of the range of the field format. The `index` tells the position of
+    /// the first invalid element.
+    InvalidPackedElement { index: usize },
 }
 
 impl From<io::Error> for DecoderError {
@@ -42,6 +47,9 @@ impl fmt::Display for DecoderError {
             Self::Interrupted => write!(fmt, "Read operation interrupted."),
             Self::InputUnderflow => write!(fmt, "Not enough bytes."),
             Self::InvalidTag => write!(fmt, "Found tag with invalid number."),
+            Self::InvalidPackedElement { index } => {
+                write!(fmt, "Found invalid packed element at index {}.", index)
+            }
         }
     }
 }
@@ -0,0 +1,186 @@
+use super::{primitives::decode_varint, DecoderError, DecoderLit};
+use crate::Typ;
+
+/// Provides the schema data formats of a field which tell how to interpret the
+/// bytes of a decoded field.
+///
+/// The wire type alone can not tell whether a varint holds an `int32`, a
+/// `sint32` or a `uint32` value. The kind of the field, usually known from the
+/// message schema, resolves the ambiguity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldKind {
+    /// Represents the `bytes` and the `string` formats.
+    Bytes,
+
+    /// Represents the `bool` format.
+    Bool,
+
+    /// Represents the `int32` format.
+    Int32,
+
+    /// Represents the `int64` format.
+    Int64,
+
+    /// Represents the `uint32` format.
+    UInt32,
+
+    /// Represents the `uint64` format.
+    UInt64,
+
+    /// Represents the `sint32` format.
+    SInt32,
+
+    /// Represents the `sint64` format.
+    SInt64,
+
+    /// Represents the `fixed32` format.
+    Fixed32,
+
+    /// Represents the `fixed64` format.
+    Fixed64,
+
+    /// Represents the `sfixed32` format.
+    SFixed32,
+
+    /// Represents the `sfixed64` format.
+    SFixed64,
+
+    /// Represents the `float` format.
+    Float,
+
+    /// Represents the `double` format.
+    Double,
+}
+
+impl FieldKind {
+    /// Returns the wire type which is used for encoding the format.
+    pub fn typ(&self) -> Typ {
+        match self {
+            Self::Bytes => Typ::LengthDelimited,
+            Self::Fixed32 | Self::SFixed32 | Self::Float => Typ::Bit32,
+            Self::Fixed64 | Self::SFixed64 | Self::Double => Typ::Bit64,
+            _ => Typ::Varint,
+        }
+    }
+
+    /// Returns `true` if the decoded varint `val` fits into the range of the
+    /// format. Formats of other wire types accept any value.
+    fn contains(&self, val: u64) -> bool {
+        match self {
+            Self::Bool => val <= 1,
+            Self::Int32 => val as i64 == (val as i32) as i64, // sign extended
+            Self::UInt32 | Self::SInt32 => val <= u32::MAX as u64,
+            _ => true,
+        }
+    }
+}
+
+impl DecoderLit {
+    /// Returns the literal of the provided `kind` holding the `byt` of a field
+    /// decoded with the wire type `typ`.
+    ///
+    /// The literal is returned only if the wire type matches the kind and a
+    /// varint value fits into the range of the kind (e.g. `int32`), otherwise
+    /// the `InvalidInput` error is returned. The bytes can then be safely
+    /// converted into the actual value.
+    ///
+    /// ```rust
+    /// use httlib_protos::{DecoderLit, FieldKind, Typ};
+    ///
+    /// let lit = DecoderLit::with_kind(FieldKind::SInt32, Typ::Varint, vec![3]).unwrap();
+    /// assert_eq!(i32::from(lit), -2);
+    /// ```
+    pub fn with_kind(kind: FieldKind, typ: Typ, byt: Vec<u8>) -> Result<Self, DecoderError> {
+        if kind.typ() != typ {
+            return Err(DecoderError::InvalidInput);
+        }
+
+        if typ == Typ::Varint {
+            let mut val = 0;
+            decode_varint(&byt, &mut val)?;
+            if !kind.contains(val) {
+                return Err(DecoderError::InvalidInput);
+            }
+        }
+
+        Ok(match kind {
+            FieldKind::Bytes => Self::Bytes(byt),
+            FieldKind::Bool => Self::Bool(byt),
+            FieldKind::Int32 => Self::Int32(byt),
+            FieldKind::Int64 => Self::Int64(byt),
+            FieldKind::UInt32 => Self::UInt32(byt),
+            FieldKind::UInt64 => Self::UInt64(byt),
+            FieldKind::SInt32 => Self::SInt32(byt),
+            FieldKind::SInt64 => Self::SInt64(byt),
+            FieldKind::Fixed32 => Self::Fixed32(byt),
+            FieldKind::Fixed64 => Self::Fixed64(byt),
+            FieldKind::SFixed32 => Self::SFixed32(byt),
+            FieldKind::SFixed64 => Self::SFixed64(byt),
+            FieldKind::Float => Self::Float(byt),
+            FieldKind::Double => Self::Double(byt),
+        })
+    }
+
+    /// Returns the packed repeated literal of the provided `kind` holding the
+    /// `byt` of a length-delimited field.
+    ///
+    /// Each element of the packed field is validated exactly as a single value
+    /// is validated by the `with_kind` function. The first incomplete element
+    /// or element out of the range of the kind is reported with the
+    /// `InvalidPackedElement` error. The `InvalidInput` error is returned for
+    /// the `bytes` kind which can not be packed.
+    ///
+    /// ```rust
+    /// use httlib_protos::{DecoderError, DecoderLit, FieldKind};
+    ///
+    /// let lit = DecoderLit::with_packed_kind(FieldKind::UInt32, vec![1, 2]).unwrap();
+    /// assert_eq!(Vec::<u32>::from(lit), vec![1, 2]);
+    /// let res = DecoderLit::with_packed_kind(FieldKind::Bool, vec![1, 2]);
+    /// assert_eq!(res.unwrap_err(), DecoderError::InvalidPackedElement { index: 1 });
+    /// ```
+    pub fn with_packed_kind(kind: FieldKind, byt: Vec<u8>) -> Result<Self, DecoderError> {
+        let size = match kind.typ() {
+            Typ::Bit32 => 4,
+            Typ::Bit64 => 8,
+            Typ::Varint => 0,
+            _ => return Err(DecoderError::InvalidInput),
+        };
+
+        if size > 0 && byt.len() / size * size != byt.len() {
+            let index = byt.len() / size; // the incomplete last element
+            return Err(DecoderError::InvalidPackedElement { index });
+        }
+        let mut pos = 0;
+        let mut index = 0;
+        while size == 0 && pos < byt.len() {
+            let len = match byt[pos..].iter().take(10).position(|b| *b <= 0x7F) {
+                Some(len) => len + 1,
+                None => return Err(DecoderError::InvalidPackedElement { index }),
+            };
+            let mut val = 0;
+            decode_varint(&byt[pos..pos + len], &mut val)?;
+            if !kind.contains(val) {
+                return Err(DecoderError::InvalidPackedElement { index });
+            }
+            pos += len;
+            index += 1;
+        }
+
+        match kind {
+            FieldKind::Bool => Ok(Self::BoolVec(byt)),
+            FieldKind::Int32 => Ok(Self::Int32Vec(byt)),
+            FieldKind::Int64 => Ok(Self::Int64Vec(byt)),
+            FieldKind::UInt32 => Ok(Self::UInt32Vec(byt)),
+            FieldKind::UInt64 => Ok(Self::UInt64Vec(byt)),
+            FieldKind::SInt32 => Ok(Self::SInt32Vec(byt)),
+            FieldKind::SInt64 => Ok(Self::SInt64Vec(byt)),
+            FieldKind::Fixed32 => Ok(Self::Fixed32Vec(byt)),
+            FieldKind::Fixed64 => Ok(Self::Fixed64Vec(byt)),
+            FieldKind::SFixed32 => Ok(Self::SFixed32Vec(byt)),
+            FieldKind::SFixed64 => Ok(Self::SFixed64Vec(byt)),
+            FieldKind::Float => Ok(Self::FloatVec(byt)),
+            FieldKind::Double => Ok(Self::DoubleVec(byt)),
+            FieldKind::Bytes => Err(DecoderError::InvalidInput),
+        }
+    }
+}
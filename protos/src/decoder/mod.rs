@@ -19,12 +19,16 @@
 //! The decoder decodes a binary stream back to the original message.
 
 mod error;
+mod kind;
 mod lit;
 mod primitives;
+mod value;
 
 pub use error::*;
+pub use kind::*;
 pub use lit::*;
 use primitives::*;
+pub use value::*;
 
 use crate::Typ;
 
@@ -230,7 +234,7 @@ impl Decoder {
     /// is thrown.
     fn decode_ld_len(&mut self, buf: &mut Vec<u8>) -> Result<usize, DecoderError> {
         let mut val = 0;
-        let size = match decode_varint(&buf, &mut val) {
+        let size = match primitives::decode_varint(&buf, &mut val) {
             Ok(size) => size,
             Err(DecoderError::InputUnderflow) => return Ok(0),
             Err(e) => return Err(e),
@@ -283,10 +287,51 @@ impl<'a> Default for Decoder {
     }
 }
 
+/// Decodes a varint (LEB128 encoded number) from the beginning of the `src`
+/// and returns the number with the number of bytes read.
+///
+/// This is the format of numeric fields and lengths of length-delimited fields
+/// which can also be used for custom framing on top of the wire format.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::decode_varint;
+///
+/// assert_eq!(decode_varint(&[0x96, 0x01, 0xff]), Ok((150, 2)));
+/// ```
+///
+/// The `InputUnderflow` error is returned when the `src` ends before the last
+/// byte of the varint, and the `InvalidInput` error when the varint is longer
+/// than `10` bytes.
+pub fn decode_varint(src: &[u8]) -> Result<(u64, usize), DecoderError> {
+    let mut val = 0;
+    let size = primitives::decode_varint(src, &mut val)?;
+    Ok((val, size))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// Should round trip varints through the public encoder and decoder
+    /// functions.
+    #[test]
+    fn round_trips_varints() {
+        for val in [0, 1, 127, 128, 150, u32::MAX as u64, u64::MAX].iter() {
+            let mut dst = vec![];
+            let size = crate::encode_varint(*val, &mut dst).unwrap();
+            dst.push(0xff); // trailing data
+            assert_eq!(decode_varint(&dst), Ok((*val, size)));
+        }
+        let mut dst = vec![];
+        crate::encode_varint(u64::MAX, &mut dst).unwrap();
+        assert_eq!(dst.len(), 10);
+        assert_eq!(decode_varint(&dst[..9]), Err(DecoderError::InputUnderflow));
+        assert_eq!(decode_varint(&[]), Err(DecoderError::InputUnderflow));
+        assert_eq!(decode_varint(&[0xff; 11]), Err(DecoderError::InvalidInput));
+    }
+
     /// Should decode supported formats from Protocol Buffers bytes.
     #[test]
     fn decodes_supported() {
@@ -440,4 +485,219 @@ mod test {
         let mut src = vec![0];
         assert!(decoder.decode(&mut src, &mut dst).is_err()); // handles errors
     }
+
+    /// Should interpret the same varint bytes by the provided field kind and
+    /// reject values out of the range of the kind.
+    #[test]
+    fn decodes_with_kind() {
+        let mut decoder = Decoder::default();
+        let mut dst = vec![];
+        decoder.decode(&mut vec![8, 3], &mut dst).unwrap(); // (1, 3)
+        let (_, typ, byt) = dst.pop().unwrap();
+        let lit = DecoderLit::with_kind(FieldKind::Int32, typ, byt.clone()).unwrap();
+        assert_eq!(i32::from(lit), 3);
+        let lit = DecoderLit::with_kind(FieldKind::SInt32, typ, byt.clone()).unwrap();
+        assert_eq!(i32::from(lit), -2);
+        let res = DecoderLit::with_kind(FieldKind::Fixed32, typ, byt);
+        assert!(matches!(res, Err(DecoderError::InvalidInput))); // wire type mismatch
+        let byt = vec![128, 128, 128, 128, 16]; // 2^32
+        let res = DecoderLit::with_kind(FieldKind::UInt32, Typ::Varint, byt.clone());
+        assert!(matches!(res, Err(DecoderError::InvalidInput)));
+        let lit = DecoderLit::with_kind(FieldKind::UInt64, Typ::Varint, byt).unwrap();
+        assert_eq!(u64::from(lit), 1 << 32);
+        let byt = vec![246, 255, 255, 255, 255, 255, 255, 255, 255, 1]; // -10
+        let lit = DecoderLit::with_kind(FieldKind::Int32, Typ::Varint, byt).unwrap();
+        assert_eq!(i32::from(lit), -10);
+    }
+
+    /// Should validate each element of a packed field and report the index of
+    /// the first invalid element.
+    #[test]
+    fn decodes_with_packed_kind() {
+        let mut byt = vec![1, 0x7f]; // 1, 127
+        byt.extend(&[128, 128, 128, 128, 16]); // 2^32 overflows i32
+        byt.push(2);
+        let res = DecoderLit::with_packed_kind(FieldKind::Int32, byt.clone());
+        assert_eq!(
+            res.unwrap_err(),
+            DecoderError::InvalidPackedElement { index: 2 }
+        );
+        let lit = DecoderLit::with_packed_kind(FieldKind::Int64, byt).unwrap();
+        assert_eq!(Vec::<i64>::from(lit), vec![1, 127, 1 << 32, 2]);
+        let byt = vec![246, 255, 255, 255, 255, 255, 255, 255, 255, 1, 3]; // -10, 3
+        let lit = DecoderLit::with_packed_kind(FieldKind::Int32, byt).unwrap();
+        assert_eq!(Vec::<i32>::from(lit), vec![-10, 3]);
+        let res = DecoderLit::with_packed_kind(FieldKind::Int32, vec![3, 128]); // truncated
+        assert_eq!(
+            res.unwrap_err(),
+            DecoderError::InvalidPackedElement { index: 1 }
+        );
+        let res = DecoderLit::with_packed_kind(FieldKind::Fixed32, vec![0; 10]);
+        assert_eq!(
+            res.unwrap_err(),
+            DecoderError::InvalidPackedElement { index: 2 }
+        );
+        let res = DecoderLit::with_packed_kind(FieldKind::Bytes, vec![1]);
+        assert_eq!(res.unwrap_err(), DecoderError::InvalidInput);
+    }
+
+    /// Should decode every format supported by the encoder back to the
+    /// original value.
+    #[test]
+    fn round_trips_encoder() {
+        use crate::{Encoder, EncoderLit};
+
+        let encoder = Encoder;
+        let mut src = vec![];
+        encoder.encode((&1, &b"foo".to_vec()), &mut src).unwrap();
+        encoder.encode((&2, &true), &mut src).unwrap();
+        encoder.encode((&3, &vec![true, false]), &mut src).unwrap();
+        encoder.encode((&4, &-1i32), &mut src).unwrap();
+        encoder.encode((&5, &vec![-1i32, 2]), &mut src).unwrap();
+        encoder.encode((&6, &-1i64), &mut src).unwrap();
+        encoder.encode((&7, &vec![-1i64, 2]), &mut src).unwrap();
+        encoder.encode((&8, &u32::MAX), &mut src).unwrap();
+        encoder
+            .encode((&9, &vec![1u32, u32::MAX]), &mut src)
+            .unwrap();
+        encoder.encode((&10, &u64::MAX), &mut src).unwrap();
+        encoder
+            .encode((&11, &vec![1u64, u64::MAX]), &mut src)
+            .unwrap();
+        encoder.encode((&12, &1.5f32), &mut src).unwrap();
+        encoder
+            .encode((&13, &vec![1.5f32, -2.0]), &mut src)
+            .unwrap();
+        encoder.encode((&14, &1.5f64), &mut src).unwrap();
+        encoder
+            .encode((&15, &vec![1.5f64, -2.0]), &mut src)
+            .unwrap();
+        encoder
+            .encode((&16, EncoderLit::SInt32(&-150)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&17, EncoderLit::SInt32Vec(&vec![-150, 150])), &mut src)
+            .unwrap();
+        encoder
+            .encode((&18, EncoderLit::SInt64(&i64::MIN)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&19, EncoderLit::SInt64Vec(&vec![i64::MIN, 1])), &mut src)
+            .unwrap();
+        encoder
+            .encode((&20, EncoderLit::Fixed32(&7)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&21, EncoderLit::Fixed32Vec(&vec![7, 8])), &mut src)
+            .unwrap();
+        encoder
+            .encode((&22, EncoderLit::Fixed64(&7)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&23, EncoderLit::Fixed64Vec(&vec![7, 8])), &mut src)
+            .unwrap();
+        encoder
+            .encode((&24, EncoderLit::SFixed32(&-7)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&25, EncoderLit::SFixed32Vec(&vec![-7, 8])), &mut src)
+            .unwrap();
+        encoder
+            .encode((&26, EncoderLit::SFixed64(&-7)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&27, EncoderLit::SFixed64Vec(&vec![-7, 8])), &mut src)
+            .unwrap();
+
+        let mut decoder = Decoder::default();
+        let mut dst = vec![];
+        decoder.decode(&mut src, &mut dst).unwrap();
+        assert_eq!(dst.len(), 27);
+        let mut fields = dst.into_iter().map(|(_, _, byt)| byt);
+        let mut next = || fields.next().unwrap();
+        assert_eq!(Vec::<u8>::from(DecoderLit::Bytes(next())), b"foo");
+        assert!(bool::from(DecoderLit::Bool(next())));
+        assert_eq!(
+            Vec::<bool>::from(DecoderLit::BoolVec(next())),
+            vec![true, false]
+        );
+        assert_eq!(i32::from(DecoderLit::Int32(next())), -1);
+        assert_eq!(Vec::<i32>::from(DecoderLit::Int32Vec(next())), vec![-1, 2]);
+        assert_eq!(i64::from(DecoderLit::Int64(next())), -1);
+        assert_eq!(Vec::<i64>::from(DecoderLit::Int64Vec(next())), vec![-1, 2]);
+        assert_eq!(u32::from(DecoderLit::UInt32(next())), u32::MAX);
+        assert_eq!(
+            Vec::<u32>::from(DecoderLit::UInt32Vec(next())),
+            vec![1, u32::MAX]
+        );
+        assert_eq!(u64::from(DecoderLit::UInt64(next())), u64::MAX);
+        assert_eq!(
+            Vec::<u64>::from(DecoderLit::UInt64Vec(next())),
+            vec![1, u64::MAX]
+        );
+        assert_eq!(f32::from(DecoderLit::Float(next())), 1.5);
+        assert_eq!(
+            Vec::<f32>::from(DecoderLit::FloatVec(next())),
+            vec![1.5, -2.0]
+        );
+        assert_eq!(f64::from(DecoderLit::Double(next())), 1.5);
+        assert_eq!(
+            Vec::<f64>::from(DecoderLit::DoubleVec(next())),
+            vec![1.5, -2.0]
+        );
+        assert_eq!(i32::from(DecoderLit::SInt32(next())), -150);
+        assert_eq!(
+            Vec::<i32>::from(DecoderLit::SInt32Vec(next())),
+            vec![-150, 150]
+        );
+        assert_eq!(i64::from(DecoderLit::SInt64(next())), i64::MIN);
+        assert_eq!(
+            Vec::<i64>::from(DecoderLit::SInt64Vec(next())),
+            vec![i64::MIN, 1]
+        );
+        assert_eq!(u32::from(DecoderLit::Fixed32(next())), 7);
+        assert_eq!(Vec::<u32>::from(DecoderLit::Fixed32Vec(next())), vec![7, 8]);
+        assert_eq!(u64::from(DecoderLit::Fixed64(next())), 7);
+        assert_eq!(Vec::<u64>::from(DecoderLit::Fixed64Vec(next())), vec![7, 8]);
+        assert_eq!(i32::from(DecoderLit::SFixed32(next())), -7);
+        assert_eq!(
+            Vec::<i32>::from(DecoderLit::SFixed32Vec(next())),
+            vec![-7, 8]
+        );
+        assert_eq!(i64::from(DecoderLit::SFixed64(next())), -7);
+        assert_eq!(
+            Vec::<i64>::from(DecoderLit::SFixed64Vec(next())),
+            vec![-7, 8]
+        );
+    }
+
+    /// Should zigzag each element of packed signed fields so that negative
+    /// and boundary values survive a round trip.
+    #[test]
+    fn round_trips_packed_signed() {
+        use crate::{Encoder, EncoderLit};
+
+        let vals32 = vec![i32::MIN, -1, 0, i32::MAX];
+        let vals64 = vec![i64::MIN, -1, 0, i64::MAX];
+        let mut src = vec![];
+        Encoder
+            .encode((&1, EncoderLit::SInt32Vec(&vals32)), &mut src)
+            .unwrap();
+        Encoder
+            .encode((&2, EncoderLit::SInt64Vec(&vals64)), &mut src)
+            .unwrap();
+        assert_eq!(
+            src[..14],
+            [10, 12, 255, 255, 255, 255, 15, 1, 0, 254, 255, 255, 255, 15]
+        );
+
+        let mut decoder = Decoder::default();
+        let mut dst = vec![];
+        decoder.decode(&mut src, &mut dst).unwrap();
+        let mut fields = dst.into_iter().map(|(_, _, byt)| byt);
+        let byt = fields.next().unwrap();
+        assert_eq!(Vec::<i32>::from(DecoderLit::SInt32Vec(byt)), vals32);
+        let byt = fields.next().unwrap();
+        assert_eq!(Vec::<i64>::from(DecoderLit::SInt64Vec(byt)), vals64);
+    }
 }
@@ -27,11 +27,15 @@ use crate::{Encoder, Typ};
 /// ```
 ///
 /// On success, the number of read bytes is returned otherwise an error is
-/// thrown.
+/// thrown. A varint longer than `10` bytes can not represent a 64-bit number
+/// and results in the `InvalidInput` error.
 pub fn decode_varint(buf: &[u8], dst: &mut u64) -> Result<usize, DecoderError> {
     let mut value: u64 = 0;
     let mut count = 0;
     loop {
+        if count == 10 {
+            return Err(DecoderError::InvalidInput);
+        }
         let byte = match buf.get(count) {
             Some(b) => *b,
             None => return Err(DecoderError::InputUnderflow),
@@ -633,6 +637,11 @@ mod test {
         let size = decode_varint(&buf, &mut dst).unwrap();
         assert_eq!(dst, 6789);
         assert_eq!(size, 2);
+        let buf = vec![0xff; 11];
+        assert_eq!(
+            decode_varint(&buf, &mut dst),
+            Err(DecoderError::InvalidInput)
+        );
     }
 
     /// Should decode a header key of a field which consists of field tag number
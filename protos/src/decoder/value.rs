@@ -0,0 +1,209 @@
+use super::{
+    primitives::{decode_fixed32, decode_fixed64, decode_key, decode_varint},
+    DecoderError,
+};
+use crate::Typ;
+
+/// The maximum number of nested messages a length-delimited field is checked
+/// for. Deeper fields are always returned as bytes.
+const MAX_DEPTH: usize = 32;
+
+/// Provides a schemaless representation of a decoded `proto3` message.
+///
+/// Without a message schema only the wire type of a field is known, thus each
+/// value is represented by the most generic data format of its wire type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProtoValue {
+    /// Represents a value of wire type `0`.
+    Varint(u64),
+
+    /// Represents a value of wire type `1`.
+    Bit64(u64),
+
+    /// Represents a value of wire type `5`.
+    Bit32(u32),
+
+    /// Represents a value of wire type `2` which is not a valid message (e.g.
+    /// a `string` or a packed repeated field).
+    Bytes(Vec<u8>),
+
+    /// Represents a message with fields in the order of their appearance.
+    /// Repeated fields appear once per value with the same tag number.
+    Message(Vec<(u32, ProtoValue)>),
+}
+
+impl ProtoValue {
+    /// Returns an iterator through all the values of a field with the `tag`
+    /// number. The iterator is empty if the value is not a message.
+    pub fn fields(&self, tag: u32) -> impl Iterator<Item = &ProtoValue> {
+        let fields = match self {
+            Self::Message(fields) => fields.as_slice(),
+            _ => &[],
+        };
+        fields.iter().filter(move |f| f.0 == tag).map(|f| &f.1)
+    }
+
+    /// Returns the first value of a field with the `tag` number.
+    pub fn field(&self, tag: u32) -> Option<&ProtoValue> {
+        self.fields(tag).next()
+    }
+}
+
+/// Decodes a `proto3` encoded message from the provided `src` without knowing
+/// its schema, similar to `protoc --decode_raw`.
+///
+/// The fields are decoded by their wire types. A length-delimited field is
+/// decoded as a nested message when its bytes are not empty and they form a
+/// sequence of fields with valid keys which exactly consumes all the bytes.
+/// Otherwise, the field is returned as bytes. Note that this is a heuristic and
+/// that a `string` or a packed field could be a valid message by accident.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::{decode_to_value, ProtoValue};
+///
+/// let value = decode_to_value(&[0x08, 0x96, 0x01]).unwrap();
+/// assert_eq!(value.field(1), Some(&ProtoValue::Varint(150)));
+/// ```
+///
+/// An error is returned if the `src` is not a complete message.
+pub fn decode_to_value(src: &[u8]) -> Result<ProtoValue, DecoderError> {
+    decode_message(src, 0).map(ProtoValue::Message)
+}
+
+/// Decodes all the fields of a message from the provided `buf` where `depth`
+/// tells the number of messages the `buf` is nested in.
+fn decode_message(buf: &[u8], depth: usize) -> Result<Vec<(u32, ProtoValue)>, DecoderError> {
+    let mut fields = Vec::new();
+    let mut size = 0;
+    while size < buf.len() {
+        let mut key = (0, Typ::Unknown);
+        check_varint(&buf[size..])?;
+        size += decode_key(&buf[size..], &mut key)?;
+        let value = match key.1 {
+            Typ::Varint => {
+                let mut val = 0;
+                check_varint(&buf[size..])?;
+                size += decode_varint(&buf[size..], &mut val)?;
+                ProtoValue::Varint(val)
+            }
+            Typ::Bit64 => {
+                let mut val = 0;
+                size += decode_fixed64(&buf[size..], &mut val)?;
+                ProtoValue::Bit64(val)
+            }
+            Typ::Bit32 => {
+                let mut val = 0;
+                size += decode_fixed32(&buf[size..], &mut val)?;
+                ProtoValue::Bit32(val)
+            }
+            Typ::LengthDelimited => {
+                let mut len = 0;
+                check_varint(&buf[size..])?;
+                size += decode_varint(&buf[size..], &mut len)?;
+                if len > (buf.len() - size) as u64 {
+                    return Err(DecoderError::InputUnderflow);
+                }
+                let bytes = &buf[size..size + len as usize];
+                size += len as usize;
+                decode_ld(bytes, depth)
+            }
+            Typ::Unknown => return Err(DecoderError::InvalidInput),
+        };
+        fields.push((key.0, value));
+    }
+    Ok(fields)
+}
+
+/// Decodes a length-delimited field as a nested message or falls back to
+/// bytes.
+fn decode_ld(bytes: &[u8], depth: usize) -> ProtoValue {
+    if bytes.is_empty() || depth >= MAX_DEPTH {
+        return ProtoValue::Bytes(bytes.to_vec());
+    }
+    match decode_message(bytes, depth + 1) {
+        Ok(fields) => ProtoValue::Message(fields),
+        Err(_) => ProtoValue::Bytes(bytes.to_vec()),
+    }
+}
+
+/// Verifies that the `buf` starts with a varint of at most `10` bytes so that
+/// decoding it can not overflow.
+fn check_varint(buf: &[u8]) -> Result<(), DecoderError> {
+    match buf.iter().take(10).position(|b| *b <= 0x7F) {
+        Some(_) => Ok(()),
+        None if buf.len() < 10 => Err(DecoderError::InputUnderflow),
+        None => Err(DecoderError::InvalidInput),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Encoder, EncoderLit};
+
+    /// Should decode a nested message built with the encoder where strings and
+    /// packed fields which are not valid messages are returned as bytes.
+    #[test]
+    fn decodes_to_value() {
+        let encoder = Encoder;
+        let mut inner = vec![];
+        encoder.encode((&1, "John"), &mut inner).unwrap();
+        encoder.encode((&2, &35u32), &mut inner).unwrap();
+        let mut src = vec![];
+        encoder.encode((&1, &150i32), &mut src).unwrap();
+        encoder.encode((&2, &inner), &mut src).unwrap();
+        encoder.encode((&2, &inner), &mut src).unwrap();
+        encoder
+            .encode((&3, EncoderLit::Fixed32(&7)), &mut src)
+            .unwrap();
+        encoder
+            .encode((&4, EncoderLit::SFixed64(&-1)), &mut src)
+            .unwrap();
+        encoder.encode((&5, "testing"), &mut src).unwrap();
+        encoder
+            .encode((&6, &vec![3u32, 270, 86942]), &mut src)
+            .unwrap();
+        encoder.encode((&7, ""), &mut src).unwrap();
+
+        let value = decode_to_value(&src).unwrap();
+        let nested = ProtoValue::Message(vec![
+            (1, ProtoValue::Bytes(b"John".to_vec())),
+            (2, ProtoValue::Varint(35)),
+        ]);
+        assert_eq!(value.field(1), Some(&ProtoValue::Varint(150)));
+        assert_eq!(value.fields(2).collect::<Vec<_>>(), vec![&nested, &nested]);
+        assert_eq!(value.field(3), Some(&ProtoValue::Bit32(7)));
+        assert_eq!(value.field(4), Some(&ProtoValue::Bit64(u64::MAX)));
+        assert_eq!(
+            value.field(5),
+            Some(&ProtoValue::Bytes(b"testing".to_vec()))
+        );
+        assert_eq!(
+            value.field(6),
+            Some(&ProtoValue::Bytes(vec![0x03, 0x8e, 0x02, 0x9e, 0xa7, 0x05]))
+        );
+        assert_eq!(value.field(7), Some(&ProtoValue::Bytes(vec![])));
+        assert_eq!(value.field(8), None);
+        assert_eq!(nested.field(2).unwrap().field(1), None);
+    }
+
+    /// Should fail to decode an incomplete or an invalid message.
+    #[test]
+    fn fails_on_invalid_message() {
+        assert_eq!(decode_to_value(&[]), Ok(ProtoValue::Message(vec![])));
+        assert_eq!(decode_to_value(&[0x08]), Err(DecoderError::InputUnderflow));
+        assert_eq!(
+            decode_to_value(&[0x12, 0x05, 0x01]),
+            Err(DecoderError::InputUnderflow)
+        );
+        assert_eq!(
+            decode_to_value(&[0x0b, 0x01]),
+            Err(DecoderError::InvalidInput)
+        );
+        let mut src = vec![0x08]; // varint longer than 10 bytes
+        src.extend_from_slice(&[0xff; 11]);
+        assert_eq!(decode_to_value(&src), Err(DecoderError::InvalidInput));
+    }
+}
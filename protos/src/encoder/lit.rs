@@ -6,6 +6,20 @@ pub enum EncoderLit<'a> {
     /// Represents `binary` format of wire type `2`.
     Bytes(&'a Vec<u8>),
 
+    /// Represents an embedded message of wire type `2`. The value holds the
+    /// already encoded fields of the message, which are written exactly as
+    /// `Bytes` with a length prefix.
+    Message(&'a Vec<u8>),
+
+    /// Represents `string` format of wire type `2`. The value is encoded as
+    /// UTF-8 bytes with a length prefix.
+    String(&'a str),
+
+    /// Represents `string` format of wire type `2` for repeated fields. Strings
+    /// can not be packed thus each element is encoded as a separate
+    /// length-delimited field with the same tag.
+    StringVec(&'a Vec<String>),
+
     /// Represents `bool` format of wire type `0`.
     Bool(&'a bool),
 
@@ -18,6 +32,15 @@ pub enum EncoderLit<'a> {
     /// Represents `int32` format of wire type `0` for packed repeated fields.
     Int32Vec(&'a Vec<i32>),
 
+    /// Represents `enum` format of wire type `0`. Enum values are encoded as
+    /// `int32` thus a negative value takes `10` bytes. Any `#[repr(i32)]` enum
+    /// can be passed by casting it with `as i32`.
+    Enum(&'a i32),
+
+    /// Represents `enum` format of wire type `0` for packed repeated fields.
+    /// Enum values are encoded as `int32`.
+    EnumVec(&'a Vec<i32>),
+
     /// Represents `int64` format of wire type `0`.
     Int64(&'a i64),
 
@@ -180,3 +203,15 @@ impl<'a> From<&'a Vec<u8>> for EncoderLit<'a> {
         Self::Bytes(v)
     }
 }
+
+impl<'a> From<&'a str> for EncoderLit<'a> {
+    fn from(v: &'a str) -> Self {
+        Self::String(v)
+    }
+}
+
+impl<'a> From<&'a Vec<String>> for EncoderLit<'a> {
+    fn from(v: &'a Vec<String>) -> Self {
+        Self::StringVec(v)
+    }
+}
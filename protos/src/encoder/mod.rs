@@ -40,6 +40,10 @@ use std::io;
 pub use error::*;
 pub use lit::*;
 use primitives::*;
+pub use primitives::{
+    encode_double, encode_fixed32, encode_fixed64, encode_float, encode_sfixed32, encode_sfixed64,
+    encode_varint,
+};
 
 use crate::Typ;
 
@@ -82,6 +86,8 @@ impl Encoder {
             EncoderLit::BoolVec(val) => self.encode_bool_vec(field.0, val, dst),
             EncoderLit::Int32(val) => self.encode_int32(field.0, val, dst),
             EncoderLit::Int32Vec(val) => self.encode_int32_vec(field.0, val, dst),
+            EncoderLit::Enum(val) => self.encode_int32(field.0, val, dst),
+            EncoderLit::EnumVec(val) => self.encode_int32_vec(field.0, val, dst),
             EncoderLit::Int64(val) => self.encode_int64(field.0, val, dst),
             EncoderLit::Int64Vec(val) => self.encode_int64_vec(field.0, val, dst),
             EncoderLit::UInt32(val) => self.encode_uint32(field.0, val, dst),
@@ -105,6 +111,9 @@ impl Encoder {
             EncoderLit::SFixed64(val) => self.encode_sfixed64(field.0, val, dst),
             EncoderLit::SFixed64Vec(val) => self.encode_sfixed64_vec(field.0, val, dst),
             EncoderLit::Bytes(val) => self.encode_bytes(field.0, val, dst),
+            EncoderLit::Message(val) => self.encode_message(field.0, val, dst),
+            EncoderLit::String(val) => self.encode_string(field.0, val, dst),
+            EncoderLit::StringVec(val) => self.encode_string_vec(field.0, val, dst),
         }
     }
 
@@ -383,6 +392,124 @@ impl Encoder {
         Ok(size)
     }
 
+    /// Encodes the provided already encoded fields `val` of a message into an
+    /// embedded message field with a specific `tag` number and writes the
+    /// resulting bytes into `dst`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_protos::Encoder;
+    ///
+    /// let encoder = Encoder::default();
+    ///
+    /// let mut child = Vec::new();
+    /// encoder.encode((&1, &150i32), &mut child).unwrap();
+    /// let mut dst = Vec::new();
+    /// encoder.encode_message(&3, &child, &mut dst).unwrap();
+    /// assert_eq!(dst, vec![0x1a, 0x03, 0x08, 0x96, 0x01]);
+    /// ```
+    ///
+    /// On success the number of written bytes is returned otherwise an error is
+    /// thrown.
+    pub fn encode_message<W>(
+        &self,
+        tag: &u32,
+        val: &[u8],
+        dst: &mut W,
+    ) -> Result<usize, EncoderError>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut size = 0;
+        size += encode_key(*tag, Typ::LengthDelimited, dst)?;
+        size += encode_bytes(val.to_vec(), dst)?;
+        Ok(size)
+    }
+
+    /// Encodes the provided `entries` into `map` field with a specific `tag`
+    /// number and writes the resulting bytes into `dst`.
+    ///
+    /// A map is encoded as a repeated embedded message where each entry holds
+    /// the key at tag number `1` and the value at tag number `2`. Entries are
+    /// written in the order of the iterator. An empty map writes nothing.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// use httlib_protos::Encoder;
+    ///
+    /// let encoder = Encoder::default();
+    ///
+    /// let mut dst = Vec::new();
+    /// let entries = vec![("a", &1i32), ("b", &2i32)];
+    /// encoder.encode_map(&1, entries, &mut dst).unwrap();
+    /// ```
+    ///
+    /// On success the number of written bytes is returned otherwise an error is
+    /// thrown.
+    pub fn encode_map<'a, K, V, I, W>(
+        &self,
+        tag: &u32,
+        entries: I,
+        dst: &mut W,
+    ) -> Result<usize, EncoderError>
+    where
+        K: Into<EncoderLit<'a>>,
+        V: Into<EncoderLit<'a>>,
+        I: IntoIterator<Item = (K, V)>,
+        W: ?Sized + io::Write,
+    {
+        let mut size = 0;
+        for (key, val) in entries {
+            let mut data = vec![];
+            self.encode((&1, key), &mut data)?;
+            self.encode((&2, val), &mut data)?;
+            size += self.encode_message(tag, &data, dst)?;
+        }
+        Ok(size)
+    }
+
+    /// Encodes the provided `val` into `string` field with a specific `tag`
+    /// number and writes the resulting bytes into `dst`.
+    ///
+    /// On success the number of written bytes is returned otherwise an error is
+    /// thrown.
+    pub fn encode_string<W>(&self, tag: &u32, val: &str, dst: &mut W) -> Result<usize, EncoderError>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut size = 0;
+        size += encode_key(*tag, Typ::LengthDelimited, dst)?;
+        size += encode_bytes(val.as_bytes().to_vec(), dst)?;
+        Ok(size)
+    }
+
+    /// Encodes the provided `vals` into repeated `string` field with a specific
+    /// `tag` number and writes the resulting bytes into `dst`.
+    ///
+    /// Unlike repeated scalar fields, strings are never packed. Each value is
+    /// written as a separate length-delimited field with the same `tag`. An
+    /// empty list writes nothing.
+    ///
+    /// On success the number of written bytes is returned otherwise an error is
+    /// thrown.
+    pub fn encode_string_vec<W>(
+        &self,
+        tag: &u32,
+        vals: &[String],
+        dst: &mut W,
+    ) -> Result<usize, EncoderError>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut size = 0;
+        for val in vals {
+            size += self.encode_string(tag, val, dst)?;
+        }
+        Ok(size)
+    }
+
     /// Encodes the provided `val` into `sint32` field with a specific `tag`
     /// number and writes the resulting bytes into `dst`.
     ///
@@ -434,6 +561,32 @@ impl Encoder {
         Ok(size)
     }
 
+    /// Encodes the provided signed `val` into either `int64` or `sint64` field
+    /// with a specific `tag` number and writes the resulting bytes into `dst`.
+    ///
+    /// When `zigzag` is `true`, the value is encoded with the ZigZag encoding
+    /// as `sint64`, otherwise it's encoded as a two's complement `int64`. This
+    /// is useful when the field type is known only at runtime.
+    ///
+    /// On success the number of written bytes is returned otherwise an error is
+    /// thrown.
+    pub fn encode_signed<W>(
+        &self,
+        tag: &u32,
+        val: &i64,
+        zigzag: bool,
+        dst: &mut W,
+    ) -> Result<usize, EncoderError>
+    where
+        W: ?Sized + io::Write,
+    {
+        if zigzag {
+            self.encode_sint64(tag, val, dst)
+        } else {
+            self.encode_int64(tag, val, dst)
+        }
+    }
+
     /// Encodes the provided `vals` into `sin64` repeated field with a
     /// specific `tag` number and writes the resulting bytes into `dst`.
     ///
@@ -626,6 +779,126 @@ impl Default for Encoder {
     }
 }
 
+/// Encodes a single `lit` field with the `field_number` into `proto3` binary
+/// format and writes the result into `dst`, which can be any writer (e.g. a
+/// file or a socket).
+///
+/// The field key (`field_number << 3 | wire_type`) is written first, followed
+/// by the value body. The wire type is selected from the variant, where packed
+/// variants (e.g. `EncoderLit::Int32Vec`) are written as length-delimited
+/// fields with a length prefix.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::{encode, EncoderLit};
+///
+/// let mut dst = Vec::new();
+/// encode(1, EncoderLit::Int32(&150), &mut dst).unwrap();
+/// assert_eq!(dst, vec![0x08, 0x96, 0x01]);
+/// ```
+///
+/// On success the number of written bytes is returned otherwise an error is
+/// thrown.
+pub fn encode<W>(field_number: u32, lit: EncoderLit, dst: &mut W) -> Result<usize, EncoderError>
+where
+    W: ?Sized + io::Write,
+{
+    Encoder.encode((&field_number, lit), dst)
+}
+
+/// Encodes a single `lit` field with the `field_number` exactly as the `encode`
+/// function does and returns the resulting bytes.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::{encode_to_vec, EncoderLit};
+///
+/// let dst = encode_to_vec(1, EncoderLit::Int32(&150)).unwrap();
+/// assert_eq!(dst, vec![0x08, 0x96, 0x01]);
+/// ```
+pub fn encode_to_vec(field_number: u32, lit: EncoderLit) -> Result<Vec<u8>, EncoderError> {
+    let mut dst = Vec::new();
+    encode(field_number, lit, &mut dst)?;
+    Ok(dst)
+}
+
+/// Encodes the provided `lits` as an unpacked repeated field with the
+/// `field_number` and writes the result into `dst`.
+///
+/// Each element is written as a separate key and value with the wire type of
+/// the scalar format, as `proto2` encodes repeated fields without the
+/// `[packed = true]` option. Packed variants (e.g. `EncoderLit::Int32Vec`) are
+/// split into their elements. Length-delimited formats (e.g. strings, bytes
+/// and messages) can not be packed, thus they are always effectively unpacked
+/// and are written exactly as the `encode` function writes them.
+///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::{encode_repeated_unpacked, EncoderLit};
+///
+/// let mut dst = Vec::new();
+/// let vals = vec![1, 150];
+/// encode_repeated_unpacked(1, vec![EncoderLit::Int32Vec(&vals)], &mut dst).unwrap();
+/// assert_eq!(dst, vec![0x08, 0x01, 0x08, 0x96, 0x01]);
+/// ```
+///
+/// On success the number of written bytes is returned otherwise an error is
+/// thrown.
+pub fn encode_repeated_unpacked<'a, I, F, W>(
+    field_number: u32,
+    lits: I,
+    dst: &mut W,
+) -> Result<usize, EncoderError>
+where
+    I: IntoIterator<Item = F>,
+    F: Into<EncoderLit<'a>>,
+    W: ?Sized + io::Write,
+{
+    let tag = &field_number;
+    let mut size = 0;
+    for lit in lits {
+        size += match lit.into() {
+            EncoderLit::BoolVec(vals) => unpack(vals, |v| Encoder.encode_bool(tag, v, dst))?,
+            EncoderLit::Int32Vec(vals) | EncoderLit::EnumVec(vals) => {
+                unpack(vals, |v| Encoder.encode_int32(tag, v, dst))?
+            }
+            EncoderLit::Int64Vec(vals) => unpack(vals, |v| Encoder.encode_int64(tag, v, dst))?,
+            EncoderLit::UInt32Vec(vals) => unpack(vals, |v| Encoder.encode_uint32(tag, v, dst))?,
+            EncoderLit::UInt64Vec(vals) => unpack(vals, |v| Encoder.encode_uint64(tag, v, dst))?,
+            EncoderLit::FloatVec(vals) => unpack(vals, |v| Encoder.encode_float(tag, v, dst))?,
+            EncoderLit::DoubleVec(vals) => unpack(vals, |v| Encoder.encode_double(tag, v, dst))?,
+            EncoderLit::SInt32Vec(vals) => unpack(vals, |v| Encoder.encode_sint32(tag, v, dst))?,
+            EncoderLit::SInt64Vec(vals) => unpack(vals, |v| Encoder.encode_sint64(tag, v, dst))?,
+            EncoderLit::Fixed32Vec(vals) => unpack(vals, |v| Encoder.encode_fixed32(tag, v, dst))?,
+            EncoderLit::Fixed64Vec(vals) => unpack(vals, |v| Encoder.encode_fixed64(tag, v, dst))?,
+            EncoderLit::SFixed32Vec(vals) => {
+                unpack(vals, |v| Encoder.encode_sfixed32(tag, v, dst))?
+            }
+            EncoderLit::SFixed64Vec(vals) => {
+                unpack(vals, |v| Encoder.encode_sfixed64(tag, v, dst))?
+            }
+            lit => Encoder.encode((tag, lit), dst)?,
+        };
+    }
+    Ok(size)
+}
+
+/// Encodes each of the `vals` with the `encode` closure and returns the total
+/// number of written bytes.
+fn unpack<T, F>(vals: &[T], mut encode: F) -> Result<usize, EncoderError>
+where
+    F: FnMut(&T) -> Result<usize, EncoderError>,
+{
+    let mut size = 0;
+    for val in vals {
+        size += encode(val)?;
+    }
+    Ok(size)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -719,4 +992,237 @@ mod test {
         );
         assert_eq!(size, 209);
     }
+
+    /// Should encode a signed number as `int64` or `sint64` based on the
+    /// `zigzag` argument.
+    #[test]
+    fn encodes_signed() {
+        let encoder = Encoder;
+        for (zigzag, lit) in [
+            (false, EncoderLit::Int64(&-5)),
+            (true, EncoderLit::SInt64(&-5)),
+        ] {
+            let mut dst = vec![];
+            let size = encoder.encode_signed(&1, &-5, zigzag, &mut dst).unwrap();
+            let mut expected = vec![];
+            encoder.encode((&1, lit), &mut expected).unwrap();
+            assert_eq!(dst, expected);
+            assert_eq!(size, expected.len());
+        }
+        let mut dst = vec![];
+        encoder.encode_signed(&1, &-5, false, &mut dst).unwrap();
+        assert_eq!(dst, vec![8, 251, 255, 255, 255, 255, 255, 255, 255, 255, 1]);
+        let mut dst = vec![];
+        encoder.encode_signed(&1, &-5, true, &mut dst).unwrap();
+        assert_eq!(dst, vec![8, 9]);
+    }
+
+    /// Should write the field key followed by the value body as generated by
+    /// `protoc` ([Encoding]).
+    ///
+    /// [Encoding]: https://protobuf.dev/programming-guides/encoding/
+    #[test]
+    fn encodes_with_key() {
+        let mut dst = vec![];
+        assert_eq!(encode(1, EncoderLit::Int32(&150), &mut dst).unwrap(), 3);
+        assert_eq!(dst, vec![0x08, 0x96, 0x01]);
+        let mut dst = vec![];
+        let bytes = b"testing".to_vec();
+        assert_eq!(encode(2, EncoderLit::Bytes(&bytes), &mut dst).unwrap(), 9);
+        assert_eq!(
+            dst,
+            vec![0x12, 0x07, 0x74, 0x65, 0x73, 0x74, 0x69, 0x6e, 0x67]
+        );
+        let mut dst = vec![];
+        let vals = vec![3, 270, 86942];
+        assert_eq!(encode(4, EncoderLit::Int32Vec(&vals), &mut dst).unwrap(), 8);
+        assert_eq!(dst, vec![0x22, 0x06, 0x03, 0x8e, 0x02, 0x9e, 0xa7, 0x05]);
+        let mut dst = vec![];
+        assert_eq!(encode(5, EncoderLit::SInt32(&-2), &mut dst).unwrap(), 2);
+        assert_eq!(dst, vec![0x28, 0x03]);
+        let mut dst = vec![];
+        assert!(encode(0, EncoderLit::Bool(&true), &mut dst).is_err());
+    }
+
+    /// Should encode strings as length-delimited fields where repeated strings
+    /// are not packed.
+    #[test]
+    fn encodes_strings() {
+        let encoder = Encoder;
+        let mut dst = vec![];
+        let size = encoder.encode((&2, "testing"), &mut dst).unwrap();
+        assert_eq!(size, 9);
+        assert_eq!(
+            dst,
+            vec![0x12, 0x07, 0x74, 0x65, 0x73, 0x74, 0x69, 0x6e, 0x67]
+        );
+        let mut dst = vec![];
+        let vals = vec!["a".to_string(), "".to_string(), "ž".to_string()];
+        let size = encoder.encode((&1, &vals), &mut dst).unwrap();
+        assert_eq!(size, 9);
+        assert_eq!(
+            dst,
+            vec![0x0a, 0x01, 0x61, 0x0a, 0x00, 0x0a, 0x02, 0xc5, 0xbe]
+        );
+        let mut dst = vec![];
+        assert_eq!(
+            encoder
+                .encode((&1, &Vec::<String>::new()), &mut dst)
+                .unwrap(),
+            0
+        );
+        assert!(dst.is_empty());
+    }
+
+    /// Should encode enum values exactly as `int32` values.
+    #[test]
+    fn encodes_enums() {
+        #[repr(i32)]
+        enum Status {
+            Active = 1,
+            Unknown = -1,
+        }
+
+        let encoder = Encoder;
+        let mut dst = vec![];
+        let size = encoder
+            .encode((&1, EncoderLit::Enum(&(Status::Active as i32))), &mut dst)
+            .unwrap();
+        assert_eq!(size, 2);
+        assert_eq!(dst, vec![0x08, 0x01]);
+        let mut dst = vec![];
+        let size = encoder
+            .encode((&1, EncoderLit::Enum(&(Status::Unknown as i32))), &mut dst)
+            .unwrap();
+        assert_eq!(size, 11);
+        let mut expected = vec![];
+        encoder.encode((&1, &-1i32), &mut expected).unwrap();
+        assert_eq!(dst, expected);
+        let mut dst = vec![];
+        let vals = vec![Status::Active as i32, 2];
+        encoder
+            .encode((&2, EncoderLit::EnumVec(&vals)), &mut dst)
+            .unwrap();
+        assert_eq!(dst, vec![0x12, 0x02, 0x01, 0x02]);
+    }
+
+    /// Should encode an embedded message where the length prefix of a long
+    /// message takes multiple bytes.
+    #[test]
+    fn encodes_messages() {
+        let encoder = Encoder;
+        let mut child = vec![];
+        let bytes = vec![7u8; 197];
+        encoder.encode((&1, &bytes), &mut child).unwrap();
+        assert_eq!(child.len(), 200);
+        let mut dst = vec![];
+        let size = encoder
+            .encode((&2, EncoderLit::Message(&child)), &mut dst)
+            .unwrap();
+        assert_eq!(size, 203);
+        assert_eq!(dst[..3], [0x12, 0xc8, 0x01]);
+        assert_eq!(dst[3..], child[..]);
+        let mut decoder = crate::Decoder::default();
+        let mut fields = vec![];
+        decoder.decode(&mut dst, &mut fields).unwrap();
+        assert_eq!(fields, vec![(2, Typ::LengthDelimited, child)]);
+    }
+
+    /// Should encode map entries as embedded messages in the order of the
+    /// iterator.
+    #[test]
+    fn encodes_maps() {
+        let encoder = Encoder;
+        let mut dst = vec![];
+        let entries = vec![
+            ("b", EncoderLit::SInt32(&-1)),
+            ("a", EncoderLit::SInt32(&1)),
+        ];
+        let size = encoder.encode_map(&3, entries, &mut dst).unwrap();
+        assert_eq!(size, 14);
+        assert_eq!(
+            dst,
+            vec![
+                0x1a, 0x05, 0x0a, 0x01, b'b', 0x10, 0x01, // b => -1
+                0x1a, 0x05, 0x0a, 0x01, b'a', 0x10, 0x02, // a => 1
+            ]
+        );
+        let mut dst = vec![];
+        let entries: Vec<(&str, &u32)> = vec![];
+        assert_eq!(encoder.encode_map(&3, entries, &mut dst).unwrap(), 0);
+        assert!(dst.is_empty());
+    }
+
+    /// Should return an error instead of writing an invalid field.
+    #[test]
+    fn fails_on_invalid_field() {
+        struct Failing;
+        impl io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("failed"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let encoder = Encoder;
+        let mut dst = vec![];
+        let res = encoder.encode((&0, &1u32), &mut dst);
+        assert_eq!(res, Err(EncoderError::InvalidTag));
+        let res = encoder.encode((&(Encoder::TAG_MAX + 1), &1u32), &mut dst);
+        assert_eq!(res, Err(EncoderError::InvalidTag));
+        assert!(dst.is_empty());
+        let res = encoder.encode((&Encoder::TAG_MAX, &1u32), &mut dst);
+        assert_eq!(res, Ok(6));
+        let res = encoder.encode((&1, &1u32), &mut Failing);
+        assert_eq!(res, Err(EncoderError::Interrupted));
+    }
+
+    /// Should encode fields directly into any writer.
+    #[test]
+    fn encodes_into_writer() {
+        let mut cursor = io::Cursor::new([0u8; 8]);
+        let size = encode(1, EncoderLit::Int32(&150), &mut cursor).unwrap();
+        assert_eq!(size, 3);
+        encode(2, EncoderLit::Bool(&true), &mut cursor).unwrap();
+        assert_eq!(cursor.position(), 5);
+        assert_eq!(cursor.get_ref()[..5], [0x08, 0x96, 0x01, 0x10, 0x01]);
+        let res = encode(3, EncoderLit::UInt64(&u64::MAX), &mut cursor);
+        assert_eq!(res, Err(EncoderError::Interrupted)); // cursor is full
+        let dst = encode_to_vec(1, EncoderLit::Int32(&150)).unwrap();
+        assert_eq!(dst, vec![0x08, 0x96, 0x01]);
+    }
+
+    /// Should encode repeated fields unpacked as `protoc` does for `proto2`
+    /// fields without the `[packed = true]` option.
+    #[test]
+    fn encodes_repeated_unpacked() {
+        let ints = vec![1, 150, -1];
+        let floats = vec![1.0f32];
+        let sints = vec![-1i32, 1];
+        let strings = vec!["a".to_string(), "b".to_string()];
+        let mut dst = vec![];
+        let size = encode_repeated_unpacked(1, vec![EncoderLit::Int32Vec(&ints)], &mut dst);
+        assert_eq!(size, Ok(16));
+        assert_eq!(
+            dst,
+            vec![
+                0x08, 0x01, 0x08, 0x96, 0x01, 0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0x01,
+            ]
+        );
+        let mut dst = vec![];
+        encode_repeated_unpacked(2, vec![EncoderLit::FloatVec(&floats)], &mut dst).unwrap();
+        assert_eq!(dst, vec![0x15, 0x00, 0x00, 0x80, 0x3f]);
+        let mut dst = vec![];
+        encode_repeated_unpacked(3, vec![EncoderLit::SInt32Vec(&sints)], &mut dst).unwrap();
+        assert_eq!(dst, vec![0x18, 0x01, 0x18, 0x02]);
+        let mut dst = vec![];
+        encode_repeated_unpacked(4, vec![&true, &false], &mut dst).unwrap();
+        assert_eq!(dst, vec![0x20, 0x01, 0x20, 0x00]);
+        let mut dst = vec![];
+        encode_repeated_unpacked(5, vec![EncoderLit::StringVec(&strings)], &mut dst).unwrap();
+        assert_eq!(dst, vec![0x2a, 0x01, 0x61, 0x2a, 0x01, 0x62]);
+    }
 }
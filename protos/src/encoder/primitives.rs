@@ -26,6 +26,16 @@ use crate::{Encoder, Typ};
 ///    10010110 00000001 ... Add MSB (1=continuation, 0=last byte).
 /// ```
 ///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::encode_varint;
+///
+/// let mut dst = Vec::new();
+/// encode_varint(150, &mut dst).unwrap();
+/// assert_eq!(dst, vec![0x96, 0x01]);
+/// ```
+///
 /// On success, the number of written bytes is returned otherwise an error is
 /// thrown.
 pub fn encode_varint<W>(mut val: u64, buf: &mut W) -> Result<usize, EncoderError>
@@ -216,6 +226,16 @@ where
 /// Use this format only when data is predictable and you know that the result
 /// will be smaller than when using the "standard" formats.
 ///
+/// **Example:**
+///
+/// ```rust
+/// use httlib_protos::encode_fixed32;
+///
+/// let mut dst = Vec::new();
+/// encode_fixed32(12345, &mut dst).unwrap();
+/// assert_eq!(dst, vec![0x39, 0x30, 0x00, 0x00]);
+/// ```
+///
 /// On success, the number of written bytes is returned otherwise an error is
 /// thrown.
 pub fn encode_fixed32<W>(val: u32, buf: &mut W) -> Result<usize, EncoderError>
@@ -565,6 +585,35 @@ mod test {
         assert_eq!(size, 4);
     }
 
+    /// Should write fixed-size numbers in little-endian byte order regardless
+    /// of the byte order of the host.
+    #[test]
+    fn encodes_fixed_little_endian() {
+        let mut dst = vec![];
+        encode_fixed32(0x01020304, &mut dst).unwrap();
+        assert_eq!(dst, vec![4, 3, 2, 1]);
+
+        dst.clear();
+        encode_fixed64(0x0102030405060708, &mut dst).unwrap();
+        assert_eq!(dst, vec![8, 7, 6, 5, 4, 3, 2, 1]);
+
+        dst.clear();
+        encode_sfixed32(-2, &mut dst).unwrap();
+        assert_eq!(dst, vec![254, 255, 255, 255]);
+
+        dst.clear();
+        encode_sfixed64(-0x0102030405060708, &mut dst).unwrap();
+        assert_eq!(dst, vec![248, 248, 249, 250, 251, 252, 253, 254]);
+
+        dst.clear();
+        encode_float(1.0, &mut dst).unwrap();
+        assert_eq!(dst, vec![0, 0, 128, 63]);
+
+        dst.clear();
+        encode_double(1.0, &mut dst).unwrap();
+        assert_eq!(dst, vec![0, 0, 0, 0, 0, 0, 240, 63]);
+    }
+
     /// Should encode a bytes value as raw `bytes` data type.
     #[test]
     fn encodes_bytes() {